@@ -0,0 +1,363 @@
+//! Proactive content negotiation (RFC 9110 section 12): `Accept`, `Accept-Encoding` and
+//! `Accept-Language` with their quality values.
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use parser::is_header_char;
+use grammar::trim_ows;
+use media_type::MediaType;
+
+/// `AcceptError` defines the errors encountered when parsing an `Accept`-family header.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum AcceptError {
+    /// A media range is not `*/*`, `type/*` or `type/subtype` with parameters
+    InvalidMediaRange,
+    /// A content coding is not a token
+    InvalidCoding,
+    /// A language range is not `*` or alphanumeric subtags of one to eight characters
+    InvalidLanguageRange,
+    /// A weight is not a number from 0 to 1 with at most three decimals
+    InvalidQValue,
+}
+
+impl fmt::Display for AcceptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AcceptError::InvalidMediaRange => write!(f, "invalid media range"),
+            AcceptError::InvalidCoding => write!(f, "invalid content coding"),
+            AcceptError::InvalidLanguageRange => write!(f, "invalid language range"),
+            AcceptError::InvalidQValue => write!(f, "invalid qvalue"),
+        }
+    }
+}
+
+/// The largest weight, 1 in thousandths.
+pub const MAX_QUALITY: u16 = 1000;
+
+// qvalue = ( "0" [ "." 0*3DIGIT ] ) / ( "1" [ "." 0*3("0") ] ), in thousandths
+fn parse_qvalue(value: &[u8]) -> Result<u16, AcceptError> {
+    let (int, frac) = match value.iter().position(|&c| c == b'.') {
+        Some(dot) => (&value[..dot], &value[dot + 1..]),
+        None => (value, &b""[..]),
+    };
+    if frac.len() > 3 || !frac.iter().all(|c| c.is_ascii_digit()) {
+        return Err(AcceptError::InvalidQValue);
+    }
+    let thousandths = frac.iter().chain(b"000".iter()).take(3)
+        .fold(0, |acc, c| acc * 10 + (c - b'0') as u16);
+    match int {
+        b"0" => Ok(thousandths),
+        b"1" if thousandths == 0 => Ok(MAX_QUALITY),
+        _ => Err(AcceptError::InvalidQValue),
+    }
+}
+
+fn write_qvalue(f: &mut fmt::Formatter, q: u16) -> fmt::Result {
+    match q {
+        MAX_QUALITY => Ok(()),
+        0 => write!(f, ";q=0"),
+        q => write!(f, ";q={}", format!("0.{:03}", q).trim_end_matches('0')),
+    }
+}
+
+// Splits `token *( OWS ";" OWS name "=" value )` into the token and its weight. Parameters
+// other than `q` are ignored.
+fn parse_weighted(element: &[u8]) -> Result<(&[u8], u16), AcceptError> {
+    let mut parts = element.split(|&c| c == b';');
+    let value = trim_ows(parts.next().unwrap_or_default());
+    let mut q = MAX_QUALITY;
+    for param in parts {
+        let param = trim_ows(param);
+        if param.len() >= 2 && param[0].eq_ignore_ascii_case(&b'q') && param[1] == b'=' {
+            q = parse_qvalue(&param[2..])?;
+        }
+    }
+    Ok((value, q))
+}
+
+// Of `available`, the first one with the highest nonzero quality.
+fn best<'a, T: ?Sized, Q: Fn(&T) -> u16>(available: &[&'a T], quality: Q) -> Option<&'a T> {
+    let mut best = None;
+    let mut best_q = 0;
+    for &item in available {
+        let q = quality(item);
+        if q > best_q {
+            best = Some(item);
+            best_q = q;
+        }
+    }
+    best
+}
+
+// Items with a nonzero weight, highest first and otherwise in order.
+fn preferred<T>(items: &[QualityItem<T>]) -> Vec<&T> {
+    let mut acceptable: Vec<&QualityItem<T>> = items.iter().filter(|i| i.q > 0).collect();
+    acceptable.sort_by_key(|i| Reverse(i.q));
+    acceptable.into_iter().map(|i| &i.item).collect()
+}
+
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter, items: &[QualityItem<T>]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item.item)?;
+        write_qvalue(f, item.q)?;
+    }
+    Ok(())
+}
+
+/// An element of an `Accept`-family list with its weight.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct QualityItem<T> {
+    /// The media range, coding or language range
+    pub item: T,
+    /// The weight in thousandths, from 0 (not acceptable) to `MAX_QUALITY`
+    pub q: u16,
+}
+
+/// The media ranges of `Accept`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let accept = Accept::parse(b"text/*;q=0.3, text/html;q=0.7, text/html;level=1, */*;q=0.5").unwrap();
+/// let html: MediaType = "text/html".parse().unwrap();
+/// let json: MediaType = "application/json".parse().unwrap();
+/// assert_eq!(accept.quality(&html), 700);
+/// assert_eq!(accept.negotiate(&[&json, &html]), Some(&html));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Accept {
+    /// The media ranges, in order. Parameters after `q` are dropped.
+    pub items: Vec<QualityItem<MediaType>>,
+}
+
+impl Accept {
+    /// Parses an `Accept` value.
+    pub fn parse(value: &[u8]) -> Result<Accept, AcceptError> {
+        let mut items = Vec::new();
+        let mut rest = value;
+        loop {
+            rest = trim_ows(rest);
+            while rest.first() == Some(&b',') {
+                rest = trim_ows(&rest[1..]);
+            }
+            if rest.is_empty() {
+                break;
+            }
+
+            let (range, after) = MediaType::parse_prefix(rest).map_err(|_| AcceptError::InvalidMediaRange)?;
+            if range.type_() == "*" && range.subtype() != "*" {
+                return Err(AcceptError::InvalidMediaRange);
+            }
+            if !after.is_empty() && after[0] != b',' {
+                return Err(AcceptError::InvalidMediaRange);
+            }
+            rest = after;
+
+            // `q` separates the media range parameters from accept-ext
+            let mut item = MediaType::new(range.type_(), range.subtype()).map_err(|_| AcceptError::InvalidMediaRange)?;
+            let mut q = MAX_QUALITY;
+            for (name, value) in range.params().iter() {
+                if name == "q" {
+                    q = parse_qvalue(value.as_bytes())?;
+                    break;
+                }
+                item.set_param(name, value);
+            }
+            items.push(QualityItem { item, q });
+        }
+        Ok(Accept { items })
+    }
+
+    /// The weight of `media_type`, from the most specific media range it matches, or 0.
+    pub fn quality(&self, media_type: &MediaType) -> u16 {
+        self.items.iter()
+            .filter(|i| media_type.matches(&i.item))
+            .max_by_key(|i| (i.item.type_() != "*", i.item.subtype() != "*", i.item.params().len()))
+            .map_or(0, |i| i.q)
+    }
+
+    /// Picks the first of `available` with the highest nonzero weight. Without media
+    /// ranges everything is acceptable, so the first is picked.
+    pub fn negotiate<'a>(&self, available: &[&'a MediaType]) -> Option<&'a MediaType> {
+        if self.items.is_empty() {
+            return available.first().cloned();
+        }
+        best(available, |media_type| self.quality(media_type))
+    }
+
+    /// The acceptable media ranges, highest weight first.
+    pub fn preferred(&self) -> Vec<&MediaType> {
+        preferred(&self.items)
+    }
+}
+
+impl fmt::Display for Accept {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, &self.items)
+    }
+}
+
+// x-gzip and x-compress are aliases of gzip and compress, RFC 9110 section 8.4.1
+fn canonical_coding(coding: &str) -> String {
+    let coding = coding.to_ascii_lowercase();
+    match &coding[..] {
+        "x-gzip" => "gzip".to_string(),
+        "x-compress" => "compress".to_string(),
+        _ => coding,
+    }
+}
+
+/// The content codings of `Accept-Encoding`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let accept = AcceptEncoding::parse(b"gzip;q=0.8, br, *;q=0").unwrap();
+/// assert_eq!(accept.negotiate(&["deflate", "gzip", "br"]), Some("br"));
+/// assert_eq!(accept.negotiate(&["deflate", "identity"]), None);
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct AcceptEncoding {
+    /// The codings in lower case, in order; `*` stands for any other coding
+    pub items: Vec<QualityItem<String>>,
+}
+
+impl AcceptEncoding {
+    /// Parses an `Accept-Encoding` value. An empty value allows only `identity`.
+    pub fn parse(value: &[u8]) -> Result<AcceptEncoding, AcceptError> {
+        let mut items = Vec::new();
+        for element in value.split(|&c| c == b',') {
+            let element = trim_ows(element);
+            if element.is_empty() {
+                continue;
+            }
+            let (coding, q) = parse_weighted(element)?;
+            if coding.is_empty() || !coding.iter().all(|&c| is_header_char(false, c)) {
+                return Err(AcceptError::InvalidCoding);
+            }
+            items.push(QualityItem { item: String::from_utf8_lossy(coding).to_ascii_lowercase(), q });
+        }
+        Ok(AcceptEncoding { items })
+    }
+
+    /// The weight of `coding`: the weight it is listed with, else that of `*`. `identity`
+    /// is otherwise acceptable with the lowest weight, unless `*;q=0` excludes it.
+    pub fn quality(&self, coding: &str) -> u16 {
+        let coding = canonical_coding(coding);
+        if let Some(item) = self.items.iter().find(|i| canonical_coding(&i.item) == coding) {
+            return item.q;
+        }
+        if let Some(item) = self.items.iter().find(|i| i.item == "*") {
+            return item.q;
+        }
+        if coding.eq_ignore_ascii_case("identity") { 1 } else { 0 }
+    }
+
+    /// Picks the first of `available` with the highest nonzero weight, or `None` if none
+    /// is acceptable, which calls for `415 Unsupported Media Type` or an unencoded
+    /// response.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        best(available, |coding| self.quality(coding))
+    }
+
+    /// The acceptable codings, highest weight first.
+    pub fn preferred(&self) -> Vec<&String> {
+        preferred(&self.items)
+    }
+}
+
+impl fmt::Display for AcceptEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, &self.items)
+    }
+}
+
+// language-range = ( 1*8ALPHA *( "-" 1*8alphanum ) ) / "*", RFC 4647 section 2.1
+fn is_language_range(range: &[u8]) -> bool {
+    if range == b"*" {
+        return true;
+    }
+    range.split(|&c| c == b'-').enumerate().all(|(i, subtag)| {
+        (1..=8).contains(&subtag.len()) &&
+            subtag.iter().all(|c| if i == 0 { c.is_ascii_alphabetic() } else { c.is_ascii_alphanumeric() })
+    })
+}
+
+/// Returns true if the language `tag` matches the basic language `range` (RFC 4647
+/// section 3.3.1): the range is `*`, equal to the tag or a prefix of it ending before a
+/// `-`, compared case-insensitively.
+pub fn language_matches(range: &str, tag: &str) -> bool {
+    let (range, tag) = (range.as_bytes(), tag.as_bytes());
+    range == b"*" || tag.len() >= range.len() && tag[..range.len()].eq_ignore_ascii_case(range) &&
+        (tag.len() == range.len() || tag[range.len()] == b'-')
+}
+
+/// The language ranges of `Accept-Language`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let accept = AcceptLanguage::parse(b"da, en-gb;q=0.8, en;q=0.7").unwrap();
+/// assert_eq!(accept.quality("en-GB"), 800);
+/// assert_eq!(accept.quality("en-US"), 700);
+/// assert_eq!(accept.negotiate(&["fr", "en-US", "en-GB"]), Some("en-GB"));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct AcceptLanguage {
+    /// The language ranges, in order
+    pub items: Vec<QualityItem<String>>,
+}
+
+impl AcceptLanguage {
+    /// Parses an `Accept-Language` value.
+    pub fn parse(value: &[u8]) -> Result<AcceptLanguage, AcceptError> {
+        let mut items = Vec::new();
+        for element in value.split(|&c| c == b',') {
+            let element = trim_ows(element);
+            if element.is_empty() {
+                continue;
+            }
+            let (range, q) = parse_weighted(element)?;
+            if !is_language_range(range) {
+                return Err(AcceptError::InvalidLanguageRange);
+            }
+            items.push(QualityItem { item: String::from_utf8_lossy(range).into_owned(), q });
+        }
+        Ok(AcceptLanguage { items })
+    }
+
+    /// The weight of the language `tag`, from the longest range that matches it, or 0.
+    pub fn quality(&self, tag: &str) -> u16 {
+        self.items.iter()
+            .filter(|i| language_matches(&i.item, tag))
+            .max_by_key(|i| if i.item == "*" { 0 } else { i.item.len() })
+            .map_or(0, |i| i.q)
+    }
+
+    /// Picks the first of `available` with the highest nonzero weight. Without language
+    /// ranges everything is acceptable, so the first is picked.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        if self.items.is_empty() {
+            return available.first().cloned();
+        }
+        best(available, |tag| self.quality(tag))
+    }
+
+    /// The acceptable language ranges, highest weight first.
+    pub fn preferred(&self) -> Vec<&String> {
+        preferred(&self.items)
+    }
+}
+
+impl fmt::Display for AcceptLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, &self.items)
+    }
+}
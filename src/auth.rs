@@ -0,0 +1,457 @@
+//! HTTP authentication (RFC 7235, RFC 9110 section 11): `Authorization`,
+//! `Proxy-Authorization`, `WWW-Authenticate` and `Proxy-Authenticate`, with the Basic
+//! (RFC 7617), Bearer (RFC 6750) and Digest (RFC 7616) schemes.
+
+use std::fmt;
+
+use base64;
+use md5::md5;
+use sha256::sha256;
+use grammar::{split_unquoted, take_quoted_string, take_token, trim_ows, write_quoted};
+use urlencoded::percent_decode;
+
+/// `AuthError` defines the errors encountered when parsing authentication headers or
+/// computing a Digest response.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum AuthError {
+    /// The value does not start with an authentication scheme
+    MissingScheme,
+    /// An auth-param or token68 is malformed
+    InvalidParameter,
+    /// A quoted string has no closing quote
+    UnterminatedQuote,
+    /// Basic credentials are not base64 of `user-id:password`
+    InvalidBasicCredentials,
+    /// Bearer credentials have no token
+    MissingToken,
+    /// A parameter the scheme requires is missing
+    MissingParameter(&'static str),
+    /// The Digest algorithm is not MD5, MD5-sess, SHA-256 or SHA-256-sess
+    UnsupportedAlgorithm,
+    /// The Digest qop is neither `auth` nor `auth-int`
+    UnsupportedQop,
+    /// The Digest `username` is hashed (`userhash=true`), so the plain user name is needed
+    HashedUsername,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AuthError::MissingScheme => write!(f, "missing authentication scheme"),
+            AuthError::InvalidParameter => write!(f, "invalid authentication parameter"),
+            AuthError::UnterminatedQuote => write!(f, "unterminated quoted string"),
+            AuthError::InvalidBasicCredentials => write!(f, "invalid Basic credentials"),
+            AuthError::MissingToken => write!(f, "missing Bearer token"),
+            AuthError::MissingParameter(name) => write!(f, "missing authentication parameter {}", name),
+            AuthError::UnsupportedAlgorithm => write!(f, "unsupported Digest algorithm"),
+            AuthError::UnsupportedQop => write!(f, "unsupported Digest qop"),
+            AuthError::HashedUsername => write!(f, "Digest username is hashed"),
+        }
+    }
+}
+
+fn is_token68_char(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric() || b"-._~+/".contains(&ch)
+}
+
+// Splits a comma-separated list into its elements, ignoring commas in quoted strings.
+fn split_list(data: &[u8]) -> Result<Vec<&[u8]>, AuthError> {
+    let elements = split_unquoted(data, b',').ok_or(AuthError::UnterminatedQuote)?;
+    Ok(elements.into_iter().filter(|e| !e.is_empty()).collect())
+}
+
+// Parses `name = value` where value is a token or quoted string.
+fn parse_param(data: &[u8]) -> Result<(String, String), AuthError> {
+    let (name, rest) = take_token(data);
+    let rest = trim_ows(rest);
+    if name.is_empty() || rest.first() != Some(&b'=') {
+        return Err(AuthError::InvalidParameter);
+    }
+    let rest = trim_ows(&rest[1..]);
+    let value = if rest.first() == Some(&b'"') {
+        let (value, after) = take_quoted_string(rest).ok_or(AuthError::UnterminatedQuote)?;
+        if !after.is_empty() {
+            return Err(AuthError::InvalidParameter);
+        }
+        value
+    } else {
+        let (value, after) = take_token(rest);
+        if value.is_empty() || !after.is_empty() {
+            return Err(AuthError::InvalidParameter);
+        }
+        value.to_vec()
+    };
+    Ok((String::from_utf8_lossy(name).to_ascii_lowercase(), String::from_utf8_lossy(&value).into_owned()))
+}
+
+fn is_token68(data: &[u8]) -> bool {
+    let n = data.iter().take_while(|&&c| is_token68_char(c)).count();
+    n > 0 && data[n..].iter().all(|&c| c == b'=')
+}
+
+// Returns true if the list element starts a new challenge or credentials: a token that
+// is not followed by `=`.
+fn starts_scheme(element: &[u8]) -> bool {
+    let (token, rest) = take_token(element);
+    !token.is_empty() && trim_ows(rest).first() != Some(&b'=')
+}
+
+// Parses a list of `scheme [ token68 / #auth-param ]`.
+fn parse_schemes(value: &[u8]) -> Result<Vec<Challenge>, AuthError> {
+    let mut schemes: Vec<Challenge> = Vec::new();
+    for element in split_list(value)? {
+        if starts_scheme(element) {
+            let (scheme, rest) = take_token(element);
+            if !rest.is_empty() && rest[0] != b' ' && rest[0] != b'\t' {
+                return Err(AuthError::InvalidParameter);
+            }
+            let mut challenge = Challenge {
+                scheme: String::from_utf8_lossy(scheme).into_owned(),
+                token68: None,
+                params: Vec::new(),
+            };
+            let rest = trim_ows(rest);
+            if !rest.is_empty() {
+                match parse_param(rest) {
+                    Ok(param) => challenge.params.push(param),
+                    Err(_) if is_token68(rest) =>
+                        challenge.token68 = Some(String::from_utf8_lossy(rest).into_owned()),
+                    Err(err) => return Err(err),
+                }
+            }
+            schemes.push(challenge);
+        } else {
+            match schemes.last_mut() {
+                Some(challenge) if challenge.token68.is_none() => challenge.params.push(parse_param(element)?),
+                Some(_) => return Err(AuthError::InvalidParameter),
+                None => return Err(AuthError::MissingScheme),
+            }
+        }
+    }
+    Ok(schemes)
+}
+
+/// A challenge of `WWW-Authenticate` or `Proxy-Authenticate`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let challenges = Challenge::parse_list(b"Newauth realm=\"apps\", type=1, \
+///                                          title=\"Login to \\\"apps\\\"\", Basic realm=\"simple\"").unwrap();
+/// assert_eq!(challenges.len(), 2);
+/// assert_eq!(challenges[0].scheme, "Newauth");
+/// assert_eq!(challenges[0].param("title"), Some("Login to \"apps\""));
+/// assert_eq!(challenges[1].realm(), Some("simple"));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Challenge {
+    /// Authentication scheme, as it appeared
+    pub scheme: String,
+    /// The token68 following the scheme, if there is one instead of parameters
+    pub token68: Option<String>,
+    /// Parameters with lower case names and unquoted values, in order
+    pub params: Vec<(String, String)>,
+}
+
+impl Challenge {
+    /// Parses the challenges of a `WWW-Authenticate` or `Proxy-Authenticate` value.
+    pub fn parse_list(value: &[u8]) -> Result<Vec<Challenge>, AuthError> {
+        parse_schemes(value)
+    }
+
+    /// Returns true if the scheme is `scheme`, compared case-insensitively.
+    pub fn is(&self, scheme: &str) -> bool {
+        self.scheme.eq_ignore_ascii_case(scheme)
+    }
+
+    /// Returns the value of the first parameter named `name`, compared case-insensitively.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|p| p.0.eq_ignore_ascii_case(name))
+            .map(|p| &p.1[..])
+    }
+
+    /// The `realm` parameter.
+    pub fn realm(&self) -> Option<&str> {
+        self.param("realm")
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.scheme)?;
+        if let Some(ref token68) = self.token68 {
+            return write!(f, " {}", token68);
+        }
+        for (i, (name, value)) in self.params.iter().enumerate() {
+            write!(f, "{}{}=", if i == 0 { " " } else { ", " }, name)?;
+            write_quoted(f, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Digest credentials (RFC 7616 section 3.4).
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct DigestCredentials {
+    /// `username`, or the decoded `username*`
+    pub username: String,
+    /// `realm`
+    pub realm: String,
+    /// `nonce` of the server challenge
+    pub nonce: String,
+    /// `uri`, the request target
+    pub uri: String,
+    /// `response`, the hex digest proving the password
+    pub response: String,
+    /// `algorithm`, MD5 if absent
+    pub algorithm: Option<String>,
+    /// `cnonce`, the client nonce
+    pub cnonce: Option<String>,
+    /// `opaque`, returned as the server sent it
+    pub opaque: Option<String>,
+    /// `qop`, the quality of protection
+    pub qop: Option<String>,
+    /// `nc`, the nonce count as 8 hex digits
+    pub nc: Option<String>,
+    /// `userhash`, whether `username` is hashed
+    pub userhash: bool,
+}
+
+enum DigestHash {
+    Md5,
+    Sha256,
+}
+
+impl DigestHash {
+    fn hex(&self, data: &[u8]) -> String {
+        let digest: Vec<u8> = match *self {
+            DigestHash::Md5 => md5(data).to_vec(),
+            DigestHash::Sha256 => sha256(data).to_vec(),
+        };
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl DigestCredentials {
+    fn from_params(params: &[(String, String)]) -> Result<DigestCredentials, AuthError> {
+        let get = |name| params.iter().find(|p| p.0 == name).map(|p| p.1.clone());
+        let require = |name| get(name).ok_or(AuthError::MissingParameter(name));
+
+        let username = match get("username*") {
+            // ext-value of RFC 8187: charset'language'percent-encoded
+            Some(ext) => {
+                let value = ext.splitn(3, '\'').nth(2).ok_or(AuthError::InvalidParameter)?;
+                String::from_utf8_lossy(&percent_decode(value.as_bytes())).into_owned()
+            },
+            None => require("username")?,
+        };
+        Ok(DigestCredentials {
+            username,
+            realm: require("realm")?,
+            nonce: require("nonce")?,
+            uri: require("uri")?,
+            response: require("response")?,
+            algorithm: get("algorithm"),
+            cnonce: get("cnonce"),
+            opaque: get("opaque"),
+            qop: get("qop"),
+            nc: get("nc"),
+            userhash: get("userhash").is_some_and(|v| v.eq_ignore_ascii_case("true")),
+        })
+    }
+
+    // The hash function of `algorithm` and whether it is a session variant.
+    fn hash(&self) -> Result<(DigestHash, bool), AuthError> {
+        let algorithm = self.algorithm.as_ref().map_or("MD5", |a| &a[..]);
+        if algorithm.eq_ignore_ascii_case("MD5") {
+            Ok((DigestHash::Md5, false))
+        } else if algorithm.eq_ignore_ascii_case("MD5-sess") {
+            Ok((DigestHash::Md5, true))
+        } else if algorithm.eq_ignore_ascii_case("SHA-256") {
+            Ok((DigestHash::Sha256, false))
+        } else if algorithm.eq_ignore_ascii_case("SHA-256-sess") {
+            Ok((DigestHash::Sha256, true))
+        } else {
+            Err(AuthError::UnsupportedAlgorithm)
+        }
+    }
+
+    /// Computes the `response` for these credentials with the request `method`, the
+    /// user's `password` and, for `qop=auth-int`, the request `body`.
+    ///
+    /// A client sets `response` to the result; a server compares it with `response`.
+    /// With `userhash` set the digest needs the plain user name, so this fails with
+    /// `AuthError::HashedUsername`; use `compute_response_as` instead.
+    pub fn compute_response(&self, method: &str, password: &str, body: &[u8]) -> Result<String, AuthError> {
+        if self.userhash {
+            return Err(AuthError::HashedUsername);
+        }
+        self.compute_response_as(&self.username, method, password, body)
+    }
+
+    /// Computes the `response` like `compute_response`, with `user` as the plain user
+    /// name. With `userhash` set, `username` is the hash of `user` and `realm`
+    /// (RFC 7616 section 3.4.4) while the digest uses `user` itself.
+    pub fn compute_response_as(&self, user: &str, method: &str, password: &str, body: &[u8])
+                               -> Result<String, AuthError> {
+        let (hash, session) = self.hash()?;
+        let cnonce = self.cnonce.as_ref().ok_or(AuthError::MissingParameter("cnonce"));
+        let mut ha1 = hash.hex(format!("{}:{}:{}", user, self.realm, password).as_bytes());
+        if session {
+            ha1 = hash.hex(format!("{}:{}:{}", ha1, self.nonce, cnonce?).as_bytes());
+        }
+
+        match self.qop {
+            None => {
+                let ha2 = hash.hex(format!("{}:{}", method, self.uri).as_bytes());
+                Ok(hash.hex(format!("{}:{}:{}", ha1, self.nonce, ha2).as_bytes()))
+            },
+            Some(ref qop) => {
+                let ha2 = if qop.eq_ignore_ascii_case("auth") {
+                    hash.hex(format!("{}:{}", method, self.uri).as_bytes())
+                } else if qop.eq_ignore_ascii_case("auth-int") {
+                    let body_hash = hash.hex(body);
+                    hash.hex(format!("{}:{}:{}", method, self.uri, body_hash).as_bytes())
+                } else {
+                    return Err(AuthError::UnsupportedQop);
+                };
+                let nc = self.nc.as_ref().ok_or(AuthError::MissingParameter("nc"))?;
+                Ok(hash.hex(format!("{}:{}:{}:{}:{}:{}", ha1, self.nonce, nc, cnonce?, qop, ha2).as_bytes()))
+            },
+        }
+    }
+
+    /// Returns true if `response` matches the one computed from `password`. Always false
+    /// with `userhash` set; use `verify_as` instead.
+    pub fn verify(&self, method: &str, password: &str, body: &[u8]) -> bool {
+        self.compute_response(method, password, body)
+            .is_ok_and(|expected| expected.eq_ignore_ascii_case(&self.response))
+    }
+
+    /// Returns true if `response` matches the one computed from `user` and `password`. With
+    /// `userhash` set, `username` must also be the hash of `user` and `realm`.
+    pub fn verify_as(&self, user: &str, method: &str, password: &str, body: &[u8]) -> bool {
+        let hashed = self.hash_username(user).is_ok_and(|hashed| hashed.eq_ignore_ascii_case(&self.username));
+        if self.userhash && !hashed {
+            return false;
+        }
+        self.compute_response_as(user, method, password, body)
+            .is_ok_and(|expected| expected.eq_ignore_ascii_case(&self.response))
+    }
+
+    /// The hashed `username` of `user` for `userhash=true`: `H(user ":" realm)`.
+    pub fn hash_username(&self, user: &str) -> Result<String, AuthError> {
+        let (hash, _) = self.hash()?;
+        Ok(hash.hex(format!("{}:{}", user, self.realm).as_bytes()))
+    }
+}
+
+impl fmt::Display for DigestCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Digest username=")?;
+        write_quoted(f, &self.username)?;
+        write!(f, ", realm=")?;
+        write_quoted(f, &self.realm)?;
+        write!(f, ", uri=")?;
+        write_quoted(f, &self.uri)?;
+        if let Some(ref algorithm) = self.algorithm {
+            write!(f, ", algorithm={}", algorithm)?;
+        }
+        write!(f, ", nonce=")?;
+        write_quoted(f, &self.nonce)?;
+        if let Some(ref nc) = self.nc {
+            write!(f, ", nc={}", nc)?;
+        }
+        if let Some(ref cnonce) = self.cnonce {
+            write!(f, ", cnonce=")?;
+            write_quoted(f, cnonce)?;
+        }
+        if let Some(ref qop) = self.qop {
+            write!(f, ", qop={}", qop)?;
+        }
+        write!(f, ", response=")?;
+        write_quoted(f, &self.response)?;
+        if let Some(ref opaque) = self.opaque {
+            write!(f, ", opaque=")?;
+            write_quoted(f, opaque)?;
+        }
+        if self.userhash {
+            write!(f, ", userhash=true")?;
+        }
+        Ok(())
+    }
+}
+
+/// Credentials of `Authorization` or `Proxy-Authorization`.
+///
+/// `Display` serializes them back into a header value.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let credentials = Credentials::parse(b"Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==").unwrap();
+/// assert_eq!(credentials, Credentials::Basic {
+///     username: "Aladdin".to_string(),
+///     password: "open sesame".to_string(),
+/// });
+/// assert_eq!(credentials.to_string(), "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Credentials {
+    /// Basic user-id and password
+    Basic {
+        /// User-id, before the first colon
+        username: String,
+        /// Password, which may contain colons
+        password: String,
+    },
+    /// Bearer token
+    Bearer(String),
+    /// Digest credentials
+    Digest(DigestCredentials),
+    /// Credentials of another scheme
+    Other(Challenge),
+}
+
+impl Credentials {
+    /// Parses an `Authorization` or `Proxy-Authorization` value.
+    pub fn parse(value: &[u8]) -> Result<Credentials, AuthError> {
+        let mut schemes = parse_schemes(value)?;
+        if schemes.len() != 1 {
+            return Err(if schemes.is_empty() { AuthError::MissingScheme } else { AuthError::InvalidParameter });
+        }
+        let scheme = schemes.remove(0);
+
+        if scheme.is("Basic") {
+            let encoded = scheme.token68.ok_or(AuthError::InvalidBasicCredentials)?;
+            let decoded = base64::decode(encoded.as_bytes()).ok_or(AuthError::InvalidBasicCredentials)?;
+            let decoded = String::from_utf8(decoded).map_err(|_| AuthError::InvalidBasicCredentials)?;
+            let colon = decoded.find(':').ok_or(AuthError::InvalidBasicCredentials)?;
+            Ok(Credentials::Basic {
+                username: decoded[..colon].to_string(),
+                password: decoded[colon + 1..].to_string(),
+            })
+        } else if scheme.is("Bearer") {
+            scheme.token68.map(Credentials::Bearer).ok_or(AuthError::MissingToken)
+        } else if scheme.is("Digest") {
+            DigestCredentials::from_params(&scheme.params).map(Credentials::Digest)
+        } else {
+            Ok(Credentials::Other(scheme))
+        }
+    }
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Credentials::Basic { ref username, ref password } =>
+                write!(f, "Basic {}", base64::encode(format!("{}:{}", username, password).as_bytes())),
+            Credentials::Bearer(ref token) => write!(f, "Bearer {}", token),
+            Credentials::Digest(ref digest) => write!(f, "{}", digest),
+            Credentials::Other(ref other) => write!(f, "{}", other),
+        }
+    }
+}
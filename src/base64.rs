@@ -0,0 +1,94 @@
+//! Base64 encoding and decoding (RFC 4648) used by the header and protocol helpers.
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `data` with the standard alphabet and padding.
+pub fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
+        let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        out.push(STANDARD[(n >> 18) as usize & 0x3f] as char);
+        out.push(STANDARD[(n >> 12) as usize & 0x3f] as char);
+        if chunk.len() > 1 {
+            out.push(STANDARD[(n >> 6) as usize & 0x3f] as char);
+        } else {
+            out.push('=');
+        }
+        if chunk.len() > 2 {
+            out.push(STANDARD[n as usize & 0x3f] as char);
+        } else {
+            out.push('=');
+        }
+    }
+    out
+}
+
+fn decode_char(ch: u8) -> Option<u32> {
+    match ch {
+        b'A' ..= b'Z' => Some((ch - b'A') as u32),
+        b'a' ..= b'z' => Some((ch - b'a' + 26) as u32),
+        b'0' ..= b'9' => Some((ch - b'0' + 52) as u32),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+fn decode_url_char(ch: u8) -> Option<u32> {
+    match ch {
+        b'-' => Some(62),
+        b'_' => Some(63),
+        b'+' | b'/' => None,
+        _ => decode_char(ch),
+    }
+}
+
+/// Decodes standard base64. Padding is optional, but if present it must be correct.
+pub fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    decode_with(data, decode_char)
+}
+
+/// Decodes base64url (RFC 4648 section 5), e.g. the `HTTP2-Settings` header.
+/// Padding is optional, but if present it must be correct.
+pub fn decode_url(data: &[u8]) -> Option<Vec<u8>> {
+    decode_with(data, decode_url_char)
+}
+
+fn decode_with(data: &[u8], decode_char: fn(u8) -> Option<u32>) -> Option<Vec<u8>> {
+    let mut end = data.len();
+    let mut padding = 0;
+    while end > 0 && data[end - 1] == b'=' {
+        end -= 1;
+        padding += 1;
+    }
+    if padding > 2 || (padding > 0 && data.len() % 4 != 0) {
+        return None;
+    }
+
+    let input = &data[..end];
+    if input.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut n: u32 = 0;
+        for (i, &ch) in chunk.iter().enumerate() {
+            n |= decode_char(ch)? << (18 - 6 * i);
+        }
+
+        out.push((n >> 16) as u8);
+        if chunk.len() > 2 {
+            out.push((n >> 8) as u8);
+        }
+        if chunk.len() > 3 {
+            out.push(n as u8);
+        }
+    }
+    Some(out)
+}
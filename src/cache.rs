@@ -0,0 +1,357 @@
+//! HTTP caching (RFC 9111): `Cache-Control` and `Pragma` directives and the freshness of
+//! stored responses.
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use http_date::HttpDate;
+use parser::is_header_char;
+use grammar::{take_quoted_string, write_token_or_quoted};
+use message::{Headers, Message};
+
+// delta-seconds larger than this are capped, RFC 9111 section 1.2.2
+const MAX_DELTA_SECONDS: u64 = 1 << 31;
+// share of the time since Last-Modified used as heuristic lifetime, RFC 9111 section 4.2.2
+const HEURISTIC_FRACTION: u64 = 10;
+
+/// The directives of `Cache-Control`, and of `Pragma: no-cache` without a
+/// `Cache-Control`.
+///
+/// Directive names are case-insensitive. When a directive appears more than once, the
+/// first occurrence is used. An invalid `max-age` or `s-maxage` value is taken as 0, so
+/// the response is stale.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let cc = CacheControl::parse(b"public, max-age=3600, no-cache=\"Set-Cookie, Set-Cookie2\"");
+/// assert!(cc.public);
+/// assert_eq!(cc.max_age, Some(3600));
+/// assert_eq!(cc.no_cache, Some(vec!["set-cookie".to_string(), "set-cookie2".to_string()]));
+/// assert_eq!(cc.to_string(), "max-age=3600, no-cache=\"set-cookie, set-cookie2\", public");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct CacheControl {
+    /// `max-age`, in seconds
+    pub max_age: Option<u64>,
+    /// `s-maxage`, the `max-age` of shared caches, in seconds
+    pub s_maxage: Option<u64>,
+    /// `max-stale` of requests, with the accepted staleness in seconds if it has one
+    pub max_stale: Option<Option<u64>>,
+    /// `min-fresh` of requests, in seconds
+    pub min_fresh: Option<u64>,
+    /// `no-cache`, with the lower case header field names it applies to. An empty list
+    /// applies to the whole response.
+    pub no_cache: Option<Vec<String>>,
+    /// `private`, with the lower case header field names it applies to. An empty list
+    /// applies to the whole response.
+    pub private: Option<Vec<String>>,
+    /// `no-store`
+    pub no_store: bool,
+    /// `public`
+    pub public: bool,
+    /// `must-revalidate`
+    pub must_revalidate: bool,
+    /// `proxy-revalidate`
+    pub proxy_revalidate: bool,
+    /// `must-understand`
+    pub must_understand: bool,
+    /// `no-transform`
+    pub no_transform: bool,
+    /// `only-if-cached` of requests
+    pub only_if_cached: bool,
+    /// `immutable` (RFC 8246)
+    pub immutable: bool,
+    /// `stale-while-revalidate` (RFC 5861), in seconds
+    pub stale_while_revalidate: Option<u64>,
+    /// `stale-if-error` (RFC 5861), in seconds
+    pub stale_if_error: Option<u64>,
+    /// Other directives, with their lower case names and their values
+    pub extensions: Vec<(String, Option<String>)>,
+}
+
+fn parse_delta_seconds(value: Option<&str>) -> Option<u64> {
+    let value = value?;
+    if value.is_empty() || !value.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // too many digits for u64 are capped as well
+    Some(value.parse().unwrap_or(MAX_DELTA_SECONDS).min(MAX_DELTA_SECONDS))
+}
+
+fn field_names(value: Option<&str>) -> Vec<String> {
+    value.map_or_else(Vec::new, |v| {
+        v.split(',')
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect()
+    })
+}
+
+// Splits a directive list into lower case names and unquoted values. Malformed list
+// elements are skipped.
+fn parse_directives(mut data: &[u8]) -> Vec<(String, Option<String>)> {
+    let mut directives = Vec::new();
+    while !data.is_empty() {
+        let skip = data.iter().take_while(|&&c| c == b' ' || c == b'\t' || c == b',').count();
+        data = &data[skip..];
+        let len = data.iter().take_while(|&&c| is_header_char(false, c)).count();
+        if len == 0 {
+            // skip to the next element
+            let next = data.iter().position(|&c| c == b',').unwrap_or(data.len());
+            data = &data[next..];
+            continue;
+        }
+        let name = String::from_utf8_lossy(&data[..len]).to_ascii_lowercase();
+        data = &data[len..];
+
+        let mut value = None;
+        if data.first() == Some(&b'=') {
+            data = &data[1..];
+            if data.first() == Some(&b'"') {
+                match take_quoted_string(data) {
+                    Some((v, rest)) => {
+                        value = Some(String::from_utf8_lossy(&v).into_owned());
+                        data = rest;
+                    },
+                    None => break,
+                }
+            } else {
+                let len = data.iter().take_while(|&&c| is_header_char(false, c)).count();
+                value = Some(String::from_utf8_lossy(&data[..len]).into_owned());
+                data = &data[len..];
+            }
+        }
+        directives.push((name, value));
+
+        // anything else up to the next comma is invalid
+        let next = data.iter().position(|&c| c == b',').unwrap_or(data.len());
+        data = &data[next..];
+    }
+    directives
+}
+
+impl CacheControl {
+    /// Parses a `Cache-Control` value.
+    pub fn parse(value: &[u8]) -> CacheControl {
+        let mut cc = CacheControl::default();
+        cc.add(value);
+        cc
+    }
+
+    /// Parses all `Cache-Control` header fields as one list. Without `Cache-Control`,
+    /// `Pragma: no-cache` counts as `no-cache`.
+    pub fn from_headers(headers: &Headers) -> CacheControl {
+        let mut cc = CacheControl::default();
+        let values = headers.get_all("Cache-Control");
+        if values.is_empty() {
+            if headers.has_token("Pragma", "no-cache") {
+                cc.no_cache = Some(Vec::new());
+            }
+            return cc;
+        }
+        for value in values {
+            cc.add(value.as_bytes());
+        }
+        cc
+    }
+
+    fn add(&mut self, value: &[u8]) {
+        let mut seen: Vec<String> = self.extensions.iter().map(|e| e.0.clone()).collect();
+        for (name, value) in parse_directives(value) {
+            if seen.contains(&name) || self.has(&name) {
+                continue;
+            }
+            seen.push(name.clone());
+            let value = value.as_ref().map(|v| &v[..]);
+            match &name[..] {
+                "max-age" => self.max_age = Some(parse_delta_seconds(value).unwrap_or(0)),
+                "s-maxage" => self.s_maxage = Some(parse_delta_seconds(value).unwrap_or(0)),
+                "max-stale" => self.max_stale = Some(parse_delta_seconds(value)),
+                "min-fresh" => self.min_fresh = parse_delta_seconds(value),
+                "no-cache" => self.no_cache = Some(field_names(value)),
+                "private" => self.private = Some(field_names(value)),
+                "no-store" => self.no_store = true,
+                "public" => self.public = true,
+                "must-revalidate" => self.must_revalidate = true,
+                "proxy-revalidate" => self.proxy_revalidate = true,
+                "must-understand" => self.must_understand = true,
+                "no-transform" => self.no_transform = true,
+                "only-if-cached" => self.only_if_cached = true,
+                "immutable" => self.immutable = true,
+                "stale-while-revalidate" => self.stale_while_revalidate = parse_delta_seconds(value),
+                "stale-if-error" => self.stale_if_error = parse_delta_seconds(value),
+                _ => self.extensions.push((name, value.map(|v| v.to_string()))),
+            }
+        }
+    }
+
+    // Returns true if the known directive `name` is already set.
+    fn has(&self, name: &str) -> bool {
+        match name {
+            "max-age" => self.max_age.is_some(),
+            "s-maxage" => self.s_maxage.is_some(),
+            "max-stale" => self.max_stale.is_some(),
+            "min-fresh" => self.min_fresh.is_some(),
+            "no-cache" => self.no_cache.is_some(),
+            "private" => self.private.is_some(),
+            "stale-while-revalidate" => self.stale_while_revalidate.is_some(),
+            "stale-if-error" => self.stale_if_error.is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut directives: Vec<(&str, Option<String>)> = Vec::new();
+        let seconds = |name, value: Option<u64>| value.map(|v| (name, Some(v.to_string())));
+        let fields = |name, value: &Option<Vec<String>>| value.as_ref().map(|v| {
+            (name, if v.is_empty() { None } else { Some(v.join(", ")) })
+        });
+        let flag = |name, set: bool| if set { Some((name, None)) } else { None };
+
+        directives.extend(seconds("max-age", self.max_age));
+        directives.extend(seconds("s-maxage", self.s_maxage));
+        directives.extend(self.max_stale.map(|v| ("max-stale", v.map(|v| v.to_string()))));
+        directives.extend(seconds("min-fresh", self.min_fresh));
+        directives.extend(fields("no-cache", &self.no_cache));
+        directives.extend(fields("private", &self.private));
+        directives.extend(flag("no-store", self.no_store));
+        directives.extend(flag("public", self.public));
+        directives.extend(flag("must-revalidate", self.must_revalidate));
+        directives.extend(flag("proxy-revalidate", self.proxy_revalidate));
+        directives.extend(flag("must-understand", self.must_understand));
+        directives.extend(flag("no-transform", self.no_transform));
+        directives.extend(flag("only-if-cached", self.only_if_cached));
+        directives.extend(flag("immutable", self.immutable));
+        directives.extend(seconds("stale-while-revalidate", self.stale_while_revalidate));
+        directives.extend(seconds("stale-if-error", self.stale_if_error));
+        directives.extend(self.extensions.iter().map(|e| (&e.0[..], e.1.clone())));
+
+        for (i, &(name, ref value)) in directives.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", name)?;
+            if let Some(ref value) = *value {
+                write!(f, "=")?;
+                // field name lists are always quoted
+                if name == "no-cache" || name == "private" {
+                    write!(f, "\"{}\"", value)?;
+                } else {
+                    write_token_or_quoted(f, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Parses an HTTP-date header value.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    HttpDate::parse(value.as_bytes()).ok().map(SystemTime::from)
+}
+
+// `later - earlier`, or zero if `later` is earlier
+fn elapsed(earlier: SystemTime, later: SystemTime) -> Duration {
+    later.duration_since(earlier).unwrap_or_default()
+}
+
+// Status codes whose responses are heuristically cacheable, RFC 9110 section 15.1
+fn is_heuristically_cacheable(status: u16) -> bool {
+    matches!(status, 200 | 203 | 204 | 206 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501)
+}
+
+/// The freshness of a stored response (RFC 9111 section 4.2).
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// use std::time::Duration;
+///
+/// let mut parser = HttpParser::new(HttpParserType::Response);
+/// let mut collector = MessageCollector::new();
+/// parser.execute(&mut collector, b"HTTP/1.1 200 OK\r\n\
+///                                  Cache-Control: max-age=600\r\n\
+///                                  Age: 100\r\n\
+///                                  Content-Length: 0\r\n\r\n");
+/// let response = &collector.messages[0];
+///
+/// let received = response.completed.unwrap();
+/// let now = received + Duration::from_secs(60);
+/// let freshness = Freshness::evaluate(response, received, received, now, false);
+/// assert_eq!(freshness.lifetime, Duration::from_secs(600));
+/// assert_eq!(freshness.age.as_secs(), 160);
+/// assert!(freshness.is_fresh());
+/// ```
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Freshness {
+    /// How long the response stays fresh after it was generated
+    pub lifetime: Duration,
+    /// True if the lifetime is a heuristic based on `Last-Modified` rather than given by
+    /// `Cache-Control` or `Expires`
+    pub heuristic: bool,
+    /// The current age of the response
+    pub age: Duration,
+}
+
+impl Freshness {
+    /// Evaluates the freshness of `response` at `now`. `request_time` is when the request
+    /// was sent and `response_time` when the response was received. A shared cache uses
+    /// `s-maxage` before `max-age`.
+    ///
+    /// An invalid `Expires` date makes the response stale.
+    pub fn evaluate(response: &Message, request_time: SystemTime, response_time: SystemTime,
+                    now: SystemTime, shared: bool) -> Freshness {
+        let headers = &response.headers;
+        let cc = CacheControl::from_headers(headers);
+        let date = headers.get("Date").and_then(parse_http_date).unwrap_or(response_time);
+
+        let mut heuristic = false;
+        let lifetime = if let Some(s_maxage) = cc.s_maxage.filter(|_| shared) {
+            s_maxage
+        } else if let Some(max_age) = cc.max_age {
+            max_age
+        } else if let Some(expires) = headers.get("Expires") {
+            parse_http_date(expires).map_or(0, |expires| elapsed(date, expires).as_secs())
+        } else if let Some(last_modified) = headers.get("Last-Modified").and_then(parse_http_date) {
+            let cacheable = response.status_code.is_some_and(is_heuristically_cacheable) || cc.public;
+            if cacheable {
+                heuristic = true;
+                elapsed(last_modified, date).as_secs() / HEURISTIC_FRACTION
+            } else {
+                0
+            }
+        } else {
+            0
+        };
+
+        // RFC 9111 section 4.2.3
+        let age_value = headers.get("Age")
+            .and_then(|age| parse_delta_seconds(Some(age.trim())))
+            .map_or(Duration::from_secs(0), Duration::from_secs);
+        let apparent_age = elapsed(date, response_time);
+        let corrected_age_value = age_value + elapsed(request_time, response_time);
+        let corrected_initial_age = apparent_age.max(corrected_age_value);
+        let age = corrected_initial_age + elapsed(response_time, now);
+
+        Freshness {
+            lifetime: Duration::from_secs(lifetime),
+            heuristic,
+            age,
+        }
+    }
+
+    /// Returns true if the response is still fresh.
+    pub fn is_fresh(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    /// How long the response stays fresh, zero if it is stale.
+    pub fn time_to_live(&self) -> Duration {
+        self.lifetime.checked_sub(self.age).unwrap_or_default()
+    }
+}
@@ -8,6 +8,10 @@ pub enum ParseAction {
     None,
     /// Skip body
     SkipBody,
+    /// Read the body but don't deliver it to `on_body`, e.g. after answering
+    /// `Expect: 100-continue` with `417 Expectation Failed`. The body bytes are still
+    /// consumed so the next request on the connection is parsed correctly.
+    DiscardBody,
 }
 
 /// Result of a callback function.
@@ -34,6 +38,13 @@ pub type CallbackResult = Result<ParseAction, String>;
 /// let mut cb = Callback;
 /// ```
 pub trait HttpParserCallback {
+    /// Function called when the PROXY protocol header is parsed, before the first request.
+    /// It is available in `parser.proxy_header`. Only called if `parser.proxy_protocol` is set.
+    #[allow(unused_variables)]
+    fn on_proxy_header(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
     /// Function called when starting parsing a new HTTP request or response.
     #[allow(unused_variables)]
     fn on_message_begin(&mut self, parser: &mut HttpParser) -> CallbackResult {
@@ -64,6 +75,13 @@ pub trait HttpParserCallback {
         Ok(ParseAction::None)
     }
 
+    /// Function called when a header value continues on the next line (obs-fold), before
+    /// the rest of the value. See `parser.obs_fold`.
+    #[allow(unused_variables)]
+    fn on_obs_fold(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
     /// Function called when all headers are parsed.
     #[allow(unused_variables)]
     fn on_headers_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
@@ -76,9 +94,25 @@ pub trait HttpParserCallback {
         Ok(ParseAction::None)
     }
 
+    /// Function called instead of `on_message_complete` when an interim (1xx) response is
+    /// parsed. The status is in `parser.status_code` and the headers, such as the `Link`
+    /// headers of `103 Early Hints`, have been delivered by the header callbacks. The final
+    /// response follows with a new `on_message_begin`.
+    #[allow(unused_variables)]
+    fn on_informational(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
     /// Function called when finishing parsing a HTTP request or response.
     #[allow(unused_variables)]
     fn on_message_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
         Ok(ParseAction::None)
     }
+
+    /// Function called with the bytes following an upgrade or CONNECT request when
+    /// `parser.tunnel` is set. Every later call to `execute` passes its data here.
+    #[allow(unused_variables)]
+    fn on_tunnel_data(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
 }
@@ -0,0 +1,345 @@
+//! `Cookie` and `Set-Cookie` header values (RFC 6265).
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http_date::{civil_from_days, days_from_civil, HttpDate, MONTHS};
+
+/// `CookieError` defines the errors encountered when parsing or creating a cookie.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CookieError {
+    /// The name-value pair has no `=`
+    MissingEquals,
+    /// The cookie name is empty
+    EmptyName,
+    /// The cookie name is not a token
+    InvalidName,
+    /// The cookie value has characters a cookie value cannot hold
+    InvalidValue,
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CookieError::MissingEquals => write!(f, "cookie has no '=' between name and value"),
+            CookieError::EmptyName => write!(f, "cookie name is empty"),
+            CookieError::InvalidName => write!(f, "invalid character in cookie name"),
+            CookieError::InvalidValue => write!(f, "invalid character in cookie value"),
+        }
+    }
+}
+
+/// The `SameSite` attribute of a cookie.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SameSite {
+    /// Only sent with same-site requests
+    Strict,
+    /// Also sent with top-level cross-site navigations
+    Lax,
+    /// Sent with all requests, requires `Secure`
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SameSite::Strict => write!(f, "Strict"),
+            SameSite::Lax => write!(f, "Lax"),
+            SameSite::None => write!(f, "None"),
+        }
+    }
+}
+
+/// Parses the value of a `Cookie` request header into `(name, value)` pairs, in order.
+///
+/// Pairs are separated by `;` with optional whitespace. Pairs without `=` or with an
+/// empty name are skipped, and values are kept as they are, including any double quotes.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let cookies = parse_cookies("SID=31d4d96e407aad42; lang=en-US;;flag");
+/// assert_eq!(cookies, vec![("SID".to_string(), "31d4d96e407aad42".to_string()),
+///                          ("lang".to_string(), "en-US".to_string())]);
+/// ```
+pub fn parse_cookies(value: &str) -> Vec<(String, String)> {
+    value.split(';')
+        .filter_map(|pair| split_pair(pair).ok())
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Formats `(name, value)` pairs as the value of a `Cookie` header.
+pub fn format_cookies<'a, I: IntoIterator<Item=(&'a str, &'a str)>>(pairs: I) -> String {
+    pairs.into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// A cookie set by a `Set-Cookie` response header.
+///
+/// `Display` serializes it back into a `Set-Cookie` value.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let cookie = SetCookie::parse("id=a3fWa; Max-Age=2592000; Path=/; Secure; SameSite=lax").unwrap();
+/// assert_eq!(cookie.name, "id");
+/// assert_eq!(cookie.max_age, Some(2592000));
+/// assert_eq!(cookie.same_site, Some(SameSite::Lax));
+/// assert_eq!(cookie.to_string(), "id=a3fWa; Max-Age=2592000; Path=/; Secure; SameSite=Lax");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SetCookie {
+    /// Cookie name
+    pub name: String,
+    /// Cookie value, kept as it is including any double quotes
+    pub value: String,
+    /// `Expires`, the time the cookie expires
+    pub expires: Option<SystemTime>,
+    /// `Max-Age`, the number of seconds until the cookie expires. Zero or less expires it
+    /// at once
+    pub max_age: Option<i64>,
+    /// `Domain`, lower case and without a leading dot
+    pub domain: Option<String>,
+    /// `Path`, which starts with `/`
+    pub path: Option<String>,
+    /// `Secure`, only send the cookie over secure connections
+    pub secure: bool,
+    /// `HttpOnly`, hide the cookie from scripts
+    pub http_only: bool,
+    /// `SameSite`
+    pub same_site: Option<SameSite>,
+    /// `Partitioned`, keep the cookie in storage partitioned by top-level site (CHIPS)
+    pub partitioned: bool,
+}
+
+impl SetCookie {
+    /// Creates a cookie without attributes, checking that `name` is a token and `value`
+    /// only has characters a cookie value can hold.
+    pub fn new(name: &str, value: &str) -> Result<SetCookie, CookieError> {
+        if name.is_empty() {
+            return Err(CookieError::EmptyName);
+        }
+        if !name.bytes().all(is_token) {
+            return Err(CookieError::InvalidName);
+        }
+        let unquoted = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            &value[1..value.len() - 1]
+        } else {
+            value
+        };
+        if !unquoted.bytes().all(is_cookie_octet) {
+            return Err(CookieError::InvalidValue);
+        }
+        Ok(SetCookie::unchecked(name, value))
+    }
+
+    /// Parses a `Set-Cookie` value following the user agent algorithm of RFC 6265
+    /// section 5.2: unknown attributes and attributes with invalid values are ignored,
+    /// and the last occurrence of an attribute wins.
+    pub fn parse(value: &str) -> Result<SetCookie, CookieError> {
+        let mut parts = value.split(';');
+        let (name, value) = split_pair(parts.next().unwrap_or(""))?;
+        let mut cookie = SetCookie::unchecked(name, value);
+
+        for attribute in parts {
+            let mut av = attribute.splitn(2, '=');
+            let name = av.next().unwrap_or("").trim();
+            let value = av.next().unwrap_or("").trim();
+
+            if name.eq_ignore_ascii_case("Expires") {
+                if let Some(time) = parse_cookie_date(value) {
+                    cookie.expires = Some(time);
+                }
+            } else if name.eq_ignore_ascii_case("Max-Age") {
+                if let Some(max_age) = parse_max_age(value) {
+                    cookie.max_age = Some(max_age);
+                }
+            } else if name.eq_ignore_ascii_case("Domain") {
+                let domain = value.trim_start_matches('.');
+                if !domain.is_empty() {
+                    cookie.domain = Some(domain.to_ascii_lowercase());
+                }
+            } else if name.eq_ignore_ascii_case("Path") {
+                cookie.path = if value.starts_with('/') { Some(value.to_string()) } else { None };
+            } else if name.eq_ignore_ascii_case("Secure") {
+                cookie.secure = true;
+            } else if name.eq_ignore_ascii_case("HttpOnly") {
+                cookie.http_only = true;
+            } else if name.eq_ignore_ascii_case("SameSite") {
+                cookie.same_site = if value.eq_ignore_ascii_case("Strict") {
+                    Some(SameSite::Strict)
+                } else if value.eq_ignore_ascii_case("Lax") {
+                    Some(SameSite::Lax)
+                } else if value.eq_ignore_ascii_case("None") {
+                    Some(SameSite::None)
+                } else {
+                    None
+                };
+            } else if name.eq_ignore_ascii_case("Partitioned") {
+                cookie.partitioned = true;
+            }
+        }
+
+        Ok(cookie)
+    }
+
+    fn unchecked(name: &str, value: &str) -> SetCookie {
+        SetCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            expires: None,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+            partitioned: false,
+        }
+    }
+}
+
+impl fmt::Display for SetCookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", HttpDate::from(expires))?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age)?;
+        }
+        if let Some(ref domain) = self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(ref path) = self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if self.secure {
+            write!(f, "; Secure")?;
+        }
+        if self.http_only {
+            write!(f, "; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        if self.partitioned {
+            write!(f, "; Partitioned")?;
+        }
+        Ok(())
+    }
+}
+
+fn split_pair(pair: &str) -> Result<(&str, &str), CookieError> {
+    let eq = pair.find('=').ok_or(CookieError::MissingEquals)?;
+    let name = pair[..eq].trim();
+    if name.is_empty() {
+        return Err(CookieError::EmptyName);
+    }
+    Ok((name, pair[eq + 1..].trim()))
+}
+
+fn is_token(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&ch)
+}
+
+// cookie-octet of RFC 6265: printable US-ASCII except DQUOTE, comma, semicolon and backslash
+fn is_cookie_octet(ch: u8) -> bool {
+    ch == 0x21 || (0x23..=0x2b).contains(&ch) || (0x2d..=0x3a).contains(&ch) ||
+        (0x3c..=0x5b).contains(&ch) || (0x5d..=0x7e).contains(&ch)
+}
+
+fn parse_max_age(value: &str) -> Option<i64> {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // too many digits saturate rather than drop the attribute
+    let max_age = digits.parse().unwrap_or(i64::MAX);
+    Some(if value.starts_with('-') { -max_age } else { max_age })
+}
+
+fn is_date_delimiter(ch: u8) -> bool {
+    ch == 0x09 || (0x20..=0x2f).contains(&ch) || (0x3b..=0x40).contains(&ch) ||
+        (0x5b..=0x60).contains(&ch) || (0x7b..=0x7e).contains(&ch)
+}
+
+// Leading run of one to `max` digits of a date token, followed by anything but a digit.
+fn leading_number(token: &[u8], min: usize, max: usize) -> Option<(i64, &[u8])> {
+    let len = token.iter().take_while(|c| c.is_ascii_digit()).count();
+    if len < min || len > max {
+        return None;
+    }
+    let value = token[..len].iter().fold(0i64, |acc, c| acc * 10 + (c - b'0') as i64);
+    Some((value, &token[len..]))
+}
+
+fn parse_time(token: &[u8]) -> Option<(i64, i64, i64)> {
+    let (hour, rest) = leading_number(token, 1, 2)?;
+    let (minute, rest) = leading_number(rest.strip_prefix(b":")?, 1, 2)?;
+    let (second, _) = leading_number(rest.strip_prefix(b":")?, 1, 2)?;
+    Some((hour, minute, second))
+}
+
+// The cookie-date algorithm of RFC 6265 section 5.1.1, which accepts the many date
+// formats found in `Expires`.
+fn parse_cookie_date(value: &str) -> Option<SystemTime> {
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    for token in value.as_bytes().split(|&c| is_date_delimiter(c)).filter(|t| !t.is_empty()) {
+        if time.is_none() {
+            if let Some(t) = parse_time(token) {
+                time = Some(t);
+                continue;
+            }
+        }
+        if day.is_none() {
+            if let Some((d, _)) = leading_number(token, 1, 2) {
+                day = Some(d);
+                continue;
+            }
+        }
+        if month.is_none() && token.len() >= 3 {
+            if let Some(m) = MONTHS.iter().position(|m| m.as_bytes().eq_ignore_ascii_case(&token[..3])) {
+                month = Some(m as u32 + 1);
+                continue;
+            }
+        }
+        if year.is_none() {
+            if let Some((y, _)) = leading_number(token, 2, 4) {
+                year = Some(y);
+            }
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let (day, month, mut year) = (day?, month?, year?);
+    if (70..=99).contains(&year) {
+        year += 1900;
+    } else if (0..=69).contains(&year) {
+        year += 2000;
+    }
+    if !(1..=31).contains(&day) || year < 1601 || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let days = days_from_civil(year, month, day as u32);
+    if civil_from_days(days) != (year, month, day as u32) {
+        // such as February 30
+        return None;
+    }
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    if secs >= 0 {
+        Some(UNIX_EPOCH + Duration::from_secs(secs as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(-secs as u64))
+    }
+}
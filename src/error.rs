@@ -1,7 +1,7 @@
 use std::fmt;
 
 /// `HttpErrno` defines the encountered error during parsing.
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum HttpErrno {
     // Callback-related errors
     /// Error happened in message begin callback
@@ -20,6 +20,14 @@ pub enum HttpErrno {
     CBMessageComplete,
     /// Error happened in status callback
     CBStatus,
+    /// Error happened in informational callback
+    CBInformational,
+    /// Error happened in tunnel data callback
+    CBTunnelData,
+    /// Error happened in PROXY header callback
+    CBProxyHeader,
+    /// Error happened in obs-fold callback
+    CBObsFold,
 
     // Parsing-related errors
     /// Invalid EOF state
@@ -30,6 +38,8 @@ pub enum HttpErrno {
     ClosedConnection,
     /// Invalid HTTP version
     InvalidVersion,
+    /// Valid but unsupported HTTP version, to be answered with 505
+    UnsupportedVersion,
     /// Invalid HTTP status
     InvalidStatus,
     /// Invalid HTTP method
@@ -46,14 +56,22 @@ pub enum HttpErrno {
     InvalidQueryString,
     /// Invalid fragment
     InvalidFragment,
+    /// Invalid PROXY protocol header
+    InvalidProxyHeader,
     /// Line feed is expected
     LFExpected,
     /// Invalid header token
     InvalidHeaderToken,
+    /// Header value continues on the next line
+    InvalidObsFold,
     /// Invalid content length
     InvalidContentLength,
     /// Invalid chunk size
     InvalidChunkSize,
+    /// Content-Length together with Transfer-Encoding
+    UnexpectedContentLength,
+    /// Request Transfer-Encoding that is not chunked
+    InvalidTransferEncoding,
     /// Invalid constant
     InvalidConstant,
     /// Invalid internal state
@@ -77,11 +95,16 @@ impl fmt::Display for HttpErrno {
             HttpErrno::CBBody => write!(f, "the on_body callback failed"),
             HttpErrno::CBMessageComplete => write!(f, "the on_message_complete callback failed"),
             HttpErrno::CBStatus => write!(f, "the on_status callback failed"),
+            HttpErrno::CBInformational => write!(f, "the on_informational callback failed"),
+            HttpErrno::CBTunnelData => write!(f, "the on_tunnel_data callback failed"),
+            HttpErrno::CBProxyHeader => write!(f, "the on_proxy_header callback failed"),
+            HttpErrno::CBObsFold => write!(f, "the on_obs_fold callback failed"),
 
             HttpErrno::InvalidEofState => write!(f, "stream ended at an unexpected time"),
             HttpErrno::HeaderOverflow => write!(f, "too many header bytes seen; overflow detected"),
             HttpErrno::ClosedConnection => write!(f, "data received after completed connection: close message"),
             HttpErrno::InvalidVersion => write!(f, "invalid HTTP version"),
+            HttpErrno::UnsupportedVersion => write!(f, "unsupported HTTP version"),
             HttpErrno::InvalidStatus => write!(f, "invalid HTTP status code"),
             HttpErrno::InvalidMethod => write!(f, "invalid HTTP method"),
             HttpErrno::InvalidUrl => write!(f, "invalid URL"),
@@ -90,10 +113,14 @@ impl fmt::Display for HttpErrno {
             HttpErrno::InvalidPath => write!(f, "invalid path"),
             HttpErrno::InvalidQueryString => write!(f, "invalid query string"),
             HttpErrno::InvalidFragment => write!(f, "invalid fragment"),
+            HttpErrno::InvalidProxyHeader => write!(f, "invalid PROXY protocol header"),
             HttpErrno::LFExpected => write!(f, "LF character expected"),
             HttpErrno::InvalidHeaderToken => write!(f, "invalid charater in header"),
+            HttpErrno::InvalidObsFold => write!(f, "obsolete line folding in header value"),
             HttpErrno::InvalidContentLength => write!(f, "invalid character in content-length header"),
             HttpErrno::InvalidChunkSize => write!(f, "invalid character in chunk size header"),
+            HttpErrno::UnexpectedContentLength => write!(f, "content-length and transfer-encoding are both set"),
+            HttpErrno::InvalidTransferEncoding => write!(f, "request transfer-encoding is not chunked"),
             HttpErrno::InvalidConstant => write!(f, "invalid constant string"),
             HttpErrno::InvalidInternalState => write!(f, "encountered unexpected internal state"),
             HttpErrno::Strict => write!(f, "strict mode assertion failed"),
@@ -5,10 +5,15 @@ pub enum Flags {
     Trailing                = 1isize << 3,
     Upgrade                 = 1isize << 4,
     SkipBody                = 1isize << 5,
+    ExpectContinue          = 1isize << 6,
+    DiscardBody             = 1isize << 7,
+    SimpleRequest           = 1isize << 8,
+    ContentLength           = 1isize << 9,
+    TransferEncoding        = 1isize << 10,
 }
 
 impl Flags {
-    pub fn as_u8(self) -> u8 {
-        self as u8
+    pub fn as_u16(self) -> u16 {
+        self as u16
     }
 }
@@ -0,0 +1,524 @@
+//! The client address behind proxies: `Forwarded` (RFC 7239), `X-Forwarded-For`,
+//! `X-Forwarded-Proto`, `X-Forwarded-Host` and `X-Real-IP`.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::{self, FromStr};
+
+use parser::is_header_char;
+use grammar::{split_unquoted, take_quoted_string, trim_ows, write_token_or_quoted};
+use message::Headers;
+
+/// `ForwardedError` defines the errors encountered when parsing forwarding headers.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ForwardedError {
+    /// A pair is not a token, `=` and a token or quoted string
+    InvalidParameter,
+    /// A parameter occurs twice in one forwarded-element
+    DuplicateParameter,
+    /// A quoted string has no closing quote
+    UnterminatedQuote,
+    /// A node is not an IPv4 address, a bracketed IPv6 address, `unknown` or an
+    /// obfuscated identifier, with an optional port
+    InvalidNode,
+    /// A protocol is not a URI scheme
+    InvalidProto,
+    /// A host is not a URI host and optional port
+    InvalidHost,
+    /// An address or network is not valid
+    InvalidAddress,
+}
+
+impl fmt::Display for ForwardedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ForwardedError::InvalidParameter => write!(f, "invalid forwarded parameter"),
+            ForwardedError::DuplicateParameter => write!(f, "duplicate forwarded parameter"),
+            ForwardedError::UnterminatedQuote => write!(f, "unterminated quoted string"),
+            ForwardedError::InvalidNode => write!(f, "invalid forwarded node"),
+            ForwardedError::InvalidProto => write!(f, "invalid forwarded protocol"),
+            ForwardedError::InvalidHost => write!(f, "invalid forwarded host"),
+            ForwardedError::InvalidAddress => write!(f, "invalid IP address"),
+        }
+    }
+}
+
+// obfnode and obfport: "_" 1*( ALPHA / DIGIT / "." / "_" / "-" )
+fn is_obfuscated(s: &str) -> bool {
+    s.len() > 1 && s.starts_with('_') &&
+        s.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'.' || c == b'_' || c == b'-')
+}
+
+// scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." ), RFC 3986 section 3.1
+fn parse_scheme(s: &str) -> Result<String, ForwardedError> {
+    let valid = s.bytes().next().is_some_and(|c| c.is_ascii_alphabetic()) &&
+        s.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'+' || c == b'-' || c == b'.');
+    if !valid {
+        return Err(ForwardedError::InvalidProto);
+    }
+    Ok(s.to_ascii_lowercase())
+}
+
+// uri-host [ ":" port ], checked for characters that can not occur in it
+fn parse_host(s: &str) -> Result<String, ForwardedError> {
+    let valid = !s.is_empty() && s.bytes().all(|c| {
+        c.is_ascii_graphic() && !b"\"\\,;/?#@<>{}|^`".contains(&c)
+    });
+    if !valid {
+        return Err(ForwardedError::InvalidHost);
+    }
+    Ok(s.to_string())
+}
+
+/// The name of a node of `Forwarded`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum NodeName {
+    /// An IP address
+    Ip(IpAddr),
+    /// `unknown`: the proxy does not know or hides the address
+    Unknown,
+    /// An obfuscated identifier such as `_hidden`, including the underscore
+    Obfuscated(String),
+}
+
+/// The port of a node of `Forwarded`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum NodePort {
+    /// A port number
+    Port(u16),
+    /// An obfuscated port such as `_9091`, including the underscore
+    Obfuscated(String),
+}
+
+/// A node of the `for` and `by` parameters of `Forwarded`, or an entry of
+/// `X-Forwarded-For`.
+///
+/// `Display` brackets IPv6 addresses.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Node {
+    /// The address or identifier
+    pub name: NodeName,
+    /// The port, if any
+    pub port: Option<NodePort>,
+}
+
+impl Node {
+    /// Parses a node of `Forwarded`: an IPv4 address, an IPv6 address in brackets,
+    /// `unknown` or an obfuscated identifier, optionally followed by `:` and a port or an
+    /// obfuscated port.
+    pub fn parse(value: &str) -> Result<Node, ForwardedError> {
+        let (name, port) = if let Some(rest) = value.strip_prefix('[') {
+            let end = rest.find(']').ok_or(ForwardedError::InvalidNode)?;
+            let ip = Ipv6Addr::from_str(&rest[..end]).map_err(|_| ForwardedError::InvalidNode)?;
+            let port = match &rest[end + 1..] {
+                "" => None,
+                port => Some(port.strip_prefix(':').ok_or(ForwardedError::InvalidNode)?),
+            };
+            (NodeName::Ip(IpAddr::V6(ip)), port)
+        } else {
+            let mut parts = value.splitn(2, ':');
+            let name = parts.next().unwrap_or_default();
+            let name = if name.eq_ignore_ascii_case("unknown") {
+                NodeName::Unknown
+            } else if is_obfuscated(name) {
+                NodeName::Obfuscated(name.to_string())
+            } else {
+                // IPv4 only: an IPv6 address must be in brackets
+                NodeName::Ip(IpAddr::V4(Ipv4Addr::from_str(name).map_err(|_| ForwardedError::InvalidNode)?))
+            };
+            (name, parts.next())
+        };
+
+        let port = match port {
+            None => None,
+            Some(port) if is_obfuscated(port) => Some(NodePort::Obfuscated(port.to_string())),
+            Some(port) if (1..=5).contains(&port.len()) && port.bytes().all(|c| c.is_ascii_digit()) =>
+                Some(NodePort::Port(port.parse().map_err(|_| ForwardedError::InvalidNode)?)),
+            Some(_) => return Err(ForwardedError::InvalidNode),
+        };
+        Ok(Node { name, port })
+    }
+
+    /// The IP address, unless it is unknown or obfuscated.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self.name {
+            NodeName::Ip(ip) => Some(ip),
+            _ => None,
+        }
+    }
+
+    // An entry of X-Forwarded-For, which also has IPv6 addresses without brackets
+    fn parse_x_forwarded_for(value: &str) -> Result<Node, ForwardedError> {
+        if let Ok(ip) = IpAddr::from_str(value) {
+            return Ok(Node::from(ip));
+        }
+        if let Ok(addr) = SocketAddr::from_str(value) {
+            return Ok(Node { name: NodeName::Ip(addr.ip()), port: Some(NodePort::Port(addr.port())) });
+        }
+        match Node::parse(value)? {
+            Node { name: NodeName::Obfuscated(_), .. } => Err(ForwardedError::InvalidNode),
+            node => Ok(node),
+        }
+    }
+}
+
+impl From<IpAddr> for Node {
+    fn from(ip: IpAddr) -> Node {
+        Node { name: NodeName::Ip(ip), port: None }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            NodeName::Ip(IpAddr::V4(ip)) => write!(f, "{}", ip)?,
+            NodeName::Ip(IpAddr::V6(ip)) => write!(f, "[{}]", ip)?,
+            NodeName::Unknown => write!(f, "unknown")?,
+            NodeName::Obfuscated(ref name) => write!(f, "{}", name)?,
+        }
+        match self.port {
+            Some(NodePort::Port(port)) => write!(f, ":{}", port),
+            Some(NodePort::Obfuscated(ref port)) => write!(f, ":{}", port),
+            None => Ok(()),
+        }
+    }
+}
+
+/// One forwarded-element of `Forwarded`, added by one proxy.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ForwardedElement {
+    /// `by`, the interface where the request came in to the proxy
+    pub by: Option<Node>,
+    /// `for`, the node making the request to the proxy
+    pub for_: Option<Node>,
+    /// `host`, the `Host` of the request the proxy received
+    pub host: Option<String>,
+    /// `proto`, the lower case scheme of the request the proxy received
+    pub proto: Option<String>,
+    /// Other parameters, with lower case names
+    pub extensions: Vec<(String, String)>,
+}
+
+impl ForwardedElement {
+    fn parse(data: &[u8]) -> Result<ForwardedElement, ForwardedError> {
+        let mut element = ForwardedElement::default();
+        let mut names: Vec<String> = Vec::new();
+        for pair in split_unquoted(data, b';').ok_or(ForwardedError::UnterminatedQuote)? {
+            if pair.is_empty() {
+                continue;
+            }
+            let eq = pair.iter().position(|&c| c == b'=').ok_or(ForwardedError::InvalidParameter)?;
+            let (name, value) = (&pair[..eq], &pair[eq + 1..]);
+            if name.is_empty() || !name.iter().all(|&c| is_header_char(false, c)) {
+                return Err(ForwardedError::InvalidParameter);
+            }
+            let value = if value.first() == Some(&b'"') {
+                let (value, after) = take_quoted_string(value).ok_or(ForwardedError::UnterminatedQuote)?;
+                if !after.is_empty() {
+                    return Err(ForwardedError::InvalidParameter);
+                }
+                value
+            } else if !value.is_empty() && value.iter().all(|&c| is_header_char(false, c)) {
+                value.to_vec()
+            } else {
+                return Err(ForwardedError::InvalidParameter);
+            };
+            let value = String::from_utf8(value).map_err(|_| ForwardedError::InvalidParameter)?;
+
+            let name = String::from_utf8_lossy(name).to_ascii_lowercase();
+            if names.contains(&name) {
+                return Err(ForwardedError::DuplicateParameter);
+            }
+            match &name[..] {
+                "by" => element.by = Some(Node::parse(&value)?),
+                "for" => element.for_ = Some(Node::parse(&value)?),
+                "host" => element.host = Some(parse_host(&value)?),
+                "proto" => element.proto = Some(parse_scheme(&value)?),
+                _ => element.extensions.push((name.clone(), value)),
+            }
+            names.push(name);
+        }
+        Ok(element)
+    }
+}
+
+impl fmt::Display for ForwardedElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut pairs: Vec<(&str, String)> = Vec::new();
+        if let Some(ref node) = self.for_ {
+            pairs.push(("for", node.to_string()));
+        }
+        if let Some(ref node) = self.by {
+            pairs.push(("by", node.to_string()));
+        }
+        if let Some(ref host) = self.host {
+            pairs.push(("host", host.clone()));
+        }
+        if let Some(ref proto) = self.proto {
+            pairs.push(("proto", proto.clone()));
+        }
+        pairs.extend(self.extensions.iter().map(|e| (&e.0[..], e.1.clone())));
+        for (i, (name, value)) in pairs.iter().enumerate() {
+            write!(f, "{}{}=", if i == 0 { "" } else { ";" }, name)?;
+            write_token_or_quoted(f, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The forwarded-elements of `Forwarded`, in the order the proxies added them.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let forwarded = Forwarded::parse(b"for=192.0.2.60;proto=http;by=203.0.113.43, \
+///                                    for=\"[2001:db8:cafe::17]:4711\"").unwrap();
+/// assert_eq!(forwarded.elements[0].proto, Some("http".to_string()));
+/// assert_eq!(forwarded.elements[1].for_.as_ref().unwrap().ip(), Some("2001:db8:cafe::17".parse().unwrap()));
+/// assert_eq!(forwarded.to_string(), "for=192.0.2.60;by=203.0.113.43;proto=http, \
+///                                    for=\"[2001:db8:cafe::17]:4711\"");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Forwarded {
+    /// The elements, from the one added by the proxy nearest to the client
+    pub elements: Vec<ForwardedElement>,
+}
+
+impl Forwarded {
+    /// Parses a `Forwarded` value. Empty elements are skipped.
+    pub fn parse(value: &[u8]) -> Result<Forwarded, ForwardedError> {
+        let mut elements = Vec::new();
+        for element in split_unquoted(value, b',').ok_or(ForwardedError::UnterminatedQuote)? {
+            if !element.is_empty() {
+                elements.push(ForwardedElement::parse(element)?);
+            }
+        }
+        Ok(Forwarded { elements })
+    }
+}
+
+impl fmt::Display for Forwarded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, element) in self.elements.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { "" } else { ", " }, element)?;
+        }
+        Ok(())
+    }
+}
+
+// The non-empty entries of a comma-separated list
+fn list_entries(value: &[u8]) -> Result<Vec<&str>, ForwardedError> {
+    let value = str::from_utf8(value).map_err(|_| ForwardedError::InvalidParameter)?;
+    Ok(value.split(',').map(str::trim).filter(|e| !e.is_empty()).collect())
+}
+
+/// Parses an `X-Forwarded-For` value: IPv4 or IPv6 addresses, with or without brackets
+/// and ports, or `unknown`.
+pub fn parse_x_forwarded_for(value: &[u8]) -> Result<Vec<Node>, ForwardedError> {
+    list_entries(value)?.into_iter().map(Node::parse_x_forwarded_for).collect()
+}
+
+/// Parses an `X-Forwarded-Proto` value into lower case schemes, one per proxy that set
+/// it.
+pub fn parse_x_forwarded_proto(value: &[u8]) -> Result<Vec<String>, ForwardedError> {
+    list_entries(value)?.into_iter().map(parse_scheme).collect()
+}
+
+/// Parses an `X-Forwarded-Host` value into hosts, one per proxy that set it.
+pub fn parse_x_forwarded_host(value: &[u8]) -> Result<Vec<String>, ForwardedError> {
+    list_entries(value)?.into_iter().map(parse_host).collect()
+}
+
+/// Parses an `X-Real-IP` value, an IPv4 or IPv6 address.
+pub fn parse_x_real_ip(value: &[u8]) -> Result<IpAddr, ForwardedError> {
+    let value = str::from_utf8(trim_ows(value)).map_err(|_| ForwardedError::InvalidAddress)?;
+    let value = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+    IpAddr::from_str(value).map_err(|_| ForwardedError::InvalidAddress)
+}
+
+/// An IP network in CIDR notation, such as `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// Creates a network of the addresses sharing the first `prefix` bits of `addr`.
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<IpNetwork, ForwardedError> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(ForwardedError::InvalidAddress);
+        }
+        Ok(IpNetwork { addr, prefix })
+    }
+
+    /// Returns true if `ip` is in the network. IPv4-mapped IPv6 addresses count as IPv4.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let bits = |ip: IpAddr| -> u128 {
+            match ip {
+                IpAddr::V4(ip) => u32::from(ip) as u128,
+                IpAddr::V6(ip) => u128::from(ip),
+            }
+        };
+        let ip = ip.to_canonical();
+        if ip.is_ipv4() != self.addr.is_ipv4() {
+            return false;
+        }
+        let width = if ip.is_ipv4() { 32 } else { 128 };
+        let shift = width - self.prefix as u32;
+        shift == width || bits(ip) >> shift == bits(self.addr) >> shift
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ForwardedError;
+
+    /// Parses `address/prefix`, or a single address.
+    fn from_str(s: &str) -> Result<IpNetwork, ForwardedError> {
+        let mut parts = s.trim().splitn(2, '/');
+        let addr = parts.next().unwrap_or_default();
+        let addr = IpAddr::from_str(addr).map_err(|_| ForwardedError::InvalidAddress)?.to_canonical();
+        let prefix = match parts.next() {
+            Some(prefix) => prefix.parse().map_err(|_| ForwardedError::InvalidAddress)?,
+            None => if addr.is_ipv4() { 32 } else { 128 },
+        };
+        IpNetwork::new(addr, prefix)
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// The client of a request as seen through trusted proxies.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ClientInfo {
+    /// The client, which is unknown or obfuscated if a proxy hid it
+    pub node: Node,
+    /// The scheme the client used, if a trusted proxy reported it
+    pub proto: Option<String>,
+    /// The host the client asked for, as reported by a trusted proxy or else `Host`
+    pub host: Option<String>,
+}
+
+impl ClientInfo {
+    /// The client IP address, unless a proxy hid it.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.node.ip()
+    }
+}
+
+// The entry of a list added by the same proxy as entry `index` of `hops` entries, as each
+// proxy appends to both. A single entry is usually set by the edge proxy alone.
+fn aligned<T: Clone>(list: &[T], hops: usize, index: usize) -> Option<T> {
+    if list.len() == 1 {
+        return Some(list[0].clone());
+    }
+    list.len().checked_sub(hops - index).map(|i| list[i].clone())
+}
+
+/// Resolves the client of a request from forwarding headers added by trusted proxies.
+///
+/// Only headers from a trusted peer are used. The hops are walked from the right, the
+/// proxy nearest to this server, and the first one that is not a trusted address is the
+/// client. An unknown or obfuscated hop ends the walk, as it can not be trusted.
+/// `Forwarded` is used over `X-Forwarded-For`, and `X-Real-IP` only without either.
+/// `X-Forwarded-Proto` and `X-Forwarded-Host` entries are matched to hops from the right,
+/// unless there is only one.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let proxies = TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()]);
+/// let mut headers = Headers::new();
+/// headers.push("X-Forwarded-For", "198.51.100.7, 203.0.113.9, 10.1.2.3");
+/// headers.push("X-Forwarded-Proto", "https");
+/// let client = proxies.resolve("10.0.0.1".parse().unwrap(), &headers).unwrap();
+/// assert_eq!(client.ip(), Some("203.0.113.9".parse().unwrap()));
+/// assert_eq!(client.proto, Some("https".to_string()));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct TrustedProxies {
+    /// The networks of the trusted proxies
+    pub networks: Vec<IpNetwork>,
+}
+
+impl TrustedProxies {
+    /// Creates a resolver trusting the proxies in `networks`.
+    pub fn new(networks: Vec<IpNetwork>) -> TrustedProxies {
+        TrustedProxies { networks }
+    }
+
+    /// Returns true if `ip` is in a trusted network.
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|n| n.contains(ip))
+    }
+
+    // The index of the client among the hops, walking from the right past trusted ones
+    fn client_index(&self, hops: &[Option<&Node>]) -> usize {
+        let mut i = hops.len() - 1;
+        while i > 0 && hops[i].and_then(Node::ip).is_some_and(|ip| self.is_trusted(ip)) {
+            i -= 1;
+        }
+        i
+    }
+
+    /// Resolves the client of a request received from `peer` with `headers`. A malformed
+    /// forwarding header from a trusted peer is an error.
+    pub fn resolve(&self, peer: IpAddr, headers: &Headers) -> Result<ClientInfo, ForwardedError> {
+        let host = headers.get("Host").map(|h| h.trim().to_string());
+        if !self.is_trusted(peer) {
+            return Ok(ClientInfo { node: Node::from(peer), proto: None, host });
+        }
+
+        if let Some(value) = headers.list("Forwarded") {
+            let forwarded = Forwarded::parse(value.as_bytes())?;
+            if !forwarded.elements.is_empty() {
+                let hops: Vec<Option<&Node>> = forwarded.elements.iter().map(|e| e.for_.as_ref()).collect();
+                let element = &forwarded.elements[self.client_index(&hops)];
+                return Ok(ClientInfo {
+                    node: element.for_.clone().unwrap_or(Node { name: NodeName::Unknown, port: None }),
+                    proto: element.proto.clone(),
+                    host: element.host.clone().or(host),
+                });
+            }
+        }
+
+        let protos = match headers.list("X-Forwarded-Proto") {
+            Some(value) => parse_x_forwarded_proto(value.as_bytes())?,
+            None => Vec::new(),
+        };
+        let hosts = match headers.list("X-Forwarded-Host") {
+            Some(value) => parse_x_forwarded_host(value.as_bytes())?,
+            None => Vec::new(),
+        };
+
+        if let Some(value) = headers.list("X-Forwarded-For") {
+            let nodes = parse_x_forwarded_for(value.as_bytes())?;
+            if !nodes.is_empty() {
+                let hops: Vec<Option<&Node>> = nodes.iter().map(Some).collect();
+                let index = self.client_index(&hops);
+                return Ok(ClientInfo {
+                    node: nodes[index].clone(),
+                    proto: aligned(&protos, nodes.len(), index),
+                    host: aligned(&hosts, nodes.len(), index).or(host),
+                });
+            }
+        }
+
+        if let Some(value) = headers.get("X-Real-IP") {
+            return Ok(ClientInfo {
+                node: Node::from(parse_x_real_ip(value.as_bytes())?),
+                proto: aligned(&protos, 1, 0),
+                host: aligned(&hosts, 1, 0).or(host),
+            });
+        }
+
+        Ok(ClientInfo { node: Node::from(peer), proto: aligned(&protos, 1, 0), host: aligned(&hosts, 1, 0).or(host) })
+    }
+}
@@ -0,0 +1,89 @@
+//! Shared pieces of the header field value grammar (RFC 9110 section 5.6): tokens,
+//! optional whitespace, quoted strings and comma-separated lists.
+
+use std::fmt;
+
+use parser::is_header_char;
+
+pub(crate) fn is_token(s: &[u8]) -> bool {
+    !s.is_empty() && s.iter().all(|&c| is_header_char(false, c))
+}
+
+// Strips the optional whitespace (spaces and tabs) around `value`.
+pub(crate) fn trim_ows(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|&c| c != b' ' && c != b'\t').unwrap_or(value.len());
+    let end = value.iter().rposition(|&c| c != b' ' && c != b'\t').map_or(start, |end| end + 1);
+    &value[start..end]
+}
+
+pub(crate) fn take_token(data: &[u8]) -> (&[u8], &[u8]) {
+    let n = data.iter().take_while(|&&c| is_header_char(false, c)).count();
+    (&data[..n], &data[n..])
+}
+
+// Parses the quoted string at the start of `data`, which starts with `"`, and returns its
+// unescaped content and what follows it, or `None` if it has no closing quote.
+pub(crate) fn take_quoted_string(data: &[u8]) -> Option<(Vec<u8>, &[u8])> {
+    let mut value = Vec::new();
+    let mut i = 1;
+    while i < data.len() {
+        match data[i] {
+            b'"' => return Some((value, &data[i + 1..])),
+            b'\\' if i + 1 < data.len() => {
+                value.push(data[i + 1]);
+                i += 2;
+            },
+            ch => {
+                value.push(ch);
+                i += 1;
+            },
+        }
+    }
+    None
+}
+
+// Splits `data` at each `separator` outside quoted strings and trims the parts, or returns
+// `None` if a quoted string has no closing quote.
+pub(crate) fn split_unquoted(data: &[u8], separator: u8) -> Option<Vec<&[u8]>> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'\\' if quoted => i += 1,
+            b'"' => quoted = !quoted,
+            ch if ch == separator && !quoted => {
+                parts.push(trim_ows(&data[start..i]));
+                start = i + 1;
+            },
+            _ => (),
+        }
+        i += 1;
+    }
+    if quoted {
+        return None;
+    }
+    parts.push(trim_ows(&data[start..]));
+    Some(parts)
+}
+
+// Writes `value` as a token, or as a quoted string if it is not one.
+pub(crate) fn write_token_or_quoted(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    if is_token(value.as_bytes()) {
+        return write!(f, "{}", value);
+    }
+    write_quoted(f, value)
+}
+
+// Writes `value` as a quoted string, escaping `"` and `\`.
+pub(crate) fn write_quoted(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for ch in value.chars() {
+        if ch == '"' || ch == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", ch)?;
+    }
+    write!(f, "\"")
+}
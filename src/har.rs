@@ -0,0 +1,526 @@
+//! HAR (HTTP Archive) 1.2 export and import of parsed messages.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64;
+use cookie::SetCookie;
+use json::Json;
+use http_date::{civil_from_days, days_from_civil};
+use http_method::HttpMethod;
+use http_version::HttpVersion;
+use message::{Headers, Message};
+
+const HAR_VERSION: &str = "1.2";
+const CREATOR_NAME: &str = "http_parser";
+const CREATOR_VERSION: &str = "0.0.2";
+
+/// `HarError` defines the errors encountered when importing a HAR document.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum HarError {
+    /// The document is not valid JSON
+    InvalidJson,
+    /// A mandatory field is missing or has the wrong type
+    MissingField(&'static str),
+    /// The request method is not supported
+    InvalidMethod,
+    /// The HTTP version string is not recognized
+    InvalidVersion,
+    /// The response status is out of range
+    InvalidStatus,
+    /// A body marked as base64 is not valid base64
+    InvalidContent,
+    /// The `startedDateTime` is not an ISO 8601 date
+    InvalidDate,
+}
+
+impl fmt::Display for HarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HarError::InvalidJson => write!(f, "invalid JSON document"),
+            HarError::MissingField(name) => write!(f, "missing or invalid field '{}'", name),
+            HarError::InvalidMethod => write!(f, "invalid HTTP method"),
+            HarError::InvalidVersion => write!(f, "invalid HTTP version"),
+            HarError::InvalidStatus => write!(f, "invalid HTTP status code"),
+            HarError::InvalidContent => write!(f, "invalid base64 content"),
+            HarError::InvalidDate => write!(f, "invalid ISO 8601 date"),
+        }
+    }
+}
+
+/// Time spent in each phase of an exchange, in milliseconds.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct HarTimings {
+    /// Time from the first to the last byte of the request
+    pub send: f64,
+    /// Time from the end of the request to the first byte of the response
+    pub wait: f64,
+    /// Time from the first to the last byte of the response
+    pub receive: f64,
+}
+
+impl HarTimings {
+    /// Total time of the exchange.
+    pub fn total(&self) -> f64 {
+        self.send + self.wait + self.receive
+    }
+}
+
+/// A request and its response, as recorded in a HAR `entries` array.
+#[derive(PartialEq, Clone, Debug)]
+pub struct HarEntry {
+    /// When the request started
+    pub started_date_time: Option<SystemTime>,
+    /// The request
+    pub request: Message,
+    /// The response, or `None` if the request did not get one
+    pub response: Option<Message>,
+    /// Phase timings
+    pub timings: HarTimings,
+}
+
+/// An HTTP Archive holding a list of exchanges.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let mut parser = HttpParser::new(HttpParserType::Request);
+/// let mut requests = MessageCollector::new();
+/// parser.execute(&mut requests, b"GET /a?b=c HTTP/1.1\r\nHost: example.com\r\n\r\n");
+///
+/// let mut har = Har::new();
+/// har.entries.push(HarEntry::new(requests.messages.remove(0), None));
+///
+/// let json = har.to_json();
+/// let imported = Har::from_json(&json).unwrap();
+/// assert_eq!(imported.entries[0].request_bytes(),
+///            b"GET http://example.com/a?b=c HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec());
+/// ```
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Har {
+    /// Recorded exchanges, in order
+    pub entries: Vec<HarEntry>,
+}
+
+fn millis_between(from: Option<SystemTime>, to: Option<SystemTime>) -> f64 {
+    match (from, to) {
+        (Some(from), Some(to)) => match to.duration_since(from) {
+            Ok(d) => d.as_secs() as f64 * 1000.0 + d.subsec_nanos() as f64 / 1_000_000.0,
+            Err(..) => 0.0,
+        },
+        _ => 0.0,
+    }
+}
+
+impl HarEntry {
+    /// Creates an entry from parsed messages, deriving timings from their timestamps.
+    pub fn new(request: Message, response: Option<Message>) -> HarEntry {
+        let timings = HarTimings {
+            send: millis_between(request.started, request.completed),
+            wait: millis_between(request.completed, response.as_ref().and_then(|r| r.started)),
+            receive: response.as_ref().map_or(0.0, |r| millis_between(r.started, r.completed)),
+        };
+        HarEntry {
+            started_date_time: request.started,
+            request,
+            response,
+            timings,
+        }
+    }
+
+    /// Serializes the request into raw HTTP/1.1 bytes that can be fed to `HttpParser::execute`.
+    pub fn request_bytes(&self) -> Vec<u8> {
+        wire_message(&self.request).to_bytes()
+    }
+
+    /// Serializes the response into raw HTTP/1.1 bytes that can be fed to `HttpParser::execute`.
+    pub fn response_bytes(&self) -> Option<Vec<u8>> {
+        self.response.as_ref().map(|r| wire_message(r).to_bytes())
+    }
+
+    fn to_json(&self) -> Json {
+        let started = self.started_date_time.unwrap_or(UNIX_EPOCH);
+        let empty = Message::default();
+        let response = self.response.as_ref().unwrap_or(&empty);
+
+        object(vec![
+            ("startedDateTime", Json::String(format_iso8601(started))),
+            ("time", Json::Number(self.timings.total())),
+            ("request", request_to_json(&self.request)),
+            ("response", response_to_json(response)),
+            ("cache", Json::Object(Vec::new())),
+            ("timings", object(vec![
+                ("blocked", Json::Number(-1.0)),
+                ("dns", Json::Number(-1.0)),
+                ("connect", Json::Number(-1.0)),
+                ("send", Json::Number(self.timings.send)),
+                ("wait", Json::Number(self.timings.wait)),
+                ("receive", Json::Number(self.timings.receive)),
+                ("ssl", Json::Number(-1.0)),
+            ])),
+        ])
+    }
+
+    fn from_json(entry: &Json) -> Result<HarEntry, HarError> {
+        let started = match entry.get("startedDateTime").and_then(Json::as_str) {
+            Some(s) => Some(parse_iso8601(s).ok_or(HarError::InvalidDate)?),
+            None => None,
+        };
+
+        let request_json = entry.get("request").ok_or(HarError::MissingField("request"))?;
+        let response_json = entry.get("response").ok_or(HarError::MissingField("response"))?;
+
+        let mut request = Message::default();
+        let method = str_field(request_json, "method")?;
+        request.method = Some(method.parse::<HttpMethod>().map_err(|_| HarError::InvalidMethod)?);
+        request.url = str_field(request_json, "url")?.to_string();
+        request.http_version = parse_har_version(str_field(request_json, "httpVersion")?)?;
+        request.headers = headers_from_json(request_json)?;
+        if let Some(post_data) = request_json.get("postData") {
+            request.body = body_from_json(post_data)?;
+        }
+
+        let status = response_json.get("status").and_then(Json::as_f64)
+            .ok_or(HarError::MissingField("status"))?;
+        if !(0.0..=999.0).contains(&status) || status.fract() != 0.0 {
+            return Err(HarError::InvalidStatus);
+        }
+        // status 0 means the request did not get a response
+        let response = if status == 0.0 {
+            None
+        } else {
+            let mut response = Message {
+                status_code: Some(status as u16),
+                status: str_field(response_json, "statusText")?.to_string(),
+                http_version: parse_har_version(str_field(response_json, "httpVersion")?)?,
+                headers: headers_from_json(response_json)?,
+                ..Default::default()
+            };
+            if let Some(content) = response_json.get("content") {
+                response.body = body_from_json(content)?;
+            }
+            Some(response)
+        };
+
+        let mut timings = HarTimings::default();
+        if let Some(t) = entry.get("timings") {
+            let phase = |name: &str| t.get(name).and_then(Json::as_f64).map_or(0.0, |v| v.max(0.0));
+            timings.send = phase("send");
+            timings.wait = phase("wait");
+            timings.receive = phase("receive");
+        }
+
+        Ok(HarEntry {
+            started_date_time: started,
+            request,
+            response,
+            timings,
+        })
+    }
+}
+
+impl Har {
+    /// Creates an empty archive.
+    pub fn new() -> Har {
+        Har { entries: Vec::new() }
+    }
+
+    /// Serializes the archive into HAR 1.2 JSON. The `cookies` arrays are filled from the
+    /// `Cookie` and `Set-Cookie` header fields.
+    pub fn to_json(&self) -> String {
+        let doc = object(vec![
+            ("log", object(vec![
+                ("version", Json::String(HAR_VERSION.to_string())),
+                ("creator", object(vec![
+                    ("name", Json::String(CREATOR_NAME.to_string())),
+                    ("version", Json::String(CREATOR_VERSION.to_string())),
+                ])),
+                ("entries", Json::Array(self.entries.iter().map(HarEntry::to_json).collect())),
+            ])),
+        ]);
+        doc.to_string()
+    }
+
+    /// Parses a HAR document. Cookies are taken from the header fields, so the `cookies`
+    /// arrays are not read.
+    pub fn from_json(s: &str) -> Result<Har, HarError> {
+        let doc = Json::parse(s).ok_or(HarError::InvalidJson)?;
+        let log = doc.get("log").ok_or(HarError::MissingField("log"))?;
+        let entries = log.get("entries").and_then(Json::as_array)
+            .ok_or(HarError::MissingField("entries"))?;
+
+        let mut har = Har::new();
+        for entry in entries {
+            har.entries.push(HarEntry::from_json(entry)?);
+        }
+        Ok(har)
+    }
+}
+
+fn object(members: Vec<(&str, Json)>) -> Json {
+    Json::Object(members.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn str_field<'a>(json: &'a Json, name: &'static str) -> Result<&'a str, HarError> {
+    json.get(name).and_then(Json::as_str).ok_or(HarError::MissingField(name))
+}
+
+fn name_value_list<'a, I: Iterator<Item=(&'a str, &'a str)>>(pairs: I) -> Json {
+    Json::Array(pairs.map(|(name, value)| object(vec![
+        ("name", Json::String(name.to_string())),
+        ("value", Json::String(value.to_string())),
+    ])).collect())
+}
+
+// Adds the `text` of a body, base64 with an `encoding` marker if it is not UTF-8.
+fn push_body_text(members: &mut Vec<(&str, Json)>, body: &[u8]) {
+    match String::from_utf8(body.to_vec()) {
+        Ok(text) => members.push(("text", Json::String(text))),
+        Err(..) => {
+            members.push(("text", Json::String(base64::encode(body))));
+            members.push(("encoding", Json::String("base64".to_string())));
+        },
+    }
+}
+
+// Reads the body from the `text` and `encoding` of `content` or `postData`.
+fn body_from_json(json: &Json) -> Result<Vec<u8>, HarError> {
+    match json.get("text").and_then(Json::as_str) {
+        Some(text) if json.get("encoding").and_then(Json::as_str) == Some("base64") =>
+            base64::decode(text.as_bytes()).ok_or(HarError::InvalidContent),
+        Some(text) => Ok(text.as_bytes().to_vec()),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn set_cookie_to_json(cookie: &SetCookie) -> Json {
+    let mut members = vec![
+        ("name", Json::String(cookie.name.clone())),
+        ("value", Json::String(cookie.value.clone())),
+    ];
+    if let Some(ref path) = cookie.path {
+        members.push(("path", Json::String(path.clone())));
+    }
+    if let Some(ref domain) = cookie.domain {
+        members.push(("domain", Json::String(domain.clone())));
+    }
+    if let Some(expires) = cookie.expires {
+        members.push(("expires", Json::String(format_iso8601(expires))));
+    }
+    members.push(("httpOnly", Json::Bool(cookie.http_only)));
+    members.push(("secure", Json::Bool(cookie.secure)));
+    if let Some(same_site) = cookie.same_site {
+        members.push(("sameSite", Json::String(same_site.to_string())));
+    }
+    object(members)
+}
+
+fn headers_to_json(headers: &Headers) -> Json {
+    name_value_list(headers.iter().map(|h| (&h.0[..], &h.1[..])))
+}
+
+fn headers_from_json(json: &Json) -> Result<Headers, HarError> {
+    let list = json.get("headers").and_then(Json::as_array)
+        .ok_or(HarError::MissingField("headers"))?;
+    let mut headers = Headers::new();
+    for header in list {
+        headers.push(str_field(header, "name")?, str_field(header, "value")?);
+    }
+    Ok(headers)
+}
+
+fn format_har_version(version: HttpVersion) -> String {
+    format!("HTTP/{}", version)
+}
+
+fn parse_har_version(s: &str) -> Result<HttpVersion, HarError> {
+    match &s.to_ascii_uppercase()[..] {
+        "HTTP/0.9" => Ok(HttpVersion { major: 0, minor: 9 }),
+        "HTTP/1.0" => Ok(HttpVersion { major: 1, minor: 0 }),
+        "HTTP/1.1" | "" => Ok(HttpVersion { major: 1, minor: 1 }),
+        "HTTP/2" | "HTTP/2.0" | "H2" | "H2C" => Ok(HttpVersion { major: 2, minor: 0 }),
+        "HTTP/3" | "HTTP/3.0" | "H3" => Ok(HttpVersion { major: 3, minor: 0 }),
+        _ => Err(HarError::InvalidVersion),
+    }
+}
+
+fn absolute_url(request: &Message) -> String {
+    if request.url.starts_with('/') {
+        if let Some(host) = request.headers.get("Host") {
+            return format!("http://{}{}", host, request.url);
+        }
+    }
+    request.url.clone()
+}
+
+fn query_string(url: &str) -> Json {
+    let query = match url.find('?') {
+        Some(i) => &url[i + 1..],
+        None => "",
+    };
+    let query = match query.find('#') {
+        Some(i) => &query[..i],
+        None => query,
+    };
+    name_value_list(query.split('&').filter(|p| !p.is_empty()).map(|pair| {
+        match pair.find('=') {
+            Some(i) => (&pair[..i], &pair[i + 1..]),
+            None => (pair, ""),
+        }
+    }))
+}
+
+fn request_to_json(request: &Message) -> Json {
+    let method = request.method.map_or(String::new(), |m| m.to_string());
+    let url = absolute_url(request);
+    let cookies = request.headers.cookies();
+    let mut members = vec![
+        ("method", Json::String(method)),
+        ("url", Json::String(url.clone())),
+        ("httpVersion", Json::String(format_har_version(request.http_version))),
+        ("cookies", name_value_list(cookies.iter().map(|c| (&c.0[..], &c.1[..])))),
+        ("headers", headers_to_json(&request.headers)),
+        ("queryString", query_string(&url)),
+    ];
+    if !request.body.is_empty() {
+        let mut post_data = vec![
+            ("mimeType", Json::String(request.headers.get("Content-Type").unwrap_or("").to_string())),
+        ];
+        push_body_text(&mut post_data, &request.body);
+        members.push(("postData", object(post_data)));
+    }
+    members.push(("headersSize", Json::Number(-1.0)));
+    members.push(("bodySize", Json::Number(request.body.len() as f64)));
+    object(members)
+}
+
+fn response_to_json(response: &Message) -> Json {
+    let mut content = vec![
+        ("size", Json::Number(response.body.len() as f64)),
+        ("mimeType", Json::String(response.headers.get("Content-Type").unwrap_or("").to_string())),
+    ];
+    if !response.body.is_empty() {
+        push_body_text(&mut content, &response.body);
+    }
+
+    let http_version = if response.status_code.is_some() {
+        format_har_version(response.http_version)
+    } else {
+        String::new()
+    };
+
+    object(vec![
+        ("status", Json::Number(response.status_code.unwrap_or(0) as f64)),
+        ("statusText", Json::String(response.status.clone())),
+        ("httpVersion", Json::String(http_version)),
+        ("cookies", Json::Array(response.headers.set_cookies().iter().map(set_cookie_to_json).collect())),
+        ("headers", headers_to_json(&response.headers)),
+        ("content", object(content)),
+        ("redirectURL", Json::String(response.headers.get("Location").unwrap_or("").to_string())),
+        ("headersSize", Json::Number(-1.0)),
+        ("bodySize", Json::Number(response.body.len() as f64)),
+    ])
+}
+
+/// Rewrites a message recorded from any HTTP version so it can be sent as HTTP/1.x.
+///
+/// HTTP/2 pseudo-headers are dropped (`:authority` becomes `Host`), and the body is framed
+/// with a `Content-Length` since the archived body is already de-chunked.
+fn wire_message(msg: &Message) -> Message {
+    let mut wire = msg.clone();
+    if wire.http_version.major != 1 {
+        wire.http_version = HttpVersion { major: 1, minor: 1 };
+    }
+
+    let mut headers = Headers::new();
+    let mut authority = None;
+    for (name, value) in msg.headers.iter() {
+        if name.starts_with(':') {
+            if name == ":authority" {
+                authority = Some(value.clone());
+            }
+            continue;
+        }
+        if name.eq_ignore_ascii_case("Content-Length") ||
+            name.eq_ignore_ascii_case("Transfer-Encoding") {
+            continue;
+        }
+        headers.push(name, value);
+    }
+    if let Some(host) = authority {
+        if !headers.contains("Host") {
+            headers.push("Host", &host);
+        }
+    }
+
+    let bodiless_status = msg.status().is_some_and(|status| !status.allows_body());
+    if !msg.body.is_empty() || (!msg.is_request() && !bodiless_status) {
+        headers.push("Content-Length", &msg.body.len().to_string());
+    }
+    wire.headers = headers;
+    wire
+}
+
+fn format_iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_else(|_| Duration::from_secs(0));
+    let secs = since_epoch.as_secs() as i64;
+    let (year, month, day) = civil_from_days(secs / 86400);
+    let rem = secs % 86400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year, month, day, rem / 3600, rem / 60 % 60, rem % 60,
+            since_epoch.subsec_millis())
+}
+
+fn parse_digits(s: &[u8]) -> Option<i64> {
+    if s.is_empty() || !s.iter().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(s.iter().fold(0i64, |acc, c| acc * 10 + (c - b'0') as i64))
+}
+
+fn parse_iso8601(s: &str) -> Option<SystemTime> {
+    let b = s.as_bytes();
+    if b.len() < 19 || b[4] != b'-' || b[7] != b'-' || b[10] != b'T' || b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+    let year = parse_digits(&b[0..4])?;
+    let month = parse_digits(&b[5..7])? as u32;
+    let day = parse_digits(&b[8..10])? as u32;
+    let hour = parse_digits(&b[11..13])?;
+    let minute = parse_digits(&b[14..16])?;
+    let second = parse_digits(&b[17..19])?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &b[19..];
+    let mut nanos: u64 = 0;
+    if rest.first() == Some(&b'.') {
+        let digits = rest[1..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let mut scale = 100_000_000;
+        for &c in rest[1..1 + digits].iter().take(9) {
+            nanos += (c - b'0') as u64 * scale;
+            scale /= 10;
+        }
+        rest = &rest[1 + digits..];
+    }
+
+    let offset = match rest {
+        b"Z" | b"z" => 0,
+        _ if rest.len() == 6 && (rest[0] == b'+' || rest[0] == b'-') && rest[3] == b':' => {
+            let minutes = parse_digits(&rest[1..3])? * 60 + parse_digits(&rest[4..6])?;
+            if rest[0] == b'+' { minutes * 60 } else { -minutes * 60 }
+        },
+        _ => return None,
+    };
+
+    let secs = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+    if secs < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::new(secs as u64, nanos as u32))
+}
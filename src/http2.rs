@@ -0,0 +1,174 @@
+//! HTTP/2 cleartext upgrade (`Upgrade: h2c`) and SETTINGS payload decoding (RFC 7540).
+
+use std::fmt;
+
+use base64;
+use message::Message;
+
+const H2C: &str = "h2c";
+const HTTP2_SETTINGS: &str = "HTTP2-Settings";
+
+const MAX_WINDOW_SIZE: u32 = (1 << 31) - 1;
+const MIN_FRAME_SIZE: u32 = 1 << 14;
+const MAX_FRAME_SIZE: u32 = (1 << 24) - 1;
+
+/// `Http2Error` defines the errors encountered when validating an h2c upgrade.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Http2Error {
+    /// `Upgrade: h2c` is missing
+    MissingUpgrade,
+    /// `Connection` doesn't list both `Upgrade` and `HTTP2-Settings`
+    MissingConnectionOptions,
+    /// There isn't exactly one `HTTP2-Settings` header
+    MissingSettings,
+    /// `HTTP2-Settings` is not valid base64url
+    InvalidBase64,
+    /// The SETTINGS payload is not a multiple of 6 bytes
+    InvalidSettingsLength,
+    /// A setting has a value outside of its allowed range
+    InvalidSettingValue(u16),
+    /// The response status is not `101 Switching Protocols`
+    InvalidStatus,
+}
+
+impl fmt::Display for Http2Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Http2Error::MissingUpgrade => write!(f, "missing 'Upgrade: h2c' header"),
+            Http2Error::MissingConnectionOptions =>
+                write!(f, "'Connection' must list 'Upgrade' and 'HTTP2-Settings'"),
+            Http2Error::MissingSettings => write!(f, "expected exactly one 'HTTP2-Settings' header"),
+            Http2Error::InvalidBase64 => write!(f, "'HTTP2-Settings' is not valid base64url"),
+            Http2Error::InvalidSettingsLength => write!(f, "SETTINGS payload length is not a multiple of 6"),
+            Http2Error::InvalidSettingValue(id) => write!(f, "invalid value for setting {:#x}", id),
+            Http2Error::InvalidStatus => write!(f, "h2c upgrade response is not 101"),
+        }
+    }
+}
+
+/// The parameters of an HTTP/2 SETTINGS frame. Settings that were not sent are `None`.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Http2Settings {
+    /// SETTINGS_HEADER_TABLE_SIZE (0x1)
+    pub header_table_size: Option<u32>,
+    /// SETTINGS_ENABLE_PUSH (0x2)
+    pub enable_push: Option<bool>,
+    /// SETTINGS_MAX_CONCURRENT_STREAMS (0x3)
+    pub max_concurrent_streams: Option<u32>,
+    /// SETTINGS_INITIAL_WINDOW_SIZE (0x4)
+    pub initial_window_size: Option<u32>,
+    /// SETTINGS_MAX_FRAME_SIZE (0x5)
+    pub max_frame_size: Option<u32>,
+    /// SETTINGS_MAX_HEADER_LIST_SIZE (0x6)
+    pub max_header_list_size: Option<u32>,
+    /// Settings with an identifier this crate doesn't know, which must be ignored
+    pub unknown: Vec<(u16, u32)>,
+}
+
+impl Http2Settings {
+    /// Decodes a SETTINGS frame payload. When a setting is repeated the last value wins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use http_parser::*;
+    /// let settings = Http2Settings::decode(&[0, 3, 0, 0, 0, 100]).unwrap();
+    /// assert_eq!(settings.max_concurrent_streams, Some(100));
+    /// ```
+    pub fn decode(payload: &[u8]) -> Result<Http2Settings, Http2Error> {
+        if payload.len() % 6 != 0 {
+            return Err(Http2Error::InvalidSettingsLength);
+        }
+
+        let mut settings = Http2Settings::default();
+        for setting in payload.chunks(6) {
+            let id = u16::from_be_bytes([setting[0], setting[1]]);
+            let value = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+            match id {
+                0x1 => settings.header_table_size = Some(value),
+                0x2 => settings.enable_push = match value {
+                    0 => Some(false),
+                    1 => Some(true),
+                    _ => return Err(Http2Error::InvalidSettingValue(id)),
+                },
+                0x3 => settings.max_concurrent_streams = Some(value),
+                0x4 => {
+                    if value > MAX_WINDOW_SIZE {
+                        return Err(Http2Error::InvalidSettingValue(id));
+                    }
+                    settings.initial_window_size = Some(value);
+                },
+                0x5 => {
+                    if !(MIN_FRAME_SIZE..=MAX_FRAME_SIZE).contains(&value) {
+                        return Err(Http2Error::InvalidSettingValue(id));
+                    }
+                    settings.max_frame_size = Some(value);
+                },
+                0x6 => settings.max_header_list_size = Some(value),
+                _ => settings.unknown.push((id, value)),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Decodes the base64url value of an `HTTP2-Settings` header.
+    pub fn from_base64url(value: &str) -> Result<Http2Settings, Http2Error> {
+        let payload = base64::decode_url(value.trim().as_bytes()).ok_or(Http2Error::InvalidBase64)?;
+        Http2Settings::decode(&payload)
+    }
+}
+
+/// A validated `Upgrade: h2c` request.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let mut parser = HttpParser::new(HttpParserType::Request);
+/// let mut cb = MessageCollector::new();
+/// parser.execute(&mut cb, b"GET / HTTP/1.1\r\n\
+///                           Host: server.example.com\r\n\
+///                           Connection: Upgrade, HTTP2-Settings\r\n\
+///                           Upgrade: h2c\r\n\
+///                           HTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n");
+///
+/// let upgrade = H2cUpgrade::from_request(&cb.messages[0]).unwrap();
+/// assert_eq!(upgrade.settings.max_concurrent_streams, Some(100));
+/// assert_eq!(upgrade.settings.initial_window_size, Some(65535));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct H2cUpgrade {
+    /// The client's initial settings from `HTTP2-Settings`
+    pub settings: Http2Settings,
+}
+
+impl H2cUpgrade {
+    /// Validates an HTTP/1.1 request asking to upgrade to HTTP/2 over cleartext.
+    pub fn from_request(request: &Message) -> Result<H2cUpgrade, Http2Error> {
+        if !request.headers.has_token("Upgrade", H2C) {
+            return Err(Http2Error::MissingUpgrade);
+        }
+        if !request.headers.has_token("Connection", "upgrade") ||
+            !request.headers.has_token("Connection", HTTP2_SETTINGS) {
+            return Err(Http2Error::MissingConnectionOptions);
+        }
+
+        let values = request.headers.get_all(HTTP2_SETTINGS);
+        if values.len() != 1 {
+            return Err(Http2Error::MissingSettings);
+        }
+        Ok(H2cUpgrade { settings: Http2Settings::from_base64url(values[0])? })
+    }
+
+    /// Validates that the server accepted the upgrade. Any other response means the
+    /// server stays on HTTP/1.1.
+    pub fn validate_response(&self, response: &Message) -> Result<(), Http2Error> {
+        if response.status_code != Some(101) {
+            return Err(Http2Error::InvalidStatus);
+        }
+        if !response.headers.has_token("Upgrade", H2C) {
+            return Err(Http2Error::MissingUpgrade);
+        }
+        Ok(())
+    }
+}
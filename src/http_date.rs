@@ -0,0 +1,241 @@
+//! HTTP-date (RFC 9110 section 5.6.7) of `Date`, `Last-Modified`, `If-Modified-Since`,
+//! `Expires` and `Retry-After`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use grammar::trim_ows;
+
+pub(crate) const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun",
+                                       "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const LONG_WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday",
+                                  "Thursday", "Friday", "Saturday"];
+
+/// `HttpDateError` defines the errors encountered when parsing an HTTP-date.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum HttpDateError {
+    /// The value is not an IMF-fixdate, RFC 850 or asctime date
+    InvalidFormat,
+    /// The day does not exist in the month, or the time of day is out of range
+    InvalidDate,
+}
+
+impl fmt::Display for HttpDateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HttpDateError::InvalidFormat => write!(f, "invalid HTTP-date format"),
+            HttpDateError::InvalidDate => write!(f, "invalid HTTP-date"),
+        }
+    }
+}
+
+// Howard Hinnant's days-from-civil algorithm, for the proleptic Gregorian calendar.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn number(data: &[u8]) -> Option<i64> {
+    if data.is_empty() {
+        return None;
+    }
+    data.iter().try_fold(0i64, |acc, &c| {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        acc.checked_mul(10)?.checked_add((c - b'0') as i64)
+    })
+}
+
+fn position(names: &[&str], name: &[u8]) -> Option<usize> {
+    names.iter().position(|n| n.as_bytes().eq_ignore_ascii_case(name))
+}
+
+// `HH:MM:SS`
+fn time_of_day(data: &[u8]) -> Option<(i64, i64, i64)> {
+    if data.len() != 8 || data[2] != b':' || data[5] != b':' {
+        return None;
+    }
+    Some((number(&data[..2])?, number(&data[3..5])?, number(&data[6..])?))
+}
+
+// The year of a two-digit RFC 850 year: one more than 50 years in the future is in the
+// past century instead.
+fn full_year(two_digits: i64) -> i64 {
+    let now = HttpDate::from(SystemTime::now()).secs;
+    let this_year = civil_from_days(now.div_euclid(86400)).0;
+    let year = this_year - this_year % 100 + two_digits;
+    if year > this_year + 50 { year - 100 } else { year }
+}
+
+/// A point in time of an HTTP-date, to the second.
+///
+/// `parse` accepts the IMF-fixdate format and the obsolete RFC 850 and asctime formats;
+/// `Display` formats IMF-fixdate, the only one senders may generate.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// # use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// let date = HttpDate::parse(b"Sunday, 06-Nov-94 08:49:37 GMT").unwrap();
+/// assert_eq!(date, HttpDate::parse(b"Sun Nov  6 08:49:37 1994").unwrap());
+/// assert_eq!(date.to_string(), "Sun, 06 Nov 1994 08:49:37 GMT");
+/// assert_eq!(SystemTime::from(date), UNIX_EPOCH + Duration::from_secs(784111777));
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub struct HttpDate {
+    // seconds since the Unix epoch
+    secs: i64,
+}
+
+impl HttpDate {
+    /// Parses an HTTP-date from a header value, such as the data of `on_header_value`.
+    /// The day name is not checked against the date.
+    pub fn parse(value: &[u8]) -> Result<HttpDate, HttpDateError> {
+        let value = trim_ows(value);
+        let (year, month, day, time) = if value.get(3) == Some(&b',') {
+            // Sun, 06 Nov 1994 08:49:37 GMT
+            if value.len() != 29 || value[4] != b' ' || value[7] != b' ' || value[11] != b' ' ||
+                    value[16] != b' ' || &value[25..] != b" GMT" {
+                return Err(HttpDateError::InvalidFormat);
+            }
+            position(&WEEKDAYS, &value[..3]).ok_or(HttpDateError::InvalidFormat)?;
+            (number(&value[12..16]), position(&MONTHS, &value[8..11]), number(&value[5..7]),
+             time_of_day(&value[17..25]))
+        } else if let Some(comma) = value.iter().position(|&c| c == b',') {
+            // Sunday, 06-Nov-94 08:49:37 GMT
+            position(&LONG_WEEKDAYS, &value[..comma]).ok_or(HttpDateError::InvalidFormat)?;
+            let rest = &value[comma + 1..];
+            if rest.len() != 23 || rest[0] != b' ' || rest[3] != b'-' || rest[7] != b'-' ||
+                    rest[10] != b' ' || &rest[19..] != b" GMT" {
+                return Err(HttpDateError::InvalidFormat);
+            }
+            (number(&rest[8..10]).map(full_year), position(&MONTHS, &rest[4..7]), number(&rest[1..3]),
+             time_of_day(&rest[11..19]))
+        } else {
+            // Sun Nov  6 08:49:37 1994
+            if value.len() != 24 || value[3] != b' ' || value[7] != b' ' || value[10] != b' ' ||
+                    value[19] != b' ' {
+                return Err(HttpDateError::InvalidFormat);
+            }
+            position(&WEEKDAYS, &value[..3]).ok_or(HttpDateError::InvalidFormat)?;
+            let day = if value[8] == b' ' { &value[9..10] } else { &value[8..10] };
+            (number(&value[20..]), position(&MONTHS, &value[4..7]), number(day),
+             time_of_day(&value[11..19]))
+        };
+
+        let (year, month, day, (hour, minute, second)) = match (year, month, day, time) {
+            (Some(year), Some(month), Some(day), Some(time)) => (year, month as u32 + 1, day, time),
+            _ => return Err(HttpDateError::InvalidFormat),
+        };
+        // second 60 is a leap second
+        if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+            return Err(HttpDateError::InvalidDate);
+        }
+        let days = days_from_civil(year, month, day as u32);
+        if civil_from_days(days) != (year, month, day as u32) {
+            return Err(HttpDateError::InvalidDate);
+        }
+        Ok(HttpDate { secs: days * 86400 + hour * 3600 + minute * 60 + second })
+    }
+}
+
+impl From<SystemTime> for HttpDate {
+    /// Truncates `time` to the second before it.
+    fn from(time: SystemTime) -> HttpDate {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => {
+                let d = e.duration();
+                -(d.as_secs() as i64) - if d.subsec_nanos() > 0 { 1 } else { 0 }
+            },
+        };
+        HttpDate { secs }
+    }
+}
+
+impl From<HttpDate> for SystemTime {
+    fn from(date: HttpDate) -> SystemTime {
+        if date.secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(date.secs as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(date.secs.unsigned_abs())
+        }
+    }
+}
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let days = self.secs.div_euclid(86400);
+        let rem = self.secs.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        write!(f, "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+               WEEKDAYS[(days + 4).rem_euclid(7) as usize], day, MONTHS[month as usize - 1], year,
+               rem / 3600, rem / 60 % 60, rem % 60)
+    }
+}
+
+impl FromStr for HttpDate {
+    type Err = HttpDateError;
+
+    fn from_str(s: &str) -> Result<HttpDate, HttpDateError> {
+        HttpDate::parse(s.as_bytes())
+    }
+}
+
+/// The value of `Retry-After` (RFC 9110 section 10.2.3): an HTTP-date or a delay.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum RetryAfter {
+    /// Retry after this date
+    Date(HttpDate),
+    /// Retry after this many seconds
+    Delay(u64),
+}
+
+impl RetryAfter {
+    /// Parses a `Retry-After` value.
+    pub fn parse(value: &[u8]) -> Result<RetryAfter, HttpDateError> {
+        match number(trim_ows(value)) {
+            Some(delay) => Ok(RetryAfter::Delay(delay as u64)),
+            None => HttpDate::parse(value).map(RetryAfter::Date),
+        }
+    }
+
+    /// The time to wait from `now`, which is zero if the date has passed.
+    pub fn delay(&self, now: SystemTime) -> Duration {
+        match *self {
+            RetryAfter::Date(date) => SystemTime::from(date).duration_since(now).unwrap_or_default(),
+            RetryAfter::Delay(secs) => Duration::from_secs(secs),
+        }
+    }
+}
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RetryAfter::Date(ref date) => write!(f, "{}", date),
+            RetryAfter::Delay(secs) => write!(f, "{}", secs),
+        }
+    }
+}
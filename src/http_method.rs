@@ -1,5 +1,9 @@
+use std::str::FromStr;
+
+use error::HttpErrno;
+
 /// `HttpMethod` defines supported HTTP methods.
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum HttpMethod {
     Delete,
     Get,
@@ -34,6 +38,35 @@ pub enum HttpMethod {
     Purge,
     // CalDAV
     MKCalendar,
+    // HTTP/2 connection preface (RFC 7540)
+    Pri,
+    // RTSP
+    Describe,
+    Announce,
+    Setup,
+    Play,
+    Pause,
+    Teardown,
+    GetParameter,
+    SetParameter,
+    Redirect,
+    Record,
+    PlayNotify,
+    // SIP
+    Invite,
+    Ack,
+    Bye,
+    Cancel,
+    Register,
+    Prack,
+    Publish,
+    Info,
+    Refer,
+    Message,
+    Update,
+    // ICAP
+    ReqMod,
+    RespMod,
 }
 
 impl ToString for HttpMethod {
@@ -43,7 +76,7 @@ impl ToString for HttpMethod {
             HttpMethod::Get         => "GET".to_string(),
             HttpMethod::Head        => "HEAD".to_string(),
             HttpMethod::Post        => "POST".to_string(),
-            HttpMethod::Put         => "Put".to_string(),
+            HttpMethod::Put         => "PUT".to_string(),
             HttpMethod::Connect     => "CONNECT".to_string(),
             HttpMethod::Options     => "OPTIONS".to_string(),
             HttpMethod::Trace       => "TRACE".to_string(),
@@ -66,6 +99,93 @@ impl ToString for HttpMethod {
             HttpMethod::Patch       => "PATCH".to_string(),
             HttpMethod::Purge       => "PURGE".to_string(),
             HttpMethod::MKCalendar  => "MKCALENDAR".to_string(),
+            HttpMethod::Pri         => "PRI".to_string(),
+            HttpMethod::Describe    => "DESCRIBE".to_string(),
+            HttpMethod::Announce    => "ANNOUNCE".to_string(),
+            HttpMethod::Setup       => "SETUP".to_string(),
+            HttpMethod::Play        => "PLAY".to_string(),
+            HttpMethod::Pause       => "PAUSE".to_string(),
+            HttpMethod::Teardown    => "TEARDOWN".to_string(),
+            HttpMethod::GetParameter => "GET_PARAMETER".to_string(),
+            HttpMethod::SetParameter => "SET_PARAMETER".to_string(),
+            HttpMethod::Redirect    => "REDIRECT".to_string(),
+            HttpMethod::Record      => "RECORD".to_string(),
+            HttpMethod::PlayNotify  => "PLAY_NOTIFY".to_string(),
+            HttpMethod::Invite      => "INVITE".to_string(),
+            HttpMethod::Ack         => "ACK".to_string(),
+            HttpMethod::Bye         => "BYE".to_string(),
+            HttpMethod::Cancel      => "CANCEL".to_string(),
+            HttpMethod::Register    => "REGISTER".to_string(),
+            HttpMethod::Prack       => "PRACK".to_string(),
+            HttpMethod::Publish     => "PUBLISH".to_string(),
+            HttpMethod::Info        => "INFO".to_string(),
+            HttpMethod::Refer       => "REFER".to_string(),
+            HttpMethod::Message     => "MESSAGE".to_string(),
+            HttpMethod::Update      => "UPDATE".to_string(),
+            HttpMethod::ReqMod      => "REQMOD".to_string(),
+            HttpMethod::RespMod     => "RESPMOD".to_string(),
+        }
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = HttpErrno;
+
+    fn from_str(s: &str) -> Result<HttpMethod, HttpErrno> {
+        match s {
+            "DELETE"      => Ok(HttpMethod::Delete),
+            "GET"         => Ok(HttpMethod::Get),
+            "HEAD"        => Ok(HttpMethod::Head),
+            "POST"        => Ok(HttpMethod::Post),
+            "PUT"         => Ok(HttpMethod::Put),
+            "CONNECT"     => Ok(HttpMethod::Connect),
+            "OPTIONS"     => Ok(HttpMethod::Options),
+            "TRACE"       => Ok(HttpMethod::Trace),
+            "COPY"        => Ok(HttpMethod::Copy),
+            "LOCK"        => Ok(HttpMethod::Lock),
+            "MKCOL"       => Ok(HttpMethod::MKCol),
+            "MOVE"        => Ok(HttpMethod::Move),
+            "PROPFIND"    => Ok(HttpMethod::PropFind),
+            "PROPPATCH"   => Ok(HttpMethod::PropPatch),
+            "SEARCH"      => Ok(HttpMethod::Search),
+            "UNLOCK"      => Ok(HttpMethod::Unlock),
+            "REPORT"      => Ok(HttpMethod::Report),
+            "MKACTIVITY"  => Ok(HttpMethod::MKActivity),
+            "CHECKOUT"    => Ok(HttpMethod::Checkout),
+            "MERGE"       => Ok(HttpMethod::Merge),
+            "M-SEARCH"    => Ok(HttpMethod::MSearch),
+            "NOTIFY"      => Ok(HttpMethod::Notify),
+            "SUBSCRIBE"   => Ok(HttpMethod::Subscribe),
+            "UNSUBSCRIBE" => Ok(HttpMethod::Unsubscribe),
+            "PATCH"       => Ok(HttpMethod::Patch),
+            "PURGE"       => Ok(HttpMethod::Purge),
+            "MKCALENDAR"  => Ok(HttpMethod::MKCalendar),
+            "PRI"         => Ok(HttpMethod::Pri),
+            "DESCRIBE"      => Ok(HttpMethod::Describe),
+            "ANNOUNCE"      => Ok(HttpMethod::Announce),
+            "SETUP"         => Ok(HttpMethod::Setup),
+            "PLAY"          => Ok(HttpMethod::Play),
+            "PAUSE"         => Ok(HttpMethod::Pause),
+            "TEARDOWN"      => Ok(HttpMethod::Teardown),
+            "GET_PARAMETER" => Ok(HttpMethod::GetParameter),
+            "SET_PARAMETER" => Ok(HttpMethod::SetParameter),
+            "REDIRECT"      => Ok(HttpMethod::Redirect),
+            "RECORD"        => Ok(HttpMethod::Record),
+            "PLAY_NOTIFY"   => Ok(HttpMethod::PlayNotify),
+            "INVITE"        => Ok(HttpMethod::Invite),
+            "ACK"           => Ok(HttpMethod::Ack),
+            "BYE"           => Ok(HttpMethod::Bye),
+            "CANCEL"        => Ok(HttpMethod::Cancel),
+            "REGISTER"      => Ok(HttpMethod::Register),
+            "PRACK"         => Ok(HttpMethod::Prack),
+            "PUBLISH"       => Ok(HttpMethod::Publish),
+            "INFO"          => Ok(HttpMethod::Info),
+            "REFER"         => Ok(HttpMethod::Refer),
+            "MESSAGE"       => Ok(HttpMethod::Message),
+            "UPDATE"        => Ok(HttpMethod::Update),
+            "REQMOD"        => Ok(HttpMethod::ReqMod),
+            "RESPMOD"       => Ok(HttpMethod::RespMod),
+            _             => Err(HttpErrno::InvalidMethod),
         }
     }
 }
@@ -1,7 +1,10 @@
 use std::fmt;
+use std::str::FromStr;
+
+use error::HttpErrno;
 
 /// HTTP protocol version.
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Default)]
 pub struct HttpVersion {
     /// Major version
     pub major: u8,
@@ -9,8 +12,47 @@ pub struct HttpVersion {
     pub minor: u8,
 }
 
+impl HttpVersion {
+    /// HTTP/0.9
+    pub const HTTP_09: HttpVersion = HttpVersion { major: 0, minor: 9 };
+    /// HTTP/1.0
+    pub const HTTP_10: HttpVersion = HttpVersion { major: 1, minor: 0 };
+    /// HTTP/1.1
+    pub const HTTP_11: HttpVersion = HttpVersion { major: 1, minor: 1 };
+    /// HTTP/2, as in the connection preface
+    pub const HTTP_20: HttpVersion = HttpVersion { major: 2, minor: 0 };
+}
+
 impl fmt::Display for HttpVersion {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}.{}", self.major, self.minor)
     }
 }
+
+/// Parses a version as it is displayed, such as `1.1`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// assert_eq!("1.1".parse(), Ok(HttpVersion::HTTP_11));
+/// assert!(HttpVersion::HTTP_10 < HttpVersion::HTTP_11);
+/// ```
+impl FromStr for HttpVersion {
+    type Err = HttpErrno;
+
+    fn from_str(s: &str) -> Result<HttpVersion, HttpErrno> {
+        let mut parts = s.splitn(2, '.');
+        match (parts.next(), parts.next()) {
+            (Some(major), Some(minor)) if is_number(major) && is_number(minor) => Ok(HttpVersion {
+                major: major.parse().map_err(|_| HttpErrno::InvalidVersion)?,
+                minor: minor.parse().map_err(|_| HttpErrno::InvalidVersion)?,
+            }),
+            _ => Err(HttpErrno::InvalidVersion),
+        }
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
@@ -0,0 +1,529 @@
+//! ICAP (RFC 3507) messages, whose encapsulated HTTP heads and bodies are parsed by nested
+//! `HttpParser`s.
+
+use std::cmp;
+use std::fmt;
+use std::mem;
+
+use parser::{HttpParser, HttpParserType};
+use error::HttpErrno;
+use protocol::Protocol;
+use callback::{HttpParserCallback, CallbackResult, ParseAction};
+use message::{Message, MessageCollector};
+
+const ENCAPSULATED: &str = "Encapsulated";
+
+/// `IcapError` defines the errors encountered when parsing an ICAP message.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum IcapError {
+    /// The ICAP head or an encapsulated HTTP message could not be parsed
+    Http(HttpErrno),
+    /// The `Encapsulated` header is malformed
+    InvalidEncapsulated,
+    /// An encapsulated HTTP head doesn't end where the `Encapsulated` offsets say
+    InvalidSectionLength,
+    /// The connection was closed in the middle of a message
+    UnexpectedEof,
+    /// An ICAP callback returned an error
+    CallbackFailed,
+}
+
+impl fmt::Display for IcapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IcapError::Http(errno) => write!(f, "{}", errno),
+            IcapError::InvalidEncapsulated => write!(f, "invalid 'Encapsulated' header"),
+            IcapError::InvalidSectionLength =>
+                write!(f, "encapsulated HTTP head doesn't match its 'Encapsulated' offsets"),
+            IcapError::UnexpectedEof => write!(f, "unexpected EOF in ICAP message"),
+            IcapError::CallbackFailed => write!(f, "the ICAP callback failed"),
+        }
+    }
+}
+
+/// A section of the encapsulated part of an ICAP message, as named in the `Encapsulated`
+/// header.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum IcapSection {
+    /// `req-hdr`, the head of the HTTP request
+    ReqHdr,
+    /// `res-hdr`, the head of the HTTP response
+    ResHdr,
+    /// `req-body`, the chunked body of the HTTP request
+    ReqBody,
+    /// `res-body`, the chunked body of the HTTP response
+    ResBody,
+    /// `opt-body`, the chunked body of an OPTIONS response
+    OptBody,
+    /// `null-body`, there is no body
+    NullBody,
+}
+
+impl IcapSection {
+    /// The name of the section in the `Encapsulated` header.
+    pub fn name(self) -> &'static str {
+        match self {
+            IcapSection::ReqHdr   => "req-hdr",
+            IcapSection::ResHdr   => "res-hdr",
+            IcapSection::ReqBody  => "req-body",
+            IcapSection::ResBody  => "res-body",
+            IcapSection::OptBody  => "opt-body",
+            IcapSection::NullBody => "null-body",
+        }
+    }
+
+    /// Returns true for the body sections, one of which ends the encapsulated part.
+    pub fn is_body(self) -> bool {
+        !matches!(self, IcapSection::ReqHdr | IcapSection::ResHdr)
+    }
+
+    fn from_name(name: &str) -> Option<IcapSection> {
+        match name {
+            "req-hdr"   => Some(IcapSection::ReqHdr),
+            "res-hdr"   => Some(IcapSection::ResHdr),
+            "req-body"  => Some(IcapSection::ReqBody),
+            "res-body"  => Some(IcapSection::ResBody),
+            "opt-body"  => Some(IcapSection::OptBody),
+            "null-body" => Some(IcapSection::NullBody),
+            _           => None,
+        }
+    }
+
+    // The head section whose message the body belongs to
+    fn head(self) -> Option<IcapSection> {
+        match self {
+            IcapSection::ReqBody => Some(IcapSection::ReqHdr),
+            IcapSection::ResBody => Some(IcapSection::ResHdr),
+            _                    => None,
+        }
+    }
+}
+
+impl fmt::Display for IcapSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Parses the value of an `Encapsulated` header into its sections and their offsets.
+///
+/// The offsets must start at 0 and increase, the request head must come before the
+/// response head, and exactly one body section must come last.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// assert_eq!(parse_encapsulated("req-hdr=0, res-hdr=137, res-body=296"),
+///            Ok(vec![(IcapSection::ReqHdr, 0), (IcapSection::ResHdr, 137),
+///                    (IcapSection::ResBody, 296)]));
+/// assert_eq!(parse_encapsulated("res-body=0, res-hdr=20"), Err(IcapError::InvalidEncapsulated));
+/// ```
+pub fn parse_encapsulated(value: &str) -> Result<Vec<(IcapSection, usize)>, IcapError> {
+    let mut sections: Vec<(IcapSection, usize)> = Vec::new();
+    for entry in value.split(',') {
+        let mut parts = entry.trim().splitn(2, '=');
+        let section = parts.next().and_then(IcapSection::from_name);
+        let offset = parts.next()
+            .filter(|o| !o.is_empty() && o.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|o| o.parse().ok());
+        let (section, offset) = match (section, offset) {
+            (Some(section), Some(offset)) => (section, offset),
+            _ => return Err(IcapError::InvalidEncapsulated),
+        };
+
+        let valid = match sections.last() {
+            None => offset == 0,
+            Some(&(last, last_offset)) => !last.is_body() && offset > last_offset &&
+                (section.is_body() || (last == IcapSection::ReqHdr && section == IcapSection::ResHdr)),
+        };
+        if !valid {
+            return Err(IcapError::InvalidEncapsulated);
+        }
+        sections.push((section, offset));
+    }
+
+    match sections.last() {
+        Some(&(last, _)) if last.is_body() => Ok(sections),
+        _ => Err(IcapError::InvalidEncapsulated),
+    }
+}
+
+/// `IcapCallback` receives the callbacks of the encapsulated HTTP messages, like
+/// `HttpParserCallback`, plus those of the ICAP messages around them.
+pub trait IcapCallback: HttpParserCallback {
+    /// Function called when the ICAP head is parsed, before any encapsulated section.
+    /// The head is in `parser.head`.
+    #[allow(unused_variables)]
+    fn on_icap_head(&mut self, parser: &mut IcapParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called when the ICAP message, including all its sections, is complete.
+    #[allow(unused_variables)]
+    fn on_icap_message_complete(&mut self, parser: &mut IcapParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+}
+
+// Forwards the callbacks of a nested parser and pauses it at the end of its message, so
+// that it doesn't read into the next section
+struct Forward<'a, T: 'a> {
+    cb: &'a mut T,
+    // the message continues with a body section
+    hold: bool,
+    complete: bool,
+}
+
+impl<'a, T: HttpParserCallback> HttpParserCallback for Forward<'a, T> {
+    fn on_message_begin(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.cb.on_message_begin(parser)
+    }
+
+    fn on_url(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.cb.on_url(parser, data)
+    }
+
+    fn on_status(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.cb.on_status(parser, data)
+    }
+
+    fn on_header_field(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.cb.on_header_field(parser, data)
+    }
+
+    fn on_header_value(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.cb.on_header_value(parser, data)
+    }
+
+    fn on_obs_fold(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.cb.on_obs_fold(parser)
+    }
+
+    fn on_headers_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.cb.on_headers_complete(parser)
+    }
+
+    fn on_body(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.cb.on_body(parser, data)
+    }
+
+    fn on_message_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.complete = true;
+        parser.pause(true);
+        if self.hold {
+            Ok(ParseAction::None)
+        } else {
+            self.cb.on_message_complete(parser)
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Stage {
+    Head,
+    Header,
+    Body,
+}
+
+/// An incremental ICAP parser.
+///
+/// The ICAP head is parsed into `head`, then each section listed in its `Encapsulated`
+/// header is handed to a nested `HttpParser`: a request parser for `req-hdr` and
+/// `req-body`, a response parser for `res-hdr`, `res-body` and `opt-body`. Their
+/// callbacks go to the `IcapCallback`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let data = b"REQMOD icap://icap.example.net/filter ICAP/1.0\r\n\
+///              Host: icap.example.net\r\n\
+///              Encapsulated: req-hdr=0, null-body=37\r\n\
+///              \r\n\
+///              GET /index.html HTTP/1.1\r\n\
+///              Host: a\r\n\
+///              \r\n";
+///
+/// let mut parser = IcapParser::new(HttpParserType::Request);
+/// let mut cb = IcapCollector::new();
+/// assert_eq!(parser.execute(&mut cb, data), data.len());
+///
+/// let message = &cb.messages[0];
+/// assert_eq!(message.head.method, Some(HttpMethod::ReqMod));
+/// assert_eq!(message.request.as_ref().unwrap().url, "/index.html");
+/// ```
+pub struct IcapParser {
+    /// Error if there was one in parsing
+    pub errno: Option<IcapError>,
+    /// The head of the current ICAP message
+    pub head: Option<Message>,
+    /// The sections of the current ICAP message and their offsets
+    pub sections: Vec<(IcapSection, usize)>,
+
+    head_parser: HttpParser,
+    head_collector: MessageCollector,
+    stage: Stage,
+    section: usize,
+    remaining: usize,
+    nested: Option<(IcapSection, HttpParser)>,
+}
+
+impl IcapParser {
+    /// Creates a parser for ICAP requests or responses.
+    pub fn new(tp: HttpParserType) -> IcapParser {
+        let mut head_parser = HttpParser::new(tp);
+        head_parser.protocol = Protocol::Icap;
+        head_parser.encapsulated = true;
+        IcapParser {
+            errno: None,
+            head: None,
+            sections: Vec::new(),
+            head_parser,
+            head_collector: MessageCollector::new(),
+            stage: Stage::Head,
+            section: 0,
+            remaining: 0,
+            nested: None,
+        }
+    }
+
+    /// Parses ICAP messages from `data` and returns the number of bytes consumed. On error
+    /// the returned count is smaller than `data.len()` and `errno` is set. An empty `data`
+    /// means EOF.
+    pub fn execute<T: IcapCallback>(&mut self, cb: &mut T, data: &[u8]) -> usize {
+        if self.errno.is_some() {
+            return 0;
+        }
+
+        if data.is_empty() {    // mean EOF
+            if self.stage != Stage::Head {
+                self.errno = Some(IcapError::UnexpectedEof);
+            } else {
+                self.head_parser.execute(&mut self.head_collector, data);
+                self.errno = self.head_parser.errno.map(IcapError::Http);
+            }
+            return 0;
+        }
+
+        let len = data.len();
+        let mut index = 0;
+        while index < len {
+            match self.stage {
+                Stage::Head => {
+                    let mut fwd = Forward { cb: &mut self.head_collector, hold: false, complete: false };
+                    index += self.head_parser.execute(&mut fwd, &data[index..]);
+                    if !fwd.complete {
+                        self.errno = self.head_parser.errno.map(IcapError::Http);
+                        return index;
+                    }
+                    self.head_parser.pause(false);
+
+                    let head = self.head_collector.messages.remove(0);
+                    self.sections = match head.headers.get(ENCAPSULATED) {
+                        Some(value) => match parse_encapsulated(value) {
+                            Ok(sections) => sections,
+                            Err(err) => {
+                                self.errno = Some(err);
+                                return index;
+                            },
+                        },
+                        None => Vec::new(),
+                    };
+                    self.head = Some(head);
+                    if cb.on_icap_head(self).is_err() {
+                        self.errno = Some(IcapError::CallbackFailed);
+                        return index;
+                    }
+                    self.section = 0;
+                    if !self.next_section(cb) {
+                        return index;
+                    }
+                },
+                Stage::Header => {
+                    let (section, ref mut parser) = *self.nested.as_mut().unwrap();
+                    let hold = self.sections[self.section + 1].0.head() == Some(section);
+                    let end = index + cmp::min(self.remaining, len - index);
+                    let mut fwd = Forward { cb: &mut *cb, hold, complete: false };
+                    let parsed = parser.execute(&mut fwd, &data[index .. end]);
+                    index += parsed;
+                    self.remaining -= parsed;
+
+                    if fwd.complete {
+                        parser.pause(false);
+                        if self.remaining != 0 {
+                            self.errno = Some(IcapError::InvalidSectionLength);
+                            return index;
+                        }
+                    } else if parser.errno.is_some() {
+                        self.errno = parser.errno.map(IcapError::Http);
+                        return index;
+                    } else if self.remaining == 0 {
+                        // the head is longer than its section
+                        self.errno = Some(IcapError::InvalidSectionLength);
+                        return index;
+                    } else {
+                        continue;
+                    }
+
+                    self.section += 1;
+                    if !self.next_section(cb) {
+                        return index;
+                    }
+                },
+                Stage::Body => {
+                    let parser = &mut self.nested.as_mut().unwrap().1;
+                    let mut fwd = Forward { cb: &mut *cb, hold: false, complete: false };
+                    index += parser.execute(&mut fwd, &data[index..]);
+                    if !fwd.complete {
+                        self.errno = parser.errno.map(IcapError::Http);
+                        return index;
+                    }
+                    if !self.message_complete(cb) {
+                        return index;
+                    }
+                },
+            }
+        }
+
+        index
+    }
+
+    // Sets up the parser for the current section. Returns false on error.
+    fn next_section<T: IcapCallback>(&mut self, cb: &mut T) -> bool {
+        let (section, offset) = match self.sections.get(self.section) {
+            Some(&entry) => entry,
+            None => return self.message_complete(cb),
+        };
+
+        match section {
+            IcapSection::ReqHdr | IcapSection::ResHdr => {
+                let tp = if section == IcapSection::ReqHdr {
+                    HttpParserType::Request
+                } else {
+                    HttpParserType::Response
+                };
+                let mut parser = HttpParser::new(tp);
+                parser.encapsulated = true;
+                self.nested = Some((section, parser));
+                self.remaining = self.sections[self.section + 1].1 - offset;
+                self.stage = Stage::Header;
+                true
+            },
+            IcapSection::NullBody => self.message_complete(cb),
+            _ => {
+                let continues = match self.nested {
+                    Some((head, _)) => section.head() == Some(head),
+                    None => false,
+                };
+                if !continues {
+                    let tp = if section == IcapSection::ReqBody {
+                        HttpParserType::Request
+                    } else {
+                        HttpParserType::Response
+                    };
+                    self.nested = Some((section, HttpParser::new(tp)));
+                }
+                self.nested.as_mut().unwrap().1.start_chunked_body();
+                self.stage = Stage::Body;
+                true
+            },
+        }
+    }
+
+    // Ends the ICAP message. Returns false on error.
+    fn message_complete<T: IcapCallback>(&mut self, cb: &mut T) -> bool {
+        self.nested = None;
+        self.stage = Stage::Head;
+        if cb.on_icap_message_complete(self).is_err() {
+            self.errno = Some(IcapError::CallbackFailed);
+            return false;
+        }
+        true
+    }
+}
+
+/// A complete ICAP message with its encapsulated HTTP messages.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct IcapMessage {
+    /// The ICAP request or response itself
+    pub head: Message,
+    /// The encapsulated HTTP request, from `req-hdr` and `req-body`
+    pub request: Option<Message>,
+    /// The encapsulated HTTP response, from `res-hdr` and `res-body`
+    pub response: Option<Message>,
+    /// The body of an OPTIONS response, from `opt-body`
+    pub options_body: Vec<u8>,
+}
+
+/// A callback that collects every parsed ICAP message into an owned `IcapMessage`.
+#[derive(Default)]
+pub struct IcapCollector {
+    /// Messages that have been completely parsed
+    pub messages: Vec<IcapMessage>,
+    http: MessageCollector,
+}
+
+impl IcapCollector {
+    /// Creates an empty collector.
+    pub fn new() -> IcapCollector {
+        IcapCollector { ..Default::default() }
+    }
+}
+
+impl HttpParserCallback for IcapCollector {
+    fn on_message_begin(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.http.on_message_begin(parser)
+    }
+
+    fn on_url(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.http.on_url(parser, data)
+    }
+
+    fn on_status(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.http.on_status(parser, data)
+    }
+
+    fn on_header_field(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.http.on_header_field(parser, data)
+    }
+
+    fn on_header_value(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.http.on_header_value(parser, data)
+    }
+
+    fn on_headers_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.http.on_headers_complete(parser)
+    }
+
+    fn on_body(&mut self, parser: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.http.on_body(parser, data)
+    }
+
+    fn on_message_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.http.on_message_complete(parser)
+    }
+}
+
+impl IcapCallback for IcapCollector {
+    fn on_icap_message_complete(&mut self, parser: &mut IcapParser) -> CallbackResult {
+        let mut message = IcapMessage {
+            head: parser.head.take().unwrap_or_default(),
+            ..Default::default()
+        };
+
+        // a body without a head section gets a message of its own with only the body
+        let body = parser.sections.last().map(|s| s.0);
+        for http in mem::take(&mut self.http.messages) {
+            if http.method.is_some() || (http.status_code.is_none() && body == Some(IcapSection::ReqBody)) {
+                message.request = Some(http);
+            } else if http.status_code.is_some() || body == Some(IcapSection::ResBody) {
+                message.response = Some(http);
+            } else {
+                message.options_body = http.body;
+            }
+        }
+        self.messages.push(message);
+        Ok(ParseAction::None)
+    }
+}
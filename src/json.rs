@@ -0,0 +1,287 @@
+//! A minimal JSON value with a parser and a serializer, enough for the HAR support.
+
+use std::fmt;
+
+// Deepest nesting of arrays and objects accepted, to bound the recursion of the parser.
+const MAX_DEPTH: usize = 128;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a complete JSON document.
+    pub fn parse(s: &str) -> Option<Json> {
+        let mut p = JsonParser { data: s.as_bytes(), pos: 0, depth: 0 };
+        let value = p.parse_value()?;
+        p.skip_ws();
+        if p.pos != p.data.len() {
+            return None;
+        }
+        Some(value)
+    }
+
+    /// Looks up a member of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref members) => {
+                members.iter().find(|m| m.0 == key).map(|m| &m.1)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match *self {
+            Json::Array(ref items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => {
+                if n.is_finite() {
+                    write!(f, "{}", n)
+                } else {
+                    write!(f, "null")
+                }
+            },
+            Json::String(ref s) => write_string(f, s),
+            Json::Array(ref items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Json::Object(ref members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).cloned()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(ch) = self.peek() {
+            if ch == b' ' || ch == b'\t' || ch == b'\r' || ch == b'\n' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect_literal(&mut self, literal: &[u8], value: Json) -> Option<Json> {
+        if self.data[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<Json> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.parse_element();
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_element(&mut self) -> Option<Json> {
+        self.skip_ws();
+        match self.peek()? {
+            b'n' => self.expect_literal(b"null", Json::Null),
+            b't' => self.expect_literal(b"true", Json::Bool(true)),
+            b'f' => self.expect_literal(b"false", Json::Bool(false)),
+            b'"' => self.parse_string().map(Json::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Some(Json::Array(items));
+                }
+                loop {
+                    items.push(self.parse_value()?);
+                    self.skip_ws();
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        b']' => {
+                            self.pos += 1;
+                            return Some(Json::Array(items));
+                        },
+                        _ => return None,
+                    }
+                }
+            },
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Some(Json::Object(members));
+                }
+                loop {
+                    self.skip_ws();
+                    if self.peek() != Some(b'"') {
+                        return None;
+                    }
+                    let key = self.parse_string()?;
+                    self.skip_ws();
+                    if self.peek() != Some(b':') {
+                        return None;
+                    }
+                    self.pos += 1;
+                    let value = self.parse_value()?;
+                    members.push((key, value));
+                    self.skip_ws();
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        b'}' => {
+                            self.pos += 1;
+                            return Some(Json::Object(members));
+                        },
+                        _ => return None,
+                    }
+                }
+            },
+            _ => self.parse_number(),
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<Json> {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            match ch {
+                b'0' ..= b'9' | b'-' | b'+' | b'.' | b'e' | b'E' => self.pos += 1,
+                _ => break,
+            }
+        }
+        let text = ::std::str::from_utf8(&self.data[start..self.pos]).ok()?;
+        text.parse::<f64>().ok().map(Json::Number)
+    }
+
+    fn parse_hex4(&mut self) -> Option<u32> {
+        if self.pos + 4 > self.data.len() {
+            return None;
+        }
+        let text = ::std::str::from_utf8(&self.data[self.pos..self.pos + 4]).ok()?;
+        self.pos += 4;
+        u32::from_str_radix(text, 16).ok()
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        // skip the opening quote
+        self.pos += 1;
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let ch = self.peek()?;
+            self.pos += 1;
+            match ch {
+                b'"' => return String::from_utf8(out).ok(),
+                b'\\' => {
+                    let esc = self.peek()?;
+                    self.pos += 1;
+                    match esc {
+                        b'"' => out.push(b'"'),
+                        b'\\' => out.push(b'\\'),
+                        b'/' => out.push(b'/'),
+                        b'b' => out.push(8),
+                        b'f' => out.push(12),
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'u' => {
+                            let mut code = self.parse_hex4()?;
+                            if (0xd800..0xdc00).contains(&code) {
+                                // surrogate pair
+                                if !self.data[self.pos..].starts_with(b"\\u") {
+                                    return None;
+                                }
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return None;
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            let c = ::std::char::from_u32(code)?;
+                            let mut buf = [0u8; 4];
+                            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                        },
+                        _ => return None,
+                    }
+                },
+                _ => out.push(ch),
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// `Lenient` is a set of checks to skip in strict mode, modelled on the lenient flags of
+/// llhttp. Setting `HttpParser::strict` to false skips all of them.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let mut parser = HttpParser::new(HttpParserType::Request);
+/// parser.lenient = Lenient::HEADERS | Lenient::KEEP_ALIVE;
+/// assert!(parser.lenient.contains(Lenient::HEADERS));
+/// assert!(!parser.lenient.contains(Lenient::VERSION));
+/// ```
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Lenient(u16);
+
+impl Lenient {
+    /// Allow spaces in header names
+    pub const HEADERS: Lenient = Lenient(1 << 0);
+    /// Allow `Content-Length` together with `Transfer-Encoding: chunked`, which wins
+    pub const CHUNKED_LENGTH: Lenient = Lenient(1 << 1);
+    /// Keep parsing after a message that closes the connection
+    pub const KEEP_ALIVE: Lenient = Lenient(1 << 2);
+    /// Allow a request whose `Transfer-Encoding` is not `chunked`
+    pub const TRANSFER_ENCODING: Lenient = Lenient(1 << 3);
+    /// Allow a misspelled protocol name, multi-digit versions and versions other than
+    /// HTTP/0.9, HTTP/1.0 and HTTP/1.1 in the start line
+    pub const VERSION: Lenient = Lenient(1 << 4);
+    /// Ignore data after a message that closes the connection
+    pub const DATA_AFTER_CLOSE: Lenient = Lenient(1 << 5);
+    /// Allow any character in place of the LF after a CR
+    pub const OPTIONAL_LF_AFTER_CR: Lenient = Lenient(1 << 6);
+    /// Allow any characters in place of the CRLF after chunk data
+    pub const OPTIONAL_CRLF_AFTER_CHUNK: Lenient = Lenient(1 << 7);
+    /// Allow spaces between the chunk size and the CRLF or extensions
+    pub const SPACES_AFTER_CHUNK_SIZE: Lenient = Lenient(1 << 8);
+    /// Allow tabs, form feeds and non-ASCII bytes in the request target
+    pub const URL: Lenient = Lenient(1 << 9);
+
+    /// No lenient flags.
+    pub fn empty() -> Lenient {
+        Lenient(0)
+    }
+
+    /// All lenient flags.
+    pub fn all() -> Lenient {
+        Lenient((1 << 10) - 1)
+    }
+
+    /// Returns true if every flag of `other` is set.
+    pub fn contains(self, other: Lenient) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if no flag is set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Sets the flags of `other`.
+    pub fn insert(&mut self, other: Lenient) {
+        self.0 |= other.0;
+    }
+
+    /// Clears the flags of `other`.
+    pub fn remove(&mut self, other: Lenient) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for Lenient {
+    type Output = Lenient;
+
+    fn bitor(self, other: Lenient) -> Lenient {
+        Lenient(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Lenient {
+    fn bitor_assign(&mut self, other: Lenient) {
+        self.0 |= other.0;
+    }
+}
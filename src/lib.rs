@@ -77,11 +77,38 @@
 
 #![crate_name = "http_parser"]
 
-pub use self::parser::{HttpParser, HttpParserType};
+pub use self::parser::{HttpParser, HttpParserType, ObsFold};
 pub use self::http_version::HttpVersion;
 pub use self::error::HttpErrno;
 pub use self::http_method::HttpMethod;
+pub use self::status_code::StatusCode;
+pub use self::protocol::Protocol;
+pub use self::lenient::Lenient;
 pub use self::callback::{HttpParserCallback, CallbackResult, ParseAction};
+pub use self::message::{Headers, Message, MessageCollector};
+pub use self::har::{Har, HarEntry, HarTimings, HarError};
+pub use self::websocket::{websocket_accept_key, WebSocketHandshake, WebSocketParser,
+                          WebSocketCallback, WebSocketRole, WebSocketOpcode,
+                          WebSocketFrameHeader, WebSocketError};
+pub use self::http2::{H2cUpgrade, Http2Settings, Http2Error};
+pub use self::proxy::{ProxyHeader, ProxyCommand, ProxyTransport, ProxyError,
+                      PROXY_HEADER_MAX_LENGTH, PP2_TYPE_ALPN, PP2_TYPE_AUTHORITY, PP2_TYPE_CRC32C,
+                      PP2_TYPE_NOOP, PP2_TYPE_UNIQUE_ID, PP2_TYPE_SSL, PP2_TYPE_NETNS};
+pub use self::icap::{parse_encapsulated, IcapParser, IcapCallback, IcapCollector, IcapMessage,
+                     IcapSection, IcapError};
+pub use self::ssdp::{SsdpMessage, SsdpKind, SsdpError};
+pub use self::cookie::{parse_cookies, format_cookies, SetCookie, SameSite, CookieError};
+pub use self::urlencoded::{percent_decode, query_pairs, QueryPairs, FormDecoder, FormError};
+pub use self::media_type::{MediaType, MediaTypeError};
+pub use self::multipart::{multipart_boundary, MultipartParser, MultipartCallback, MultipartError};
+pub use self::range::{Range, ByteRange, ContentRange, ByteRangesParser, ByteRangesCallback, RangeError};
+pub use self::cache::{CacheControl, Freshness};
+pub use self::auth::{Credentials, DigestCredentials, Challenge, AuthError};
+pub use self::http_date::{HttpDate, RetryAfter, HttpDateError};
+pub use self::accept::{Accept, AcceptEncoding, AcceptLanguage, QualityItem, AcceptError, MAX_QUALITY, language_matches};
+pub use self::forwarded::{Forwarded, ForwardedElement, Node, NodeName, NodePort, IpNetwork, TrustedProxies, ClientInfo,
+                          ForwardedError, parse_x_forwarded_for, parse_x_forwarded_proto, parse_x_forwarded_host,
+                          parse_x_real_ip};
 
 mod parser;
 mod http_version;
@@ -89,5 +116,31 @@ mod error;
 mod state;
 mod flags;
 mod http_method;
+mod status_code;
+mod protocol;
+mod lenient;
 mod callback;
+mod message;
+mod har;
+mod json;
+mod base64;
+mod sha1;
+mod md5;
+mod sha256;
+mod websocket;
+mod http2;
+mod proxy;
+mod icap;
+mod ssdp;
+mod cookie;
+mod urlencoded;
+mod grammar;
+mod media_type;
+mod multipart;
+mod range;
+mod cache;
+mod auth;
+mod http_date;
+mod accept;
+mod forwarded;
 
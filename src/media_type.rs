@@ -0,0 +1,222 @@
+//! Media types of `Content-Type` and media ranges of `Accept` (RFC 9110 section 8.3.1).
+
+use std::fmt;
+use std::str::FromStr;
+
+use grammar::{is_token, take_quoted_string, take_token, trim_ows, write_token_or_quoted};
+
+/// `MediaTypeError` defines the errors encountered when parsing a media type.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum MediaTypeError {
+    /// The type or subtype is missing or not a token
+    InvalidType,
+    /// A parameter is not a token, `=` and a token or quoted string
+    InvalidParameter,
+    /// A quoted string has no closing quote
+    UnterminatedQuote,
+}
+
+impl fmt::Display for MediaTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MediaTypeError::InvalidType => write!(f, "invalid media type"),
+            MediaTypeError::InvalidParameter => write!(f, "invalid media type parameter"),
+            MediaTypeError::UnterminatedQuote => write!(f, "unterminated quoted string"),
+        }
+    }
+}
+
+/// A media type such as `text/html; charset=utf-8`, or a media range of `Accept` such as
+/// `text/*`.
+///
+/// The type, subtype and parameter names are kept in lower case, as they are
+/// case-insensitive. Parameter values are kept as they are, without quotes.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let media_type = MediaType::parse(b"Application/Problem+JSON; charset=\"UTF-8\"").unwrap();
+/// assert_eq!(media_type.type_(), "application");
+/// assert_eq!(media_type.subtype(), "problem+json");
+/// assert_eq!(media_type.suffix(), Some("json"));
+/// assert_eq!(media_type.charset(), Some("UTF-8"));
+/// assert!(media_type.matches(&"application/*".parse().unwrap()));
+/// assert_eq!(media_type.to_string(), "application/problem+json; charset=UTF-8");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MediaType {
+    ty: String,
+    subtype: String,
+    params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    /// Creates a media type without parameters. `type_` and `subtype` must be tokens.
+    pub fn new(type_: &str, subtype: &str) -> Result<MediaType, MediaTypeError> {
+        if !is_token(type_.as_bytes()) || !is_token(subtype.as_bytes()) {
+            return Err(MediaTypeError::InvalidType);
+        }
+        Ok(MediaType {
+            ty: type_.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            params: Vec::new(),
+        })
+    }
+
+    /// Parses a media type from a header value, such as the data of `on_header_value`.
+    /// Empty parameters, as in `text/plain;;charset=utf-8;`, are skipped.
+    pub fn parse(value: &[u8]) -> Result<MediaType, MediaTypeError> {
+        let (media_type, rest) = MediaType::parse_prefix(value)?;
+        if !rest.is_empty() {
+            return Err(MediaTypeError::InvalidParameter);
+        }
+        Ok(media_type)
+    }
+
+    // Parses a media type at the start of `value` and returns it and the data from the
+    // first character that can not belong to it, such as the `,` of a list.
+    pub(crate) fn parse_prefix(value: &[u8]) -> Result<(MediaType, &[u8]), MediaTypeError> {
+        let (ty, rest) = take_token(trim_ows(value));
+        if ty.is_empty() || rest.first() != Some(&b'/') {
+            return Err(MediaTypeError::InvalidType);
+        }
+        let (subtype, mut rest) = take_token(&rest[1..]);
+        if subtype.is_empty() {
+            return Err(MediaTypeError::InvalidType);
+        }
+
+        // both are tokens, so ASCII
+        let mut media_type = MediaType {
+            ty: String::from_utf8_lossy(ty).to_ascii_lowercase(),
+            subtype: String::from_utf8_lossy(subtype).to_ascii_lowercase(),
+            params: Vec::new(),
+        };
+
+        loop {
+            rest = trim_ows(rest);
+            if rest.first() != Some(&b';') {
+                break;
+            }
+            rest = trim_ows(&rest[1..]);
+            if rest.is_empty() || rest[0] == b';' || rest[0] == b',' {
+                continue;
+            }
+
+            let (name, after) = take_token(rest);
+            if name.is_empty() || after.first() != Some(&b'=') {
+                return Err(MediaTypeError::InvalidParameter);
+            }
+            let after = &after[1..];
+            let (value, after) = if after.first() == Some(&b'"') {
+                take_quoted_string(after).ok_or(MediaTypeError::UnterminatedQuote)?
+            } else {
+                let (token, after) = take_token(after);
+                if token.is_empty() {
+                    return Err(MediaTypeError::InvalidParameter);
+                }
+                (token.to_vec(), after)
+            };
+            media_type.params.push((String::from_utf8_lossy(name).to_ascii_lowercase(),
+                                    String::from_utf8_lossy(&value).into_owned()));
+            rest = after;
+        }
+
+        Ok((media_type, rest))
+    }
+
+    /// The type, such as `text`, or `*` in a media range.
+    pub fn type_(&self) -> &str {
+        &self.ty
+    }
+
+    /// The subtype, such as `html`, or `*` in a media range.
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// The structured syntax suffix of the subtype, such as `json` for
+    /// `application/ld+json`.
+    pub fn suffix(&self) -> Option<&str> {
+        self.subtype.rfind('+').map(|plus| &self.subtype[plus + 1..])
+    }
+
+    /// The type and subtype without parameters, such as `text/html`.
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.ty, self.subtype)
+    }
+
+    /// Returns true if the type and subtype are `essence`, compared case-insensitively.
+    pub fn is(&self, essence: &str) -> bool {
+        let mut parts = essence.splitn(2, '/');
+        parts.next().is_some_and(|t| t.eq_ignore_ascii_case(&self.ty)) &&
+            parts.next().is_some_and(|s| s.eq_ignore_ascii_case(&self.subtype))
+    }
+
+    /// Returns the value of the first parameter named `name`, compared case-insensitively.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|p| p.0.eq_ignore_ascii_case(name))
+            .map(|p| &p.1[..])
+    }
+
+    /// The parameters as `(name, value)` pairs, in order.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Sets the parameter `name`, replacing any parameter with the same name.
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        let name = name.to_ascii_lowercase();
+        self.params.retain(|p| p.0 != name);
+        self.params.push((name, value.to_string()));
+    }
+
+    /// The `charset` parameter.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// The `boundary` parameter of a multipart type.
+    pub fn boundary(&self) -> Option<&str> {
+        self.param("boundary")
+    }
+
+    /// Returns true if this media type falls within the media range `range`: the types
+    /// are equal or the range has `*`, and every parameter of the range has the same value
+    /// here. `charset` values are compared case-insensitively.
+    pub fn matches(&self, range: &MediaType) -> bool {
+        if range.ty != "*" && range.ty != self.ty {
+            return false;
+        }
+        if range.subtype != "*" && range.subtype != self.subtype {
+            return false;
+        }
+        range.params.iter().all(|(name, value)| {
+            match self.param(name) {
+                Some(v) if name == "charset" => v.eq_ignore_ascii_case(value),
+                Some(v) => v == value,
+                None => false,
+            }
+        })
+    }
+}
+
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.ty, self.subtype)?;
+        for (name, value) in self.params.iter() {
+            write!(f, "; {}=", name)?;
+            write_token_or_quoted(f, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = MediaTypeError;
+
+    fn from_str(s: &str) -> Result<MediaType, MediaTypeError> {
+        MediaType::parse(s.as_bytes())
+    }
+}
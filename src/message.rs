@@ -0,0 +1,335 @@
+//! Owned representation of parsed HTTP messages.
+
+use std::mem;
+use std::time::SystemTime;
+
+use parser::HttpParser;
+use http_method::HttpMethod;
+use http_version::HttpVersion;
+use protocol::Protocol;
+use status_code::StatusCode;
+use cookie::{parse_cookies, SetCookie};
+use media_type::MediaType;
+use http_date::HttpDate;
+use accept::{Accept, AcceptEncoding, AcceptLanguage};
+use callback::{HttpParserCallback, CallbackResult, ParseAction};
+
+/// An ordered list of header fields. Lookups by name are case-insensitive.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+impl Headers {
+    /// Creates an empty header list.
+    pub fn new() -> Headers {
+        Headers { entries: Vec::new() }
+    }
+
+    /// Appends a header field, keeping any existing fields with the same name.
+    pub fn push(&mut self, name: &str, value: &str) {
+        self.entries.push((name.to_string(), value.to_string()));
+    }
+
+    /// Returns the value of the first header field named `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.iter()
+            .find(|e| e.0.eq_ignore_ascii_case(name))
+            .map(|e| &e.1[..])
+    }
+
+    /// Returns the values of all header fields named `name`, in order.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries.iter()
+            .filter(|e| e.0.eq_ignore_ascii_case(name))
+            .map(|e| &e.1[..])
+            .collect()
+    }
+
+    /// Returns true if there is a header field named `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns true if any header field named `name` contains `token` in its
+    /// comma-separated list, e.g. `Connection: keep-alive, Upgrade`. Tokens are compared
+    /// case-insensitively.
+    pub fn has_token(&self, name: &str, token: &str) -> bool {
+        self.get_all(name).iter()
+            .flat_map(|v| v.split(','))
+            .any(|t| t.trim().eq_ignore_ascii_case(token))
+    }
+
+    /// Returns the media type of the `Content-Type` header field, if it is valid.
+    pub fn content_type(&self) -> Option<MediaType> {
+        self.get("Content-Type").and_then(|v| MediaType::parse(v.as_bytes()).ok())
+    }
+
+    /// Returns the HTTP-date of the header field `name`, such as `Last-Modified`, if it is
+    /// valid.
+    pub fn http_date(&self, name: &str) -> Option<HttpDate> {
+        self.get(name).and_then(|v| HttpDate::parse(v.as_bytes()).ok())
+    }
+
+    /// Returns the media ranges of all `Accept` header fields, if there are any and they
+    /// are valid.
+    pub fn accept(&self) -> Option<Accept> {
+        self.list("Accept").and_then(|v| Accept::parse(v.as_bytes()).ok())
+    }
+
+    /// Returns the codings of all `Accept-Encoding` header fields, if there are any and
+    /// they are valid.
+    pub fn accept_encoding(&self) -> Option<AcceptEncoding> {
+        self.list("Accept-Encoding").and_then(|v| AcceptEncoding::parse(v.as_bytes()).ok())
+    }
+
+    /// Returns the language ranges of all `Accept-Language` header fields, if there are
+    /// any and they are valid.
+    pub fn accept_language(&self) -> Option<AcceptLanguage> {
+        self.list("Accept-Language").and_then(|v| AcceptLanguage::parse(v.as_bytes()).ok())
+    }
+
+    // The values of all header fields named `name` joined into one list, RFC 9110
+    // section 5.3.
+    pub(crate) fn list(&self, name: &str) -> Option<String> {
+        let values = self.get_all(name);
+        if values.is_empty() { None } else { Some(values.join(", ")) }
+    }
+
+    /// Returns the `(name, value)` pairs of all `Cookie` header fields, in order.
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        self.get_all("Cookie").iter()
+            .flat_map(|v| parse_cookies(v))
+            .collect()
+    }
+
+    /// Returns the cookies of all `Set-Cookie` header fields, skipping invalid ones.
+    pub fn set_cookies(&self) -> Vec<SetCookie> {
+        self.get_all("Set-Cookie").iter()
+            .filter_map(|v| SetCookie::parse(v).ok())
+            .collect()
+    }
+
+    /// Appends a `Set-Cookie` header field for `cookie`.
+    pub fn push_set_cookie(&mut self, cookie: &SetCookie) {
+        self.push("Set-Cookie", &cookie.to_string());
+    }
+
+    /// Removes all header fields named `name`.
+    pub fn remove(&mut self, name: &str) {
+        self.entries.retain(|e| !e.0.eq_ignore_ascii_case(name));
+    }
+
+    /// Iterates over `(name, value)` pairs in order.
+    pub fn iter(&self) -> ::std::slice::Iter<'_, (String, String)> {
+        self.entries.iter()
+    }
+
+    /// Number of header fields.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no header fields.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A complete HTTP request or response.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Message {
+    /// HTTP method, requests only
+    pub method: Option<HttpMethod>,
+    /// Request target as it appeared in the request line, requests only
+    pub url: String,
+    /// HTTP version
+    pub http_version: HttpVersion,
+    /// Protocol of the start line, `HTTP` unless the parser was set to another one
+    pub protocol: Protocol,
+    /// Status code, responses only
+    pub status_code: Option<u16>,
+    /// Reason phrase, responses only
+    pub status: String,
+    /// Header fields in the order they were received
+    pub headers: Headers,
+    /// Message body, with any chunked encoding removed
+    pub body: Vec<u8>,
+    /// When the first byte of the message was parsed
+    pub started: Option<SystemTime>,
+    /// When the message was complete
+    pub completed: Option<SystemTime>,
+}
+
+impl Message {
+    /// Returns true if this is a request.
+    pub fn is_request(&self) -> bool {
+        self.method.is_some()
+    }
+
+    /// The status code of a response as a `StatusCode`.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status_code.and_then(StatusCode::new)
+    }
+
+    /// Serializes the message into HTTP/1.x wire format, or the RTSP, SIP or ICAP
+    /// equivalent for messages of those protocols.
+    ///
+    /// The headers are written as they are, so they need to describe the body correctly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use http_parser::*;
+    /// let mut msg = Message::default();
+    /// msg.method = Some(HttpMethod::Get);
+    /// msg.url = "/".to_string();
+    /// msg.http_version = HttpVersion { major: 1, minor: 1 };
+    /// msg.headers.push("Host", "example.com");
+    ///
+    /// assert_eq!(msg.to_bytes(), b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self.method {
+            Some(method) => {
+                out.extend_from_slice(method.to_string().as_bytes());
+                out.push(b' ');
+                out.extend_from_slice(self.url.as_bytes());
+                out.extend_from_slice(format!(" {}/{}\r\n", self.protocol, self.http_version).as_bytes());
+            },
+            None => {
+                out.extend_from_slice(format!("{}/{} {:03}", self.protocol, self.http_version,
+                                              self.status_code.unwrap_or(0)).as_bytes());
+                if !self.status.is_empty() {
+                    out.push(b' ');
+                    out.extend_from_slice(self.status.as_bytes());
+                }
+                out.extend_from_slice(b"\r\n");
+            },
+        }
+        for (name, value) in self.headers.iter() {
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(b": ");
+            out.extend_from_slice(value.as_bytes());
+            out.extend_from_slice(b"\r\n");
+        }
+        out.extend_from_slice(b"\r\n");
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+/// A callback that collects every parsed message into an owned `Message`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let mut parser = HttpParser::new(HttpParserType::Request);
+/// let mut collector = MessageCollector::new();
+///
+/// parser.execute(&mut collector, b"GET /index.html HTTP/1.1\r\nHost: example.com\r\n\r\n");
+///
+/// assert_eq!(collector.messages.len(), 1);
+/// assert_eq!(collector.messages[0].url, "/index.html");
+/// assert_eq!(collector.messages[0].headers.get("host"), Some("example.com"));
+/// ```
+#[derive(Default)]
+pub struct MessageCollector {
+    /// Messages that have been completely parsed
+    pub messages: Vec<Message>,
+    /// Interim (1xx) responses, in the order they were received
+    pub informational: Vec<Message>,
+    current: Message,
+    url: Vec<u8>,
+    status: Vec<u8>,
+    field: Vec<u8>,
+    value: Vec<u8>,
+    in_value: bool,
+}
+
+impl MessageCollector {
+    /// Creates an empty collector.
+    pub fn new() -> MessageCollector {
+        MessageCollector { ..Default::default() }
+    }
+
+    fn flush_header(&mut self) {
+        if !self.field.is_empty() || self.in_value {
+            let name = String::from_utf8_lossy(&self.field).into_owned();
+            let value = String::from_utf8_lossy(&self.value).into_owned();
+            self.current.headers.push(&name, &value);
+        }
+        self.field.clear();
+        self.value.clear();
+        self.in_value = false;
+    }
+}
+
+impl HttpParserCallback for MessageCollector {
+    fn on_message_begin(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.current = Message::default();
+        self.url.clear();
+        self.status.clear();
+        self.current.started = Some(SystemTime::now());
+        Ok(ParseAction::None)
+    }
+
+    fn on_url(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.url.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_status(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.status.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_header_field(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        if self.in_value {
+            self.flush_header();
+        }
+        self.field.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_header_value(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.in_value = true;
+        self.value.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_headers_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.flush_header();
+        self.current.url = String::from_utf8_lossy(&self.url).into_owned();
+        self.current.status = String::from_utf8_lossy(&self.status).into_owned();
+        self.current.method = parser.method;
+        self.current.status_code = parser.status_code;
+        self.current.http_version = parser.http_version;
+        self.current.protocol = parser.protocol;
+        Ok(ParseAction::None)
+    }
+
+    fn on_body(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.current.body.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_informational(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.current.completed = Some(SystemTime::now());
+        let message = mem::take(&mut self.current);
+        self.informational.push(message);
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+        // trailing headers of a chunked message
+        self.flush_header();
+        self.current.completed = Some(SystemTime::now());
+        let message = mem::take(&mut self.current);
+        self.messages.push(message);
+        Ok(ParseAction::None)
+    }
+}
+
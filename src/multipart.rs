@@ -0,0 +1,409 @@
+//! Streaming `multipart/form-data` (RFC 7578) and other `multipart/*` (RFC 2046) bodies.
+
+use std::cmp;
+use std::fmt;
+use std::mem;
+
+use parser::is_header_char;
+use media_type::MediaType;
+use callback::{CallbackResult, ParseAction};
+
+const CR: u8 = b'\r';
+const LF: u8 = b'\n';
+const MAX_BOUNDARY_LENGTH: usize = 70;
+const DEFAULT_MAX_HEADER_SIZE: usize = 8 * 1024;
+
+/// `MultipartError` defines the errors encountered when parsing a multipart body.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum MultipartError {
+    /// `Content-Type` is not a multipart type with a `boundary` parameter
+    MissingBoundary,
+    /// The boundary is empty, longer than 70 characters or has invalid characters
+    InvalidBoundary,
+    /// A boundary line is followed by something other than `--` or CRLF
+    InvalidDelimiter,
+    /// A part header is malformed
+    InvalidHeader,
+    /// The headers of a part are larger than `max_header_size`
+    HeadersTooLarge,
+    /// The data of a part is larger than `max_part_size`
+    PartTooLarge,
+    /// The body is larger than `max_body_size`
+    BodyTooLarge,
+    /// The body ends before the close delimiter
+    UnexpectedEof,
+    /// A callback returned an error
+    CallbackFailed,
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MultipartError::MissingBoundary => write!(f, "no multipart boundary in Content-Type"),
+            MultipartError::InvalidBoundary => write!(f, "invalid multipart boundary"),
+            MultipartError::InvalidDelimiter => write!(f, "invalid data after multipart boundary"),
+            MultipartError::InvalidHeader => write!(f, "invalid multipart part header"),
+            MultipartError::HeadersTooLarge => write!(f, "multipart part headers too large"),
+            MultipartError::PartTooLarge => write!(f, "multipart part too large"),
+            MultipartError::BodyTooLarge => write!(f, "multipart body too large"),
+            MultipartError::UnexpectedEof => write!(f, "multipart body ends before the close delimiter"),
+            MultipartError::CallbackFailed => write!(f, "a callback failed"),
+        }
+    }
+}
+
+/// Returns the `boundary` parameter of a `multipart/*` `Content-Type` value.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let boundary = multipart_boundary("multipart/form-data; boundary=\"----abc 123\"");
+/// assert_eq!(boundary, Ok("----abc 123".to_string()));
+/// assert_eq!(multipart_boundary("text/plain"), Err(MultipartError::MissingBoundary));
+/// ```
+pub fn multipart_boundary(content_type: &str) -> Result<String, MultipartError> {
+    match MediaType::parse(content_type.as_bytes()) {
+        Ok(ref media_type) if media_type.type_() == "multipart" =>
+            media_type.boundary().map(|b| b.to_string()).ok_or(MultipartError::MissingBoundary),
+        _ => Err(MultipartError::MissingBoundary),
+    }
+}
+
+// bchars of RFC 2046
+fn is_boundary_char(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&ch)
+}
+
+/// It defines the callback functions that would be called by `MultipartParser`.
+pub trait MultipartCallback {
+    /// Function called when a part begins, before its headers.
+    #[allow(unused_variables)]
+    fn on_part_begin(&mut self, parser: &mut MultipartParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with each complete header of a part, such as
+    /// `Content-Disposition`. Whitespace around the value is removed.
+    #[allow(unused_variables)]
+    fn on_part_header(&mut self, parser: &mut MultipartParser, name: &[u8],
+                      value: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called when the headers of a part are complete, before its data.
+    #[allow(unused_variables)]
+    fn on_part_headers_complete(&mut self, parser: &mut MultipartParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with data of a part. It may be called several times per part.
+    #[allow(unused_variables)]
+    fn on_part_data(&mut self, parser: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called when a part is complete.
+    #[allow(unused_variables)]
+    fn on_part_end(&mut self, parser: &mut MultipartParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum MultipartState {
+    Preamble,
+    BoundaryTail,
+    BoundaryDash,
+    BoundaryLf,
+    HeaderFieldStart,
+    HeaderField,
+    HeaderValueStart,
+    HeaderValue,
+    HeaderValueLf,
+    HeadersLf,
+    Data,
+    Epilogue,
+}
+
+/// A streaming multipart body parser.
+///
+/// Feed it the body as it arrives in `on_body`, split at any point, and an empty slice at
+/// the end of the body. The preamble before the first boundary and the epilogue after the
+/// close delimiter are ignored.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// struct Callback {
+///     data: Vec<u8>,
+/// }
+///
+/// impl MultipartCallback for Callback {
+///     fn on_part_data(&mut self, _: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+///         self.data.extend_from_slice(data);
+///         Ok(ParseAction::None)
+///     }
+/// }
+///
+/// let body = b"--xyz\r\n\
+///              Content-Disposition: form-data; name=\"field\"\r\n\
+///              \r\n\
+///              value\r\n\
+///              --xyz--\r\n";
+/// let mut parser = MultipartParser::from_content_type("multipart/form-data; boundary=xyz").unwrap();
+/// let mut cb = Callback { data: Vec::new() };
+/// assert_eq!(parser.execute(&mut cb, body), body.len());
+/// parser.execute(&mut cb, &[]);
+/// assert_eq!(parser.errno, None);
+/// assert_eq!(cb.data, b"value".to_vec());
+/// ```
+pub struct MultipartParser {
+    /// Error if there was one in parsing
+    pub errno: Option<MultipartError>,
+    /// Maximum size of the headers of a part, 8 KiB by default
+    pub max_header_size: usize,
+    /// Maximum size of the data of a part, unlimited by default
+    pub max_part_size: u64,
+    /// Maximum size of the whole body, unlimited by default
+    pub max_body_size: u64,
+
+    state: MultipartState,
+    // "\r\n--" followed by the boundary
+    delimiter: Vec<u8>,
+    // bytes of the delimiter matched so far
+    matched: usize,
+    header_name: Vec<u8>,
+    header_value: Vec<u8>,
+    header_size: usize,
+    part_size: u64,
+    body_size: u64,
+}
+
+macro_rules! multipart_callback(
+    ($parser:ident, $cb:expr, $idx:expr) => (
+        if $cb.is_err() {
+            $parser.errno = Some(MultipartError::CallbackFailed);
+            return $idx;
+        }
+    );
+);
+
+macro_rules! multipart_error(
+    ($parser:ident, $err:expr, $idx:expr) => ({
+        $parser.errno = Some($err);
+        return $idx;
+    });
+);
+
+impl MultipartParser {
+    /// Creates a parser for parts separated by `boundary`.
+    pub fn new(boundary: &str) -> Result<MultipartParser, MultipartError> {
+        let b = boundary.as_bytes();
+        if b.is_empty() || b.len() > MAX_BOUNDARY_LENGTH || !b.iter().all(|&c| is_boundary_char(c)) ||
+                b[b.len() - 1] == b' ' {
+            return Err(MultipartError::InvalidBoundary);
+        }
+
+        let mut delimiter = b"\r\n--".to_vec();
+        delimiter.extend_from_slice(b);
+        Ok(MultipartParser {
+            errno: None,
+            max_header_size: DEFAULT_MAX_HEADER_SIZE,
+            max_part_size: u64::MAX,
+            max_body_size: u64::MAX,
+            state: MultipartState::Preamble,
+            delimiter,
+            // the first boundary may start the body, without a CRLF before it
+            matched: 2,
+            header_name: Vec::new(),
+            header_value: Vec::new(),
+            header_size: 0,
+            part_size: 0,
+            body_size: 0,
+        })
+    }
+
+    /// Creates a parser for the boundary of a `multipart/*` `Content-Type` value.
+    pub fn from_content_type(content_type: &str) -> Result<MultipartParser, MultipartError> {
+        MultipartParser::new(&multipart_boundary(content_type)?)
+    }
+
+    /// Returns true once the close delimiter has been parsed.
+    pub fn is_done(&self) -> bool {
+        self.state == MultipartState::Epilogue
+    }
+
+    /// Parses `data` and returns the number of bytes consumed. On error the returned count
+    /// is smaller than `data.len()` and `errno` is set. An empty `data` ends the body,
+    /// which is an error before the close delimiter.
+    pub fn execute<T: MultipartCallback>(&mut self, cb: &mut T, data: &[u8]) -> usize {
+        if self.errno.is_some() {
+            return 0;
+        }
+
+        if data.is_empty() {
+            if !self.is_done() {
+                self.errno = Some(MultipartError::UnexpectedEof);
+            }
+            return 0;
+        }
+
+        let len = cmp::min(data.len() as u64, self.max_body_size - self.body_size) as usize;
+        self.body_size += len as u64;
+        let mut data_mark: Option<usize> = None;
+
+        for (index, &ch) in data[..len].iter().enumerate() {
+            if matches!(self.state, MultipartState::HeaderFieldStart | MultipartState::HeaderField |
+                        MultipartState::HeaderValueStart | MultipartState::HeaderValue |
+                        MultipartState::HeaderValueLf | MultipartState::HeadersLf) {
+                self.header_size += 1;
+                if self.header_size > self.max_header_size {
+                    multipart_error!(self, MultipartError::HeadersTooLarge, index);
+                }
+            }
+
+            match self.state {
+                MultipartState::Preamble => {
+                    if ch == self.delimiter[self.matched] {
+                        self.matched += 1;
+                        if self.matched == self.delimiter.len() {
+                            self.matched = 0;
+                            self.state = MultipartState::BoundaryTail;
+                        }
+                    } else {
+                        self.matched = if ch == CR { 1 } else { 0 };
+                    }
+                },
+                MultipartState::BoundaryTail => {
+                    match ch {
+                        b'-' => self.state = MultipartState::BoundaryDash,
+                        // transport padding
+                        b' ' | b'\t' => (),
+                        CR => self.state = MultipartState::BoundaryLf,
+                        _ => multipart_error!(self, MultipartError::InvalidDelimiter, index),
+                    }
+                },
+                MultipartState::BoundaryDash => {
+                    if ch != b'-' {
+                        multipart_error!(self, MultipartError::InvalidDelimiter, index);
+                    }
+                    self.state = MultipartState::Epilogue;
+                },
+                MultipartState::BoundaryLf => {
+                    if ch != LF {
+                        multipart_error!(self, MultipartError::InvalidDelimiter, index);
+                    }
+                    self.header_size = 0;
+                    self.part_size = 0;
+                    self.state = MultipartState::HeaderFieldStart;
+                    multipart_callback!(self, cb.on_part_begin(self), index + 1);
+                },
+                MultipartState::HeaderFieldStart => {
+                    if ch == CR {
+                        self.state = MultipartState::HeadersLf;
+                    } else if is_header_char(false, ch) {
+                        self.header_name.push(ch);
+                        self.state = MultipartState::HeaderField;
+                    } else {
+                        multipart_error!(self, MultipartError::InvalidHeader, index);
+                    }
+                },
+                MultipartState::HeaderField => {
+                    if ch == b':' {
+                        self.state = MultipartState::HeaderValueStart;
+                    } else if is_header_char(false, ch) {
+                        self.header_name.push(ch);
+                    } else {
+                        multipart_error!(self, MultipartError::InvalidHeader, index);
+                    }
+                },
+                MultipartState::HeaderValueStart | MultipartState::HeaderValue => {
+                    match ch {
+                        CR => self.state = MultipartState::HeaderValueLf,
+                        LF => multipart_error!(self, MultipartError::InvalidHeader, index),
+                        b' ' | b'\t' if self.state == MultipartState::HeaderValueStart => (),
+                        _ => {
+                            self.header_value.push(ch);
+                            self.state = MultipartState::HeaderValue;
+                        },
+                    }
+                },
+                MultipartState::HeaderValueLf => {
+                    if ch != LF {
+                        multipart_error!(self, MultipartError::InvalidHeader, index);
+                    }
+                    let name = mem::take(&mut self.header_name);
+                    let mut value = mem::take(&mut self.header_value);
+                    while value.last() == Some(&b' ') || value.last() == Some(&b'\t') {
+                        value.pop();
+                    }
+                    self.state = MultipartState::HeaderFieldStart;
+                    multipart_callback!(self, cb.on_part_header(self, &name, &value), index + 1);
+                },
+                MultipartState::HeadersLf => {
+                    if ch != LF {
+                        multipart_error!(self, MultipartError::InvalidHeader, index);
+                    }
+                    self.state = MultipartState::Data;
+                    multipart_callback!(self, cb.on_part_headers_complete(self), index + 1);
+                },
+                MultipartState::Data => {
+                    if ch == self.delimiter[self.matched] {
+                        if self.matched == 0 {
+                            if let Some(mark) = data_mark.take() {
+                                if let Err(err) = self.part_data(cb, &data[mark..index]) {
+                                    multipart_error!(self, err, mark);
+                                }
+                            }
+                        }
+                        self.matched += 1;
+                        if self.matched == self.delimiter.len() {
+                            self.matched = 0;
+                            self.state = MultipartState::BoundaryTail;
+                            multipart_callback!(self, cb.on_part_end(self), index + 1);
+                        }
+                    } else {
+                        if self.matched > 0 {
+                            // what looked like a delimiter was data; CR only starts one
+                            let prefix = self.delimiter[..self.matched].to_vec();
+                            self.matched = 0;
+                            if let Err(err) = self.part_data(cb, &prefix) {
+                                multipart_error!(self, err, index);
+                            }
+                        }
+                        if ch == CR {
+                            self.matched = 1;
+                        } else if data_mark.is_none() {
+                            data_mark = Some(index);
+                        }
+                    }
+                },
+                MultipartState::Epilogue => (),
+            }
+        }
+
+        if let Some(mark) = data_mark {
+            if let Err(err) = self.part_data(cb, &data[mark..len]) {
+                multipart_error!(self, err, mark);
+            }
+        }
+
+        if len < data.len() {
+            multipart_error!(self, MultipartError::BodyTooLarge, len);
+        }
+        len
+    }
+
+    fn part_data<T: MultipartCallback>(&mut self, cb: &mut T, data: &[u8]) -> Result<(), MultipartError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if data.len() as u64 > self.max_part_size - self.part_size {
+            return Err(MultipartError::PartTooLarge);
+        }
+        self.part_size += data.len() as u64;
+        cb.on_part_data(self, data).map(|_| ()).map_err(|_| MultipartError::CallbackFailed)
+    }
+}
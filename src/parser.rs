@@ -9,6 +9,10 @@ use error::HttpErrno;
 use http_method::HttpMethod;
 use http_version::HttpVersion;
 use callback::{HttpParserCallback, ParseAction};
+use proxy::{ProxyHeader, PROXY_HEADER_MAX_LENGTH};
+use protocol::Protocol;
+use lenient::Lenient;
+use status_code::StatusCode;
 
 /// `HttpParserType` is a type specifies whether the parser is going to parse a HTTP request,
 /// response or both.
@@ -22,6 +26,19 @@ pub enum HttpParserType {
     Both
 }
 
+/// `ObsFold` defines how the parser handles obsolete line folding, a header value that
+/// continues on the next line starting with a space or tab.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum ObsFold {
+    /// Deliver the continuation line as it is, leading whitespace included, without the CRLF
+    #[default]
+    Preserve,
+    /// Fail with `HttpErrno::InvalidObsFold`, as RFC 9112 asks of servers for requests
+    Reject,
+    /// Deliver a single space to `on_header_value` in place of the CRLF and the whitespace
+    Replace,
+}
+
 /// The HTTP parser that parses requests and responses.
 ///
 /// # Example
@@ -59,20 +76,58 @@ pub struct HttpParser {
 
     /// whether the protocol is upgraded
     pub upgrade: bool,
+
+    /// The protocol of the messages, `HTTP` by default. Set it before parsing to read
+    /// RTSP, SIP or ICAP messages.
+    pub protocol: Protocol,
+
+    /// Host of a CONNECT request target, without the brackets of an IPv6 literal
+    pub connect_host: Option<String>,
+    /// Port of a CONNECT request target
+    pub connect_port: Option<u16>,
+
+    /// whether to pass the bytes after an upgrade or CONNECT request to `on_tunnel_data`
+    /// instead of returning from `execute`
+    pub tunnel: bool,
+
+    /// whether to accept HTTP/0.9: a request line without version is a simple request
+    /// with no headers, and a response parser reads the whole stream as body until EOF
+    pub http09: bool,
+
+    /// how to handle obsolete line folding in header values. `on_obs_fold` is called for
+    /// each fold unless it is rejected.
+    pub obs_fold: ObsFold,
+
+    /// whether the connection starts with a PROXY protocol v1 or v2 header
+    pub proxy_protocol: bool,
+    /// The PROXY protocol header, once parsed
+    pub proxy_header: Option<ProxyHeader>,
     
     // TODO make it as a constructor parameter?
     /// whether using strict parsing mode
     pub strict: bool,      // parsing using strict rules
+    /// checks to skip in strict mode. Spaces after the chunk size are allowed by default,
+    /// as they always were.
+    pub lenient: Lenient,
 
     // private
     tp: HttpParserType,
     state: State,
     header_state: HeaderState,
-    flags: u8,
+    flags: u16,
     index: usize,             // index into current matcher
 
     nread: usize,            // bytes read in various scenarios
     content_length: u64,   // bytes in body (0 if no Content-Length header)
+    host: String,          // CONNECT host being parsed
+    port: u32,             // CONNECT port being parsed
+    proxy_buf: Vec<u8>,    // PROXY header bytes seen so far
+    token: String,         // method of a non-HTTP protocol being parsed
+
+    // ICAP encapsulated HTTP head: the message ends with its headers, whatever they say
+    pub(crate) encapsulated: bool,
+    // SSDP datagram: a message without Content-Length ends with the packet
+    pub(crate) datagram: bool,
 }
 
 //============== End of public interfaces ===================
@@ -92,10 +147,11 @@ macro_rules! callback(
     );
 );
 
-/// A macro that returns parsing error if it is in strict mode and the condition is not met.
-macro_rules! strict_check(
-    ($parser:ident, $cond:expr, $idx:expr) => (
-        if $parser.strict && $cond {
+/// A macro that returns parsing error if the condition is not met, unless the parser is
+/// lenient about it.
+macro_rules! lenient_check(
+    ($parser:ident, $flag:expr, $cond:expr, $idx:expr) => (
+        if $cond && !$parser.is_lenient($flag) {
             $parser.errno = Option::Some(HttpErrno::Strict);
             return $idx;
         }
@@ -112,6 +168,7 @@ macro_rules! mark(
 );
 
 const HTTP_MAX_HEADER_SIZE: usize = 80*1024;
+const MAX_METHOD_SIZE: usize = 16;
 const ULLONG_MAX: u64 = u64::MAX;
 
 const CR: u8 = b'\r';
@@ -122,9 +179,13 @@ const CONNECTION: &'static str = "connection";
 const CONTENT_LENGTH: &'static str = "content-length";
 const TRANSFER_ENCODING: &'static str = "transfer-encoding";
 const UPGRADE: &'static str = "upgrade";
+const EXPECT: &str = "expect";
 const CHUNKED: &'static str = "chunked";
 const KEEP_ALIVE: &'static str = "keep-alive";
 const CLOSE: &'static str = "close";
+const CONTINUE: &str = "100-continue";
+// what follows the empty header block of "PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n"
+const HTTP2_PREFACE_BODY: &str = "SM\r\n\r\n";
 
 fn is_normal_header_char(ch: u8) -> bool {
     ch == b'!' || (ch >= b'#' && ch <= b'\'') /* #, $, %, &, ' */||
@@ -133,8 +194,8 @@ fn is_normal_header_char(ch: u8) -> bool {
         (ch >= b'^' && ch <= b'z') /* ^, _, `, a-z */ || ch == b'|' || ch == b'~'
 }
 
-fn is_header_char(strict: bool, ch: u8) -> bool {
-    if strict {
+pub(crate) fn is_header_char(lenient: bool, ch: u8) -> bool {
+    if !lenient {
         is_normal_header_char(ch)
     } else {
         ch == b' ' || is_normal_header_char(ch)
@@ -146,8 +207,8 @@ fn is_normal_url_char(ch: u8) -> bool {
     ch == b'!' || ch == b'"' || (ch >= b'$' && ch <= b'>') || (ch >= b'@' && ch <= b'~')
 }
 
-fn is_url_char(strict: bool, ch: u8) -> bool {
-    is_normal_url_char(ch) || (!strict && ((ch & 0x80) > 0 || ch == 9 || ch == 12))
+fn is_url_char(lenient: bool, ch: u8) -> bool {
+    is_normal_url_char(ch) || (lenient && ((ch & 0x80) > 0 || ch == 9 || ch == 12))
 }
 
 fn unhex_value(ch: u8) -> Option<i32> {
@@ -189,6 +250,15 @@ fn is_userinfo_char(ch: u8) -> bool {
         ch == b'+' || ch == b'$' || ch == b','
 }
 
+// reg-name characters of an authority-form host
+fn is_host_char(ch: u8) -> bool {
+    ch != b':' && is_userinfo_char(ch)
+}
+
+fn is_ipv6_char(ch: u8) -> bool {
+    unhex_value(ch).is_some() || ch == b':' || ch == b'.'
+}
+
 impl HttpParser {
     /// Creates a parser of the specified type.
     ///
@@ -216,7 +286,22 @@ impl HttpParser {
             status_code: Option::None,
             method: Option::None,
             upgrade: false,
+            protocol: Protocol::Http,
+            connect_host: Option::None,
+            connect_port: Option::None,
+            tunnel: false,
+            http09: false,
+            obs_fold: ObsFold::Preserve,
+            proxy_protocol: false,
+            proxy_header: Option::None,
             strict: true,
+            lenient: Lenient::SPACES_AFTER_CHUNK_SIZE,
+            host: String::new(),
+            port: 0,
+            proxy_buf: Vec::new(),
+            token: String::new(),
+            encapsulated: false,
+            datagram: false,
         }
     }
 
@@ -256,6 +341,10 @@ impl HttpParser {
             return 0;
         }
 
+        if self.proxy_protocol && self.proxy_header.is_none() {
+            return self.execute_proxy_header(cb, data);
+        }
+
         if len == 0 {    // mean EOF
             match self.state {
                 State::BodyIdentityEof => {
@@ -266,7 +355,8 @@ impl HttpParser {
                 State::Dead | 
                 State::StartReqOrRes | 
                 State::StartReq | 
-                State::StartRes => {
+                State::StartRes |
+                State::Tunnel => {
                     return 0;
                 },
                 _ => {
@@ -295,7 +385,13 @@ impl HttpParser {
             State::ReqQueryStringStart |
             State::ReqQueryString |
             State::ReqFragmentStart |
-            State::ReqFragment => url_mark = Option::Some(0),
+            State::ReqFragment |
+            State::ReqConnectHostStart |
+            State::ReqConnectHost |
+            State::ReqConnectHostV6 |
+            State::ReqConnectHostV6End |
+            State::ReqConnectPortStart |
+            State::ReqConnectPort => url_mark = Option::Some(0),
             State::ResStatus => status_mark = Option::Some(0),
             _ => (),
         }
@@ -327,7 +423,9 @@ impl HttpParser {
                 let mut retry = false;
                 match self.state {
                     State::Dead => {
-                        if ch != CR && ch != LF {
+                        if self.is_lenient(Lenient::DATA_AFTER_CLOSE) {
+                            index = len - 1;
+                        } else if ch != CR && ch != LF {
                             self.errno = Option::Some(HttpErrno::ClosedConnection);
                             return index;
                         }
@@ -337,7 +435,13 @@ impl HttpParser {
                             self.flags = 0;
                             self.content_length = ULLONG_MAX;
 
-                            if ch == b'H' {
+                            if self.protocol != Protocol::Http {
+                                // the first token is either a method or the protocol name
+                                self.token.clear();
+                                self.state = State::ReqMethodToken;
+                                retry = true;
+                                callback!(self, cb.on_message_begin(self), HttpErrno::CBMessageBegin, index+1);
+                            } else if ch == b'H' {
                                 self.state = State::ResOrRespH;
                                 callback!(self, cb.on_message_begin(self), HttpErrno::CBMessageBegin, index+1);
                             } else {
@@ -350,7 +454,8 @@ impl HttpParser {
                     State::ResOrRespH => {
                         if ch == b'T' {
                             self.tp = HttpParserType::Response;
-                            self.state = State::ResHT;
+                            self.index = 2;
+                            self.state = State::ResProtocol;
                         } else {
                             if ch != b'E' {
                                 self.errno = Option::Some(HttpErrno::InvalidConstant);
@@ -367,32 +472,35 @@ impl HttpParser {
                         self.flags = 0;
                         self.content_length = ULLONG_MAX;
 
-                        match ch {
-                            b'H' => self.state = State::ResH,
-                            CR | LF => (),
-                            _ => {
+                        if self.http09 {
+                            // No status line and no headers, everything is body
+                            self.http_version.major = 0;
+                            self.http_version.minor = 9;
+                            self.state = State::BodyIdentityEof;
+                            callback!(self, cb.on_message_begin(self), HttpErrno::CBMessageBegin, index);
+                            callback!(self, cb.on_headers_complete(self), HttpErrno::CBHeadersComplete, index);
+                            retry = true;
+                        } else {
+                            if ch == self.protocol.name().as_bytes()[0] {
+                                self.index = 1;
+                                self.state = State::ResProtocol;
+                            } else if ch != CR && ch != LF {
                                 self.errno = Option::Some(HttpErrno::InvalidConstant);
                                 return index;
-                            },
+                            }
+
+                            callback!(self, cb.on_message_begin(self), HttpErrno::CBMessageBegin, index+1);
                         }
-                        
-                        callback!(self, cb.on_message_begin(self), HttpErrno::CBMessageBegin, index+1);
-                    },
-                    State::ResH => {
-                        strict_check!(self, ch != b'T', index);                       
-                        self.state = State::ResHT;
-                    },
-                    State::ResHT => {
-                        strict_check!(self, ch != b'T', index);
-                        self.state = State::ResHTT;
                     },
-                    State::ResHTT => {
-                        strict_check!(self, ch != b'P', index);
-                        self.state = State::ResHTTP;
-                    },
-                    State::ResHTTP => {
-                        strict_check!(self, ch != b'/', index);
-                        self.state = State::ResFirstHttpMajor;
+                    State::ResProtocol => {
+                        let name = self.protocol.name().as_bytes();
+                        if self.index < name.len() {
+                            lenient_check!(self, Lenient::VERSION, ch != name[self.index], index);
+                            self.index += 1;
+                        } else {
+                            lenient_check!(self, Lenient::VERSION, ch != b'/', index);
+                            self.state = State::ResFirstHttpMajor;
+                        }
                     },
                     State::ResFirstHttpMajor => {
                         if !is_num(ch) {
@@ -411,13 +519,20 @@ impl HttpParser {
                                 return index;
                             }
 
-                            self.http_version.major *= 10;
-                            self.http_version.major += ch - b'0';
+                            // a single digit unless lenient
+                            if !self.is_lenient(Lenient::VERSION) {
+                                self.errno = Option::Some(HttpErrno::InvalidVersion);
+                                return index;
+                            }
 
-                            if self.http_version.major > 99 {
+                            // at most two digits
+                            if self.http_version.major > 9 {
                                 self.errno = Option::Some(HttpErrno::InvalidVersion);
                                 return index;
                             }
+
+                            self.http_version.major *= 10;
+                            self.http_version.major += ch - b'0';
                         }
                     },
                     State::ResFirstHttpMinor => {
@@ -432,6 +547,10 @@ impl HttpParser {
                     // minor HTTP version or end of request line
                     State::ResHttpMinor => {
                         if ch == b' ' {
+                            if !self.is_supported_version() {
+                                self.errno = Option::Some(HttpErrno::UnsupportedVersion);
+                                return index;
+                            }
                             self.state = State::ResFirstStatusCode;
                         } else {
                             if !is_num(ch) {
@@ -439,13 +558,20 @@ impl HttpParser {
                                 return index;
                             }
 
-                            self.http_version.minor *= 10;
-                            self.http_version.minor += ch - b'0';
+                            // a single digit unless lenient
+                            if !self.is_lenient(Lenient::VERSION) {
+                                self.errno = Option::Some(HttpErrno::InvalidVersion);
+                                return index;
+                            }
 
-                            if self.http_version.minor > 99 {
+                            // at most two digits
+                            if self.http_version.minor > 9 {
                                 self.errno = Option::Some(HttpErrno::InvalidVersion);
                                 return index;
                             }
+
+                            self.http_version.minor *= 10;
+                            self.http_version.minor += ch - b'0';
                         }
                     },
                     State::ResFirstStatusCode => {
@@ -505,13 +631,27 @@ impl HttpParser {
                         }
                     },
                     State::ResLineAlmostDone => {
-                        strict_check!(self, ch != LF, index);
+                        lenient_check!(self, Lenient::OPTIONAL_LF_AFTER_CR, ch != LF, index);
                         self.state = State::HeaderFieldStart;
                     },
                     State::StartReq => {
                         if ch != CR && ch != LF {
                             self.flags = 0;
                             self.content_length = ULLONG_MAX;
+                            self.connect_host = Option::None;
+                            self.connect_port = Option::None;
+
+                            if self.protocol != Protocol::Http {
+                                if !is_alpha(ch) {
+                                    self.errno = Option::Some(HttpErrno::InvalidMethod);
+                                    return index;
+                                }
+                                self.token.clear();
+                                self.token.push(ch as char);
+                                self.state = State::ReqMethodToken;
+                                callback!(self, cb.on_message_begin(self), HttpErrno::CBMessageBegin, index+1);
+                                break;
+                            }
 
                             if !is_alpha(ch) {
                                 self.errno = Option::Some(HttpErrno::InvalidMethod);
@@ -527,7 +667,7 @@ impl HttpParser {
                                 b'M' => self.method = Option::Some(HttpMethod::MKCol), // or Move, MKActivity, Merge, MSearch, MKCalendar
                                 b'N' => self.method = Option::Some(HttpMethod::Notify),
                                 b'O' => self.method = Option::Some(HttpMethod::Options),
-                                b'P' => self.method = Option::Some(HttpMethod::Post), // or PropFind|PropPatch|Put|Patch|Purge
+                                b'P' => self.method = Option::Some(HttpMethod::Post), // or PropFind|PropPatch|Put|Patch|Purge|Pri
                                 b'R' => self.method = Option::Some(HttpMethod::Report),
                                 b'S' => self.method = Option::Some(HttpMethod::Subscribe), // or Search
                                 b'T' => self.method = Option::Some(HttpMethod::Trace),
@@ -583,7 +723,7 @@ impl HttpParser {
                             }
                         } else if self.index == 1 && self.method == Option::Some(HttpMethod::Post) {
                            if ch == b'R' {
-                               self.method = Option::Some(HttpMethod::PropFind); // or PropPatch
+                               self.method = Option::Some(HttpMethod::PropFind); // or PropPatch, Pri
                            } else if ch == b'U' {
                                self.method = Option::Some(HttpMethod::Put); // or Purge
                            } else if ch == b'A' {
@@ -607,6 +747,8 @@ impl HttpParser {
                                     self.errno = Option::Some(HttpErrno::InvalidMethod);
                                     return index;
                                 }
+                            } else if self.method == Option::Some(HttpMethod::PropFind) && ch == b'I' {
+                                self.method = Option::Some(HttpMethod::Pri);
                             } else {
                                 self.errno = Option::Some(HttpErrno::InvalidMethod);
                                 return index;
@@ -620,18 +762,118 @@ impl HttpParser {
 
                         self.index += 1;
                     },
+                    // methods of the other protocols are matched as a whole
+                    State::ReqMethodToken => {
+                        if ch == b' ' {
+                            self.method = match self.token.parse() {
+                                Ok(method) if self.protocol.has_method(method) => Option::Some(method),
+                                _ => {
+                                    self.errno = Option::Some(HttpErrno::InvalidMethod);
+                                    return index;
+                                },
+                            };
+                            self.tp = HttpParserType::Request;
+                            self.state = State::ReqSpacesBeforeUrl;
+                        } else if ch == b'/' && self.tp == HttpParserType::Both &&
+                            self.token == self.protocol.name() {
+                            self.tp = HttpParserType::Response;
+                            self.state = State::ResFirstHttpMajor;
+                        } else if (is_alpha(ch) || ch == b'_' || ch == b'-') &&
+                            self.token.len() < MAX_METHOD_SIZE {
+                            self.token.push(ch as char);
+                        } else {
+                            self.errno = Option::Some(HttpErrno::InvalidMethod);
+                            return index;
+                        }
+                    },
                     State::ReqSpacesBeforeUrl => {
                         if ch != b' ' {
                             mark!(url_mark, index);
                             if self.method == Option::Some(HttpMethod::Connect) {
-                                self.state = State::ReqServerStart;
+                                // CONNECT takes an authority-form target: host:port
+                                self.host.clear();
+                                self.port = 0;
+                                self.state = State::ReqConnectHostStart;
+                                retry = true;
+                            } else {
+                                self.state = HttpParser::parse_url_char(self, self.state, ch);
+                                if self.state == State::Dead {
+                                    self.errno = Option::Some(HttpErrno::InvalidUrl);
+                                    return index;
+                                }
                             }
-
-                            self.state = HttpParser::parse_url_char(self, self.state, ch);
-                            if self.state == State::Dead {
-                                self.errno = Option::Some(HttpErrno::InvalidUrl);
+                        }
+                    },
+                    State::ReqConnectHostStart => {
+                        if ch == b'[' {
+                            self.state = State::ReqConnectHostV6;
+                        } else if is_host_char(ch) {
+                            self.host.push(ch as char);
+                            self.state = State::ReqConnectHost;
+                        } else {
+                            self.errno = Option::Some(HttpErrno::InvalidHost);
+                            return index;
+                        }
+                    },
+                    State::ReqConnectHost => {
+                        if is_host_char(ch) {
+                            self.host.push(ch as char);
+                        } else if ch == b':' {
+                            self.state = State::ReqConnectPortStart;
+                        } else {
+                            // the port is mandatory
+                            self.errno = Option::Some(if ch == b' ' || ch == CR || ch == LF {
+                                HttpErrno::InvalidPort
+                            } else {
+                                HttpErrno::InvalidHost
+                            });
+                            return index;
+                        }
+                    },
+                    State::ReqConnectHostV6 => {
+                        if is_ipv6_char(ch) {
+                            self.host.push(ch as char);
+                        } else if ch == b']' && self.host.contains(':') {
+                            self.state = State::ReqConnectHostV6End;
+                        } else {
+                            self.errno = Option::Some(HttpErrno::InvalidHost);
+                            return index;
+                        }
+                    },
+                    State::ReqConnectHostV6End => {
+                        if ch == b':' {
+                            self.state = State::ReqConnectPortStart;
+                        } else {
+                            self.errno = Option::Some(if ch == b' ' || ch == CR || ch == LF {
+                                HttpErrno::InvalidPort
+                            } else {
+                                HttpErrno::InvalidHost
+                            });
+                            return index;
+                        }
+                    },
+                    State::ReqConnectPortStart |
+                    State::ReqConnectPort => {
+                        if is_num(ch) {
+                            self.port = self.port * 10 + (ch - b'0') as u32;
+                            if self.port > u16::MAX as u32 {
+                                self.errno = Option::Some(HttpErrno::InvalidPort);
                                 return index;
                             }
+                            self.state = State::ReqConnectPort;
+                        } else if ch == b' ' && self.state == State::ReqConnectPort {
+                            self.connect_host = Option::Some(self.host.clone());
+                            self.connect_port = Option::Some(self.port as u16);
+                            self.state = State::ReqHttpStart;
+                            if url_mark.is_some() {
+                                callback!(self,
+                                    cb.on_url(self, &data[url_mark.unwrap() .. index]),
+                                    HttpErrno::CBUrl, index+1);
+                                url_mark = Option::None;
+                            }
+                        } else {
+                            self.errno = Option::Some(HttpErrno::InvalidPort);
+                            return index;
                         }
                     },
                     State::ReqSchema |
@@ -673,8 +915,14 @@ impl HttpParser {
                             CR | LF => {
                                 self.http_version.major = 0;
                                 self.http_version.minor = 9;
+                                if self.http09 {
+                                    self.flags |= Flags::SimpleRequest.as_u16();
+                                }
                                 self.state = if ch == CR {
                                     State::ReqLineAlmostDone 
+                                } else if self.http09 {
+                                    retry = true;
+                                    State::HeadersAlmostDone
                                 } else {
                                     State::HeaderFieldStart
                                 };
@@ -695,30 +943,34 @@ impl HttpParser {
                         }
                     },
                     State::ReqHttpStart => {
-                        match ch {
-                            b'H' => self.state = State::ReqHttpH,
-                            b' ' => (),
-                            _    => {
-                                self.errno = Option::Some(HttpErrno::InvalidConstant);
-                                return index;
+                        if ch == self.protocol.name().as_bytes()[0] {
+                            self.index = 1;
+                            self.state = State::ReqProtocol;
+                        } else if self.http09 && (ch == CR || ch == LF) {
+                            // "GET /index.html \r\n"
+                            self.http_version.major = 0;
+                            self.http_version.minor = 9;
+                            self.flags |= Flags::SimpleRequest.as_u16();
+                            if ch == CR {
+                                self.state = State::ReqLineAlmostDone;
+                            } else {
+                                self.state = State::HeadersAlmostDone;
+                                retry = true;
                             }
+                        } else if ch != b' ' {
+                            self.errno = Option::Some(HttpErrno::InvalidConstant);
+                            return index;
                         }
                     },
-                    State::ReqHttpH => {
-                        strict_check!(self, ch != b'T', index);
-                        self.state = State::ReqHttpHT;
-                    },
-                    State::ReqHttpHT => {
-                        strict_check!(self, ch != b'T', index);
-                        self.state = State::ReqHttpHTT;
-                    },
-                    State::ReqHttpHTT => {
-                        strict_check!(self, ch != b'P', index);
-                        self.state = State::ReqHttpHTTP;
-                    },
-                    State::ReqHttpHTTP => {
-                        strict_check!(self, ch != b'/', index);
-                        self.state = State::ReqFirstHttpMajor;
+                    State::ReqProtocol => {
+                        let name = self.protocol.name().as_bytes();
+                        if self.index < name.len() {
+                            lenient_check!(self, Lenient::VERSION, ch != name[self.index], index);
+                            self.index += 1;
+                        } else {
+                            lenient_check!(self, Lenient::VERSION, ch != b'/', index);
+                            self.state = State::ReqFirstHttpMajor;
+                        }
                     },
                     // first digit of major HTTP version
                     State::ReqFirstHttpMajor => {
@@ -740,13 +992,20 @@ impl HttpParser {
                                 return index;
                             }
 
-                            self.http_version.major *= 10;
-                            self.http_version.major += ch - b'0';
+                            // a single digit unless lenient
+                            if !self.is_lenient(Lenient::VERSION) {
+                                self.errno = Option::Some(HttpErrno::InvalidVersion);
+                                return index;
+                            }
 
-                            if self.http_version.major > 99 {
+                            // at most two digits
+                            if self.http_version.major > 9 {
                                 self.errno = Option::Some(HttpErrno::InvalidVersion);
                                 return index;
                             }
+
+                            self.http_version.major *= 10;
+                            self.http_version.major += ch - b'0';
                         }
                     },
                     // first digit of minor HTTP version
@@ -761,18 +1020,30 @@ impl HttpParser {
                     },
                     // minor HTTP version or end of request line
                     State::ReqHttpMinor => {
+                        if (ch == CR || ch == LF) && !self.is_supported_version() {
+                            self.errno = Option::Some(HttpErrno::UnsupportedVersion);
+                            return index;
+                        }
+
                         if ch == CR {
                             self.state = State::ReqLineAlmostDone;
                         } else if ch == LF {
                             self.state = State::HeaderFieldStart;
                         } else if is_num(ch) {
-                            self.http_version.minor *= 10;
-                            self.http_version.minor += ch - b'0';
+                            // a single digit unless lenient
+                            if !self.is_lenient(Lenient::VERSION) {
+                                self.errno = Option::Some(HttpErrno::InvalidVersion);
+                                return index;
+                            }
 
-                            if self.http_version.minor > 99 {
+                            // at most two digits
+                            if self.http_version.minor > 9 {
                                 self.errno = Option::Some(HttpErrno::InvalidVersion);
                                 return index;
                             }
+
+                            self.http_version.minor *= 10;
+                            self.http_version.minor += ch - b'0';
                         } else {
                             self.errno = Option::Some(HttpErrno::InvalidVersion);
                             return index;
@@ -785,7 +1056,13 @@ impl HttpParser {
                             return index;
                         }
 
-                        self.state = State::HeaderFieldStart;
+                        if (self.flags & Flags::SimpleRequest.as_u16()) != 0 {
+                            // An HTTP/0.9 request has no headers
+                            self.state = State::HeadersAlmostDone;
+                            retry = true;
+                        } else {
+                            self.state = State::HeaderFieldStart;
+                        }
                     },
                     State::HeaderFieldStart => {
                         if ch == CR {
@@ -797,11 +1074,17 @@ impl HttpParser {
                             self.state = State::HeadersAlmostDone;
                             retry = true;
                         } else {
-                            if !is_header_char(self.strict, ch) {
+                            if !is_header_char(self.is_lenient(Lenient::HEADERS), ch) {
                                 self.errno = Option::Some(HttpErrno::InvalidHeaderToken);
                                 return index;
                             }
 
+                            // The HTTP/2 preface has no headers
+                            if self.method == Option::Some(HttpMethod::Pri) {
+                                self.errno = Option::Some(HttpErrno::InvalidConstant);
+                                return index;
+                            }
+
                             mark!(header_field_mark, index);
                             
                             self.index = 0;
@@ -812,12 +1095,13 @@ impl HttpParser {
                                 b'p' | b'P' => self.header_state = HeaderState::MatchingProxyConnection,
                                 b't' | b'T' => self.header_state = HeaderState::MatchingTransferEncoding,
                                 b'u' | b'U' => self.header_state = HeaderState::MatchingUpgrade,
+                                b'e' | b'E' => self.header_state = HeaderState::MatchingExpect,
                                 _    => self.header_state = HeaderState::General,
                             }
                         }
                     },
                     State::HeaderField => {
-                        if is_header_char(self.strict, ch) {
+                        if is_header_char(self.is_lenient(Lenient::HEADERS), ch) {
                             let c : u8 = lower(ch);
                             match self.header_state {
                                 HeaderState::General => (),
@@ -895,10 +1179,21 @@ impl HttpParser {
                                         self.header_state = HeaderState::Upgrade;
                                     }
                                 },
+                                // expect
+                                HeaderState::MatchingExpect => {
+                                    self.index += 1;
+                                    if self.index >= EXPECT.len() ||
+                                        c != (EXPECT[self.index ..].bytes().next().unwrap()) {
+                                        self.header_state = HeaderState::General;
+                                    } else if self.index == EXPECT.len()-1 {
+                                        self.header_state = HeaderState::Expect;
+                                    }
+                                },
                                 HeaderState::Connection |
                                 HeaderState::ContentLength |
                                 HeaderState::TransferEncoding |
-                                HeaderState::Upgrade => {
+                                HeaderState::Upgrade |
+                                HeaderState::Expect => {
                                     if ch != b' ' {
                                         self.header_state = HeaderState::General;
                                     }
@@ -941,15 +1236,19 @@ impl HttpParser {
 
                         match self.header_state {
                             HeaderState::Upgrade => {
-                                self.flags |= Flags::Upgrade.as_u8();
+                                self.flags |= Flags::Upgrade.as_u16();
                                 self.header_state = HeaderState::General;
                             },
                             HeaderState::TransferEncoding => {
+                                self.flags |= Flags::TransferEncoding.as_u16();
                                 // looking for 'Transfer-Encoding: chunked
                                 if c == b'c' {
                                     self.header_state = HeaderState::MatchingTransferEncodingChunked;
+                                } else if c == b',' {
+                                    self.header_state = HeaderState::TransferEncodingDelimiter;
                                 } else {
-                                    self.header_state = HeaderState::General;
+                                    self.flags &= !Flags::Chunked.as_u16();
+                                    self.header_state = HeaderState::TransferEncodingToken;
                                 }
                             },
                             HeaderState::ContentLength => {
@@ -958,8 +1257,19 @@ impl HttpParser {
                                     return index;
                                 }
 
+                                self.flags |= Flags::ContentLength.as_u16();
                                 self.content_length = (ch - b'0') as u64;
                             },
+                            HeaderState::Expect => {
+                                // looking for 'Expect: 100-continue
+                                if c == b'1' {
+                                    self.header_state = HeaderState::MatchingExpectContinue;
+                                } else if c == b',' {
+                                    self.header_state = HeaderState::ExpectDelimiter;
+                                } else {
+                                    self.header_state = HeaderState::ExpectToken;
+                                }
+                            },
                             HeaderState::Connection => {
                                 // looking for 'Connection: keep-alive
                                 if c == b'k' {
@@ -997,7 +1307,8 @@ impl HttpParser {
 
                             match self.header_state {
                                 HeaderState::General => (),
-                                HeaderState::Connection | HeaderState::TransferEncoding => {
+                                HeaderState::Connection | HeaderState::TransferEncoding |
+                                HeaderState::Expect => {
                                     panic!("Shouldn't get here.");
                                 },
                                 HeaderState::ContentLength => {
@@ -1021,16 +1332,40 @@ impl HttpParser {
                                         self.content_length = t;
                                     }
                                 },
-                                // Transfer-Encoding: chunked
+                                // Transfer-Encoding: chunked, as the final coding of the list
+                                HeaderState::MatchingTransferEncodingChunked |
+                                HeaderState::TransferEncodingToken if c == b',' => {
+                                    self.header_state = HeaderState::TransferEncodingDelimiter;
+                                },
+                                HeaderState::TransferEncodingChunked if c == b',' => {
+                                    self.flags |= Flags::Chunked.as_u16();
+                                    self.header_state = HeaderState::TransferEncodingDelimiter;
+                                },
                                 HeaderState::MatchingTransferEncodingChunked => {
                                     self.index += 1;
                                     if self.index >= CHUNKED.len() ||
                                         c != (CHUNKED[self.index ..].bytes().next().unwrap()) {
-                                        self.header_state = HeaderState::General;
+                                        self.flags &= !Flags::Chunked.as_u16();
+                                        self.header_state = HeaderState::TransferEncodingToken;
                                     } else if self.index == CHUNKED.len()-1 {
                                         self.header_state = HeaderState::TransferEncodingChunked;
                                     }
                                 },
+                                HeaderState::TransferEncodingToken => (),
+                                HeaderState::TransferEncodingDelimiter => {
+                                    if c == b'c' {
+                                        self.index = 0;
+                                        self.header_state = HeaderState::MatchingTransferEncodingChunked;
+                                    } else if c != b' ' && c != b'\t' && c != b',' {
+                                        self.flags &= !Flags::Chunked.as_u16();
+                                        self.header_state = HeaderState::TransferEncodingToken;
+                                    }
+                                },
+                                HeaderState::TransferEncodingChunked if ch != b' ' && ch != b'\t' => {
+                                    self.flags &= !Flags::Chunked.as_u16();
+                                    self.header_state = HeaderState::TransferEncodingToken;
+                                },
+                                HeaderState::TransferEncodingChunked => (),
                                 // looking for 'Connection: keep-alive
                                 HeaderState::MatchingConnectionKeepAlive => {
                                     self.index += 1;
@@ -1051,7 +1386,37 @@ impl HttpParser {
                                         self.header_state = HeaderState::ConnectionClose;
                                     }
                                 },
-                                HeaderState::TransferEncodingChunked |
+                                // looking for 'Expect: 100-continue, as any element of the list
+                                HeaderState::MatchingExpectContinue |
+                                HeaderState::ExpectToken if c == b',' => {
+                                    self.header_state = HeaderState::ExpectDelimiter;
+                                },
+                                HeaderState::ExpectContinue if c == b',' => {
+                                    self.flags |= Flags::ExpectContinue.as_u16();
+                                    self.header_state = HeaderState::ExpectDelimiter;
+                                },
+                                HeaderState::MatchingExpectContinue => {
+                                    self.index += 1;
+                                    if self.index >= CONTINUE.len() ||
+                                        c != (CONTINUE[self.index ..].bytes().next().unwrap()) {
+                                        self.header_state = HeaderState::ExpectToken;
+                                    } else if self.index == CONTINUE.len()-1 {
+                                        self.header_state = HeaderState::ExpectContinue;
+                                    }
+                                },
+                                HeaderState::ExpectToken => (),
+                                HeaderState::ExpectDelimiter => {
+                                    if c == b'1' {
+                                        self.index = 0;
+                                        self.header_state = HeaderState::MatchingExpectContinue;
+                                    } else if c != b' ' && c != b'\t' && c != b',' {
+                                        self.header_state = HeaderState::ExpectToken;
+                                    }
+                                },
+                                HeaderState::ExpectContinue if ch != b' ' && ch != b'\t' => {
+                                    self.header_state = HeaderState::ExpectToken;
+                                },
+                                HeaderState::ExpectContinue => (),
                                 HeaderState::ConnectionKeepAlive |
                                 HeaderState::ConnectionClose => {
                                     if ch != b' ' {
@@ -1066,24 +1431,43 @@ impl HttpParser {
                         }
                     },
                     State::HeaderAlmostDone => {
-                        strict_check!(self, ch != LF, index);
+                        lenient_check!(self, Lenient::OPTIONAL_LF_AFTER_CR, ch != LF, index);
                         self.state = State::HeaderValueLws;
                     },
                     State::HeaderValueLws => {
                         if ch == b' ' || ch == b'\t' {
-                            self.state = State::HeaderValueStart;
-                            retry = true;
+                            if self.obs_fold == ObsFold::Reject {
+                                self.errno = Option::Some(HttpErrno::InvalidObsFold);
+                                return index;
+                            }
+                            callback!(self, cb.on_obs_fold(self), HttpErrno::CBObsFold, index);
+
+                            if self.obs_fold == ObsFold::Replace {
+                                callback!(self, cb.on_header_value(self, b" "),
+                                          HttpErrno::CBHeaderValue, index+1);
+                                self.state = State::HeaderValueDiscardWs;
+                            } else {
+                                self.state = State::HeaderValueStart;
+                                retry = true;
+                            }
                         } else {
                             // finished the header
                             match self.header_state {
                                 HeaderState::ConnectionKeepAlive => {
-                                    self.flags |= Flags::ConnectionKeepAlive.as_u8();
+                                    self.flags |= Flags::ConnectionKeepAlive.as_u16();
                                 },
                                 HeaderState::ConnectionClose => {
-                                    self.flags |= Flags::ConnectionClose.as_u8();
+                                    self.flags |= Flags::ConnectionClose.as_u16();
                                 },
                                 HeaderState::TransferEncodingChunked => {
-                                    self.flags |= Flags::Chunked.as_u8();
+                                    self.flags |= Flags::Chunked.as_u16();
+                                },
+                                HeaderState::MatchingTransferEncodingChunked |
+                                HeaderState::TransferEncodingToken => {
+                                    self.flags &= !Flags::Chunked.as_u16();
+                                },
+                                HeaderState::ExpectContinue => {
+                                    self.flags |= Flags::ExpectContinue.as_u16();
                                 },
                                 _ => (),
                             }
@@ -1093,11 +1477,17 @@ impl HttpParser {
                         }
                     },
                     State::HeaderValueDiscardWsAlmostDone => {
-                        strict_check!(self, ch != LF, index);
+                        lenient_check!(self, Lenient::OPTIONAL_LF_AFTER_CR, ch != LF, index);
                         self.state = State::HeaderValueDiscardLws;
                     },
                     State::HeaderValueDiscardLws => {
                         if ch == b' ' || ch == b'\t' {
+                            // obs-fold after an empty value, there is nothing to replace
+                            if self.obs_fold == ObsFold::Reject {
+                                self.errno = Option::Some(HttpErrno::InvalidObsFold);
+                                return index;
+                            }
+                            callback!(self, cb.on_obs_fold(self), HttpErrno::CBObsFold, index);
                             self.state = State::HeaderValueDiscardWs;
                         } else {
                             // header value was empty
@@ -1113,9 +1503,9 @@ impl HttpParser {
                         }
                     },
                     State::HeadersAlmostDone => {
-                        strict_check!(self, ch != LF, index);
+                        lenient_check!(self, Lenient::OPTIONAL_LF_AFTER_CR, ch != LF, index);
 
-                        if (self.flags & Flags::Trailing.as_u8()) > 0 {
+                        if (self.flags & Flags::Trailing.as_u16()) > 0 {
                             // End of a chunked request
                             self.new_message();
                             callback!(self, cb.on_message_complete(self), 
@@ -1123,14 +1513,39 @@ impl HttpParser {
                         } else {
                             self.state = State::HeadersDone;
 
+                            // A body framed two ways can be read differently by a proxy
+                            // and the server behind it
+                            if (self.flags & Flags::Chunked.as_u16()) != 0 &&
+                                (self.flags & Flags::ContentLength.as_u16()) != 0 &&
+                                !self.is_lenient(Lenient::CHUNKED_LENGTH) {
+                                self.errno = Option::Some(HttpErrno::UnexpectedContentLength);
+                                return index;
+                            }
+                            if self.tp == HttpParserType::Request &&
+                                (self.flags & Flags::TransferEncoding.as_u16()) != 0 &&
+                                (self.flags & Flags::Chunked.as_u16()) == 0 &&
+                                !self.is_lenient(Lenient::TRANSFER_ENCODING) {
+                                self.errno = Option::Some(HttpErrno::InvalidTransferEncoding);
+                                return index;
+                            }
+
+                            if self.method == Option::Some(HttpMethod::Pri) &&
+                                self.http_version != HttpVersion::HTTP_20 {
+                                self.errno = Option::Some(HttpErrno::InvalidVersion);
+                                return index;
+                            }
+
                             // Set this here so that on_headers_complete()
                             // callbacks can see it
-                            self.upgrade = (self.flags & Flags::Upgrade.as_u8() != 0) ||
-                                self.method == Option::Some(HttpMethod::Connect);
+                            self.upgrade = !self.is_informational() &&
+                                ((self.flags & Flags::Upgrade.as_u16() != 0) ||
+                                 self.method == Option::Some(HttpMethod::Connect) ||
+                                 self.method == Option::Some(HttpMethod::Pri));
 
                             match cb.on_headers_complete(self) {
                                 Ok(ParseAction::None) => (),
-                                Ok(ParseAction::SkipBody) => self.flags |= Flags::SkipBody.as_u8(),
+                                Ok(ParseAction::SkipBody) => self.flags |= Flags::SkipBody.as_u16(),
+                                Ok(ParseAction::DiscardBody) => self.flags |= Flags::DiscardBody.as_u16(),
                                 _     => {
                                     self.errno = Option::Some(HttpErrno::CBHeadersComplete);
                                     return index; // Error
@@ -1144,22 +1559,39 @@ impl HttpParser {
                         }
                     },
                     State::HeadersDone => {
-                        strict_check!(self, ch != LF, index);
+                        lenient_check!(self, Lenient::OPTIONAL_LF_AFTER_CR, ch != LF, index);
                         self.nread = 0;
 
-                        // Exit, The rest of the connect is in a different protocol
-                        if self.upgrade {
+                        if self.encapsulated {
+                            // The ICAP parser feeds the encapsulated body separately
+                            self.new_message();
+                            callback!(self, cb.on_message_complete(self),
+                                      HttpErrno::CBMessageComplete, index+1);
+                        } else if self.is_informational() {
+                            // An interim response never has a body, and the final response
+                            // follows on the same connection.
+                            self.state = State::StartRes;
+                            callback!(self, cb.on_informational(self),
+                                      HttpErrno::CBInformational, index+1);
+                        } else if self.method == Option::Some(HttpMethod::Pri) {
+                            self.index = 0;
+                            self.state = State::Http2Preface;
+                        } else if self.upgrade && self.tunnel {
+                            // The rest of the connection goes to on_tunnel_data
+                            self.state = State::Tunnel;
+                            callback!(self, cb.on_message_complete(self),
+                                      HttpErrno::CBMessageComplete, index+1);
+                        } else if self.upgrade {
+                            // Exit, The rest of the connect is in a different protocol
                             self.new_message();
                             callback!(self, cb.on_message_complete(self), 
                                       HttpErrno::CBMessageComplete, index+1);
                             return index+1;
-                        }
-
-                        if (self.flags & Flags::SkipBody.as_u8()) != 0 {
+                        } else if (self.flags & Flags::SkipBody.as_u16()) != 0 {
                             self.new_message();
                             callback!(self, cb.on_message_complete(self), 
                                       HttpErrno::CBMessageComplete, index+1);
-                        } else if (self.flags & Flags::Chunked.as_u8()) != 0 {
+                        } else if (self.flags & Flags::Chunked.as_u16()) != 0 {
                             // chunked encoding - ignore Content-Length header
                             self.state = State::ChunkSizeStart;
                         } else {
@@ -1172,8 +1604,7 @@ impl HttpParser {
                                 // Content-Length header given and non-zero
                                 self.state = State::BodyIdentity;
                             } else {
-                                if self.tp == HttpParserType::Request ||
-                                    !self.http_message_needs_eof() {
+                                if !self.http_message_needs_eof() {
                                     // Assume content-length 0 - read the next
                                     self.new_message();
                                     callback!(self, cb.on_message_complete(self), 
@@ -1191,7 +1622,9 @@ impl HttpParser {
                         assert!(self.content_length != 0 &&
                                 self.content_length != ULLONG_MAX);
 
-                        mark!(body_mark, index);
+                        if !self.discarding_body() {
+                            mark!(body_mark, index);
+                        }
                         self.content_length -= to_read as u64;
 
                         index += to_read - 1;
@@ -1210,7 +1643,9 @@ impl HttpParser {
                     },
                     // read until EOF
                     State::BodyIdentityEof => {
-                        mark!(body_mark, index);
+                        if !self.discarding_body() {
+                            mark!(body_mark, index);
+                        }
                         index = len - 1;
                     },
                     State::MessageDone => {
@@ -1218,9 +1653,35 @@ impl HttpParser {
                         callback!(self, cb.on_message_complete(self), 
                                   HttpErrno::CBMessageComplete, index+1);
                     },
+                    State::Http2Preface => {
+                        if ch != HTTP2_PREFACE_BODY.as_bytes()[self.index] {
+                            self.errno = Option::Some(HttpErrno::InvalidConstant);
+                            return index;
+                        }
+                        self.index += 1;
+
+                        if self.index == HTTP2_PREFACE_BODY.len() {
+                            // The rest of the connection is HTTP/2 frames
+                            if self.tunnel {
+                                self.state = State::Tunnel;
+                                callback!(self, cb.on_message_complete(self),
+                                          HttpErrno::CBMessageComplete, index+1);
+                            } else {
+                                self.new_message();
+                                callback!(self, cb.on_message_complete(self),
+                                          HttpErrno::CBMessageComplete, index+1);
+                                return index+1;
+                            }
+                        }
+                    },
+                    State::Tunnel => {
+                        callback!(self, cb.on_tunnel_data(self, &data[index ..]),
+                                  HttpErrno::CBTunnelData, index);
+                        return len;
+                    },
                     State::ChunkSizeStart => {
                         assert!(self.nread == 1);
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
 
                         let unhex_val = unhex_value(ch);
                         if unhex_val.is_none() {
@@ -1232,14 +1693,14 @@ impl HttpParser {
                         self.state = State::ChunkSize;
                     },
                     State::ChunkSize => {
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
 
                         if ch == CR {
                             self.state = State::ChunkSizeAlmostDone;
                         } else {
                             let unhex_val = unhex_value(ch);
                             if unhex_val.is_none() {
-                                if ch == b';' || ch == b' ' {
+                                if ch == b';' || (ch == b' ' && self.is_lenient(Lenient::SPACES_AFTER_CHUNK_SIZE)) {
                                     self.state = State::ChunkParameters;
                                 } else {
                                     self.errno = Option::Some(HttpErrno::InvalidChunkSize);
@@ -1261,20 +1722,20 @@ impl HttpParser {
                         }
                     },
                     State::ChunkParameters => {
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
                         // just ignore this shit. TODO check for overflow
                         if ch == CR {
                             self.state = State::ChunkSizeAlmostDone;
                         }
                     },
                     State::ChunkSizeAlmostDone => {
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
-                        strict_check!(self, ch != LF, index);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
+                        lenient_check!(self, Lenient::OPTIONAL_LF_AFTER_CR, ch != LF, index);
 
                         self.nread = 0;
 
                         if self.content_length == 0 {
-                            self.flags |= Flags::Trailing.as_u8();
+                            self.flags |= Flags::Trailing.as_u16();
                             self.state = State::HeaderFieldStart;
                         } else {
                             self.state = State::ChunkData;
@@ -1283,11 +1744,13 @@ impl HttpParser {
                     State::ChunkData => {
                         let to_read: usize = cmp::min(self.content_length,
                                                          (len - index) as u64) as usize;
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
                         assert!(self.content_length != 0 &&
                                 self.content_length != ULLONG_MAX);
 
-                        mark!(body_mark, index);
+                        if !self.discarding_body() {
+                            mark!(body_mark, index);
+                        }
                         self.content_length -= to_read as u64;
                         index += to_read - 1;
 
@@ -1296,9 +1759,9 @@ impl HttpParser {
                         }
                     },
                     State::ChunkDataAlmostDone => {
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
                         assert!(self.content_length == 0);
-                        strict_check!(self, ch != CR, index);
+                        lenient_check!(self, Lenient::OPTIONAL_CRLF_AFTER_CHUNK, ch != CR, index);
                         self.state = State::ChunkDataDone;
 
                         if body_mark.is_some() {
@@ -1309,8 +1772,8 @@ impl HttpParser {
                         }
                     },
                     State::ChunkDataDone => {
-                        assert!(self.flags & Flags::Chunked.as_u8() != 0);
-                        strict_check!(self, ch != LF, index);
+                        assert!(self.flags & Flags::Chunked.as_u16() != 0);
+                        lenient_check!(self, Lenient::OPTIONAL_CRLF_AFTER_CHUNK, ch != LF, index);
                         self.nread = 0;
                         self.state = State::ChunkSizeStart;
                     }
@@ -1366,6 +1829,40 @@ impl HttpParser {
         self.state == State::MessageDone
     }
 
+    /// Returns true if the current response is an interim (1xx) response, such as
+    /// `100 Continue` or `103 Early Hints`. `101 Switching Protocols` is a final response.
+    pub fn is_informational(&self) -> bool {
+        match self.status() {
+            Some(status) => self.tp == HttpParserType::Response &&
+                status.is_informational() && status != StatusCode::SWITCHING_PROTOCOLS,
+            None => false,
+        }
+    }
+
+    /// The status code of the current response as a `StatusCode`.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status_code.and_then(StatusCode::new)
+    }
+
+    /// Returns true if the request carries `Expect: 100-continue`, meaning the client waits
+    /// for a `100 Continue` before sending the body. Only HTTP/1.1 and later requests can
+    /// expect it. Check it in `on_headers_complete` and return `ParseAction::DiscardBody`
+    /// to reject the body after answering with `417 Expectation Failed`.
+    pub fn expects_continue(&self) -> bool {
+        self.tp == HttpParserType::Request &&
+            (self.flags & Flags::ExpectContinue.as_u16()) != 0 &&
+            (self.http_version.major > 1 ||
+             (self.http_version.major == 1 && self.http_version.minor >= 1))
+    }
+
+    /// Returns true if the request was the HTTP/2 connection preface
+    /// (`PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n`) sent by a client with prior knowledge.
+    /// `upgrade` is set as well, and the offset returned by `execute` points at the first
+    /// HTTP/2 frame.
+    pub fn is_http2_preface(&self) -> bool {
+        self.tp == HttpParserType::Request && self.method == Option::Some(HttpMethod::Pri)
+    }
+
     /// Pauses the parser.
     pub fn pause(&mut self, pause: bool) {
         if self.errno.is_none() || self.errno == Option::Some(HttpErrno::Paused) {
@@ -1381,14 +1878,21 @@ impl HttpParser {
 
     /// Returns true if it needs to keep alive.
     pub fn http_should_keep_alive(&self) -> bool {
-        if self.http_version.major > 0 && self.http_version.minor > 0 {
+        // The connection stays open at least until the final response
+        if self.is_informational() {
+            return true;
+        }
+
+        // RTSP, SIP and ICAP connections are persistent like HTTP/1.1
+        if self.protocol != Protocol::Http ||
+            self.http_version >= HttpVersion::HTTP_11 {
             // HTTP/1.1
-            if (self.flags & Flags::ConnectionClose.as_u8()) != 0 {
+            if (self.flags & Flags::ConnectionClose.as_u16()) != 0 {
                 return false
             }
         } else {
             // HTTP/1.0 or earlier
-            if (self.flags & Flags::ConnectionKeepAlive.as_u8()) == 0 {
+            if (self.flags & Flags::ConnectionKeepAlive.as_u16()) == 0 {
                 return false
             }
         }
@@ -1399,7 +1903,13 @@ impl HttpParser {
     // Our URL parser
     fn parse_url_char(&self, s: State, ch: u8) -> State {
          
-        if ch == b' ' || ch == b'\r' || ch == b'\n' || (self.strict && (ch == b'\t' || ch == b'\x0C')) { // '\x0C' = '\f'
+        if ch == b' ' || ch == b'\r' || ch == b'\n' || (!self.is_lenient(Lenient::URL) && (ch == b'\t' || ch == b'\x0C')) { // '\x0C' = '\f'
+            return State::Dead;
+        }
+
+        // The HTTP/2 preface only has the asterisk-form target
+        if self.method == Option::Some(HttpMethod::Pri) &&
+            (s != State::ReqSpacesBeforeUrl || ch != b'*') {
             return State::Dead;
         }
 
@@ -1412,6 +1922,11 @@ impl HttpParser {
                     return State::ReqPath;
                 }
 
+                // SIP URIs such as sip:alice@example.com have no authority
+                if self.protocol == Protocol::Sip && is_alpha(ch) {
+                    return State::ReqPath;
+                }
+
                 if is_alpha(ch) {
                     return State::ReqSchema;
                 }
@@ -1454,7 +1969,7 @@ impl HttpParser {
                 }
             },
             State::ReqPath => {
-                if is_url_char(self.strict, ch) {
+                if is_url_char(self.is_lenient(Lenient::URL), ch) {
                     return s;
                 }
 
@@ -1465,7 +1980,7 @@ impl HttpParser {
                 }
             },
             State::ReqQueryStringStart | State::ReqQueryString => {
-                if is_url_char(self.strict, ch) {
+                if is_url_char(self.is_lenient(Lenient::URL), ch) {
                     return State::ReqQueryString;
                 }
 
@@ -1476,7 +1991,7 @@ impl HttpParser {
                 }
             },
             State::ReqFragmentStart => {
-                if is_url_char(self.strict, ch) {
+                if is_url_char(self.is_lenient(Lenient::URL), ch) {
                     return State::ReqFragment;
                 }
 
@@ -1487,7 +2002,7 @@ impl HttpParser {
                 }
             },
             State::ReqFragment => {
-                if is_url_char(self.strict, ch) {
+                if is_url_char(self.is_lenient(Lenient::URL), ch) {
                     return s;
                 }
 
@@ -1504,20 +2019,19 @@ impl HttpParser {
 
     // Does the parser need to see an EOF to find the end of the message?
     fn http_message_needs_eof(&self) -> bool {
-        if self.tp == HttpParserType::Request {
+        // Only HTTP delimits a response body by closing the connection, and any body
+        // in a datagram by the end of the packet
+        if (self.tp == HttpParserType::Request && !self.datagram) || self.protocol != Protocol::Http {
             return false
         }
 
-        let status_code = self.status_code.unwrap_or(0);
         // See RFC 2616 section 4.4
-        if status_code / 100 == 1 || // 1xx e.g. Continue
-            status_code == 204 ||    // No Content
-            status_code == 304 ||    // Not Modified
-            (self.flags & Flags::SkipBody.as_u8()) != 0 {// response to a HEAD request
+        if matches!(self.status(), Some(status) if !status.allows_body()) ||
+            (self.flags & Flags::SkipBody.as_u16()) != 0 {// response to a HEAD request
             return false
         }
 
-        if (self.flags & Flags::Chunked.as_u8() != 0) ||
+        if (self.flags & Flags::Chunked.as_u16() != 0) ||
             self.content_length != ULLONG_MAX {
             return false
         }
@@ -1525,9 +2039,68 @@ impl HttpParser {
         true
     }
 
+    fn discarding_body(&self) -> bool {
+        (self.flags & Flags::DiscardBody.as_u16()) != 0
+    }
+
+    // Continues with an ICAP encapsulated body, which is always chunked
+    pub(crate) fn start_chunked_body(&mut self) {
+        self.flags = Flags::Chunked.as_u16();
+        self.nread = 0;
+        self.state = State::ChunkSizeStart;
+    }
+
+    // Buffers the PROXY protocol header, then parses the rest of `data` as HTTP
+    fn execute_proxy_header<T: HttpParserCallback>(&mut self, cb: &mut T, data: &[u8]) -> usize {
+        if data.is_empty() {    // mean EOF
+            if !self.proxy_buf.is_empty() {
+                self.errno = Option::Some(HttpErrno::InvalidEofState);
+            }
+            return 0;
+        }
+
+        let buffered = self.proxy_buf.len();
+        let take = cmp::min(data.len(), PROXY_HEADER_MAX_LENGTH - buffered);
+        self.proxy_buf.extend_from_slice(&data[.. take]);
+
+        match ProxyHeader::parse(&self.proxy_buf) {
+            Err(..) => {
+                self.errno = Option::Some(HttpErrno::InvalidProxyHeader);
+                0
+            },
+            Ok(None) => take,
+            Ok(Some((header, header_len))) => {
+                let index = header_len - buffered;
+                self.proxy_buf = Vec::new();
+                self.proxy_header = Option::Some(header);
+                callback!(self, cb.on_proxy_header(self),
+                          HttpErrno::CBProxyHeader, index);
+
+                if index < data.len() {
+                    index + self.execute(cb, &data[index ..])
+                } else {
+                    index
+                }
+            },
+        }
+    }
+
+    // Only HTTP/0.9, HTTP/1.0 and HTTP/1.1 are supported, unless lenient. RTSP, SIP and
+    // ICAP have versions of their own, and the HTTP/2 preface checks its version itself.
+    fn is_supported_version(&self) -> bool {
+        self.protocol != Protocol::Http || self.method == Option::Some(HttpMethod::Pri) ||
+            self.is_lenient(Lenient::VERSION) ||
+            matches!(self.http_version, HttpVersion::HTTP_09 | HttpVersion::HTTP_10 | HttpVersion::HTTP_11)
+    }
+
+    // Returns true if the check `flag` is skipped
+    fn is_lenient(&self, flag: Lenient) -> bool {
+        !self.strict || self.lenient.contains(flag)
+    }
+
     fn new_message(&mut self) {
         let new_state = if self.tp == HttpParserType::Request { State::StartReq } else { State::StartRes };
-        self.state = if self.strict {
+        self.state = if !self.is_lenient(Lenient::KEEP_ALIVE) {
                         if self.http_should_keep_alive() {
                             new_state
                         } else {
@@ -0,0 +1,91 @@
+//! Protocols sharing the HTTP/1.x message grammar: HTTP, RTSP, SIP and ICAP.
+
+use std::fmt;
+use std::str::FromStr;
+
+use error::HttpErrno;
+use http_method::HttpMethod;
+
+/// `Protocol` defines the HTTP-like protocols the parser understands. They share the
+/// message grammar of HTTP/1.x and differ in the protocol name of the start line and in
+/// their methods.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Protocol {
+    /// HTTP (RFC 9112)
+    #[default]
+    Http,
+    /// Real Time Streaming Protocol (RFC 2326, RFC 7826)
+    Rtsp,
+    /// Session Initiation Protocol (RFC 3261)
+    Sip,
+    /// Internet Content Adaptation Protocol (RFC 3507)
+    Icap,
+}
+
+impl Protocol {
+    /// The protocol name used in the start line, such as `RTSP` in `RTSP/1.0`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Protocol::Http => "HTTP",
+            Protocol::Rtsp => "RTSP",
+            Protocol::Sip  => "SIP",
+            Protocol::Icap => "ICAP",
+        }
+    }
+
+    /// Returns true if `method` is defined by the protocol.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use http_parser::*;
+    /// assert!(Protocol::Rtsp.has_method(HttpMethod::Setup));
+    /// assert!(!Protocol::Http.has_method(HttpMethod::Setup));
+    /// assert!(Protocol::Sip.has_method(HttpMethod::Subscribe));
+    /// ```
+    pub fn has_method(self, method: HttpMethod) -> bool {
+        match self {
+            Protocol::Http => !matches!(method,
+                HttpMethod::Describe | HttpMethod::Announce | HttpMethod::Setup |
+                HttpMethod::Play | HttpMethod::Pause | HttpMethod::Teardown |
+                HttpMethod::GetParameter | HttpMethod::SetParameter | HttpMethod::Redirect |
+                HttpMethod::Record | HttpMethod::PlayNotify | HttpMethod::Invite |
+                HttpMethod::Ack | HttpMethod::Bye | HttpMethod::Cancel | HttpMethod::Register |
+                HttpMethod::Prack | HttpMethod::Publish | HttpMethod::Info | HttpMethod::Refer |
+                HttpMethod::Message | HttpMethod::Update | HttpMethod::ReqMod |
+                HttpMethod::RespMod),
+            Protocol::Rtsp => matches!(method,
+                HttpMethod::Options | HttpMethod::Describe | HttpMethod::Announce |
+                HttpMethod::Setup | HttpMethod::Play | HttpMethod::Pause | HttpMethod::Teardown |
+                HttpMethod::GetParameter | HttpMethod::SetParameter | HttpMethod::Redirect |
+                HttpMethod::Record | HttpMethod::PlayNotify),
+            Protocol::Sip => matches!(method,
+                HttpMethod::Invite | HttpMethod::Ack | HttpMethod::Bye | HttpMethod::Cancel |
+                HttpMethod::Options | HttpMethod::Register | HttpMethod::Prack |
+                HttpMethod::Subscribe | HttpMethod::Notify | HttpMethod::Publish |
+                HttpMethod::Info | HttpMethod::Refer | HttpMethod::Message | HttpMethod::Update),
+            Protocol::Icap => matches!(method,
+                HttpMethod::Options | HttpMethod::ReqMod | HttpMethod::RespMod),
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = HttpErrno;
+
+    fn from_str(s: &str) -> Result<Protocol, HttpErrno> {
+        match s {
+            "HTTP" => Ok(Protocol::Http),
+            "RTSP" => Ok(Protocol::Rtsp),
+            "SIP"  => Ok(Protocol::Sip),
+            "ICAP" => Ok(Protocol::Icap),
+            _      => Err(HttpErrno::InvalidConstant),
+        }
+    }
+}
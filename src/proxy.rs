@@ -0,0 +1,288 @@
+//! PROXY protocol v1 (text) and v2 (binary) headers, as sent by HAProxy and load balancers
+//! in front of the HTTP stream.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::{self, FromStr};
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LENGTH: usize = 16;
+
+/// Maximum number of bytes a PROXY header can take.
+pub const PROXY_HEADER_MAX_LENGTH: usize = V2_HEADER_LENGTH + 65535;
+
+/// TLV type of the ALPN protocol negotiated by the proxy
+pub const PP2_TYPE_ALPN: u8 = 0x01;
+/// TLV type of the host name sent by the client (TLS SNI)
+pub const PP2_TYPE_AUTHORITY: u8 = 0x02;
+/// TLV type of the CRC32c checksum of the header
+pub const PP2_TYPE_CRC32C: u8 = 0x03;
+/// TLV type of padding to be ignored
+pub const PP2_TYPE_NOOP: u8 = 0x04;
+/// TLV type of an opaque connection identifier
+pub const PP2_TYPE_UNIQUE_ID: u8 = 0x05;
+/// TLV type of the TLS information
+pub const PP2_TYPE_SSL: u8 = 0x20;
+/// TLV type of the network namespace
+pub const PP2_TYPE_NETNS: u8 = 0x30;
+
+/// `ProxyError` defines the errors encountered when parsing a PROXY header.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ProxyError {
+    /// The data starts with neither the v1 nor the v2 signature
+    InvalidSignature,
+    /// The v2 version nibble is not 2
+    InvalidVersion,
+    /// The v2 command is neither LOCAL nor PROXY
+    InvalidCommand,
+    /// The protocol or address family is not supported
+    InvalidFamily,
+    /// An address could not be parsed
+    InvalidAddress,
+    /// A port could not be parsed
+    InvalidPort,
+    /// The v1 line is longer than 107 bytes or malformed
+    InvalidLine,
+    /// The v2 length is too small for the address family
+    InvalidLength,
+    /// A v2 TLV runs past the end of the header
+    InvalidTlv,
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProxyError::InvalidSignature => write!(f, "missing PROXY protocol signature"),
+            ProxyError::InvalidVersion => write!(f, "unsupported PROXY protocol version"),
+            ProxyError::InvalidCommand => write!(f, "invalid PROXY protocol command"),
+            ProxyError::InvalidFamily => write!(f, "unsupported PROXY protocol address family"),
+            ProxyError::InvalidAddress => write!(f, "invalid PROXY protocol address"),
+            ProxyError::InvalidPort => write!(f, "invalid PROXY protocol port"),
+            ProxyError::InvalidLine => write!(f, "malformed PROXY protocol v1 line"),
+            ProxyError::InvalidLength => write!(f, "invalid PROXY protocol v2 length"),
+            ProxyError::InvalidTlv => write!(f, "invalid PROXY protocol v2 TLV"),
+        }
+    }
+}
+
+/// Whether the connection was relayed on behalf of a client.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ProxyCommand {
+    /// The proxy opened the connection itself, e.g. for health checks
+    Local,
+    /// The connection is relayed for the client in `source`
+    Proxy,
+}
+
+/// The transport protocol of the proxied connection.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ProxyTransport {
+    /// Unknown or unspecified
+    Unknown,
+    /// TCP or another stream protocol
+    Stream,
+    /// UDP or another datagram protocol
+    Datagram,
+}
+
+/// A parsed PROXY protocol header.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let data = b"PROXY TCP4 192.0.2.1 198.51.100.7 56324 443\r\nGET / HTTP/1.1\r\n\r\n";
+/// let (header, len) = ProxyHeader::parse(data).unwrap().unwrap();
+/// assert_eq!(header.source.unwrap().to_string(), "192.0.2.1:56324");
+/// assert_eq!(&data[len..], b"GET / HTTP/1.1\r\n\r\n");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ProxyHeader {
+    /// Protocol version, 1 or 2
+    pub version: u8,
+    /// The v2 command. v1 headers are always `Proxy`.
+    pub command: ProxyCommand,
+    /// Transport protocol
+    pub transport: ProxyTransport,
+    /// Address of the client. `None` for `UNKNOWN`, `LOCAL`, unspecified and unix addresses.
+    pub source: Option<SocketAddr>,
+    /// Address the client connected to
+    pub destination: Option<SocketAddr>,
+    /// v2 type-length-value extensions, in order
+    pub tlvs: Vec<(u8, Vec<u8>)>,
+}
+
+impl ProxyHeader {
+    /// Parses a PROXY header at the start of `data`. Returns `Ok(None)` if more data is
+    /// needed, or the header and its length in bytes.
+    pub fn parse(data: &[u8]) -> Result<Option<(ProxyHeader, usize)>, ProxyError> {
+        let n = data.len();
+        if n >= V2_SIGNATURE.len() && data.starts_with(V2_SIGNATURE) {
+            parse_v2(data)
+        } else if data.starts_with(V1_PREFIX) {
+            parse_v1(data)
+        } else if V2_SIGNATURE.starts_with(data) || V1_PREFIX.starts_with(data) {
+            Ok(None)
+        } else {
+            Err(ProxyError::InvalidSignature)
+        }
+    }
+
+    /// Returns the value of the first TLV of type `kind`.
+    pub fn tlv(&self, kind: u8) -> Option<&[u8]> {
+        self.tlvs.iter().find(|t| t.0 == kind).map(|t| &t.1[..])
+    }
+
+    /// The ALPN protocol negotiated by the proxy, such as `h2`.
+    pub fn alpn(&self) -> Option<&str> {
+        self.tlv(PP2_TYPE_ALPN).and_then(|v| str::from_utf8(v).ok())
+    }
+
+    /// The host name the client asked for, usually from TLS SNI.
+    pub fn authority(&self) -> Option<&str> {
+        self.tlv(PP2_TYPE_AUTHORITY).and_then(|v| str::from_utf8(v).ok())
+    }
+}
+
+fn parse_port(s: &str) -> Result<u16, ProxyError> {
+    if s.is_empty() || s.len() > 5 || !s.bytes().all(|b| b.is_ascii_digit()) ||
+        (s.len() > 1 && s.starts_with('0')) {
+        return Err(ProxyError::InvalidPort);
+    }
+    s.parse().map_err(|_| ProxyError::InvalidPort)
+}
+
+fn parse_v1(data: &[u8]) -> Result<Option<(ProxyHeader, usize)>, ProxyError> {
+    let end = match data.windows(2).position(|w| w == b"\r\n") {
+        Some(end) => end,
+        None if data.len() >= V1_MAX_LENGTH => return Err(ProxyError::InvalidLine),
+        None => return Ok(None),
+    };
+    if end + 2 > V1_MAX_LENGTH {
+        return Err(ProxyError::InvalidLine);
+    }
+
+    let line = str::from_utf8(&data[V1_PREFIX.len() .. end]).map_err(|_| ProxyError::InvalidLine)?;
+    let mut header = ProxyHeader {
+        version: 1,
+        command: ProxyCommand::Proxy,
+        transport: ProxyTransport::Unknown,
+        source: None,
+        destination: None,
+        tlvs: Vec::new(),
+    };
+
+    // "UNKNOWN" may be followed by anything, which must be ignored
+    if line == "UNKNOWN" || line.starts_with("UNKNOWN ") {
+        return Ok(Some((header, end + 2)));
+    }
+
+    let parts: Vec<&str> = line.split(' ').collect();
+    if parts.len() != 5 {
+        return Err(ProxyError::InvalidLine);
+    }
+    let (src, dst) = match parts[0] {
+        "TCP4" => (
+            IpAddr::V4(Ipv4Addr::from_str(parts[1]).map_err(|_| ProxyError::InvalidAddress)?),
+            IpAddr::V4(Ipv4Addr::from_str(parts[2]).map_err(|_| ProxyError::InvalidAddress)?),
+        ),
+        "TCP6" => (
+            IpAddr::V6(Ipv6Addr::from_str(parts[1]).map_err(|_| ProxyError::InvalidAddress)?),
+            IpAddr::V6(Ipv6Addr::from_str(parts[2]).map_err(|_| ProxyError::InvalidAddress)?),
+        ),
+        _ => return Err(ProxyError::InvalidFamily),
+    };
+
+    header.transport = ProxyTransport::Stream;
+    header.source = Some(SocketAddr::new(src, parse_port(parts[3])?));
+    header.destination = Some(SocketAddr::new(dst, parse_port(parts[4])?));
+    Ok(Some((header, end + 2)))
+}
+
+fn parse_v2(data: &[u8]) -> Result<Option<(ProxyHeader, usize)>, ProxyError> {
+    if data.len() < V2_HEADER_LENGTH {
+        return Ok(None);
+    }
+
+    let version = data[12] >> 4;
+    if version != 2 {
+        return Err(ProxyError::InvalidVersion);
+    }
+    let command = match data[12] & 0x0f {
+        0 => ProxyCommand::Local,
+        1 => ProxyCommand::Proxy,
+        _ => return Err(ProxyError::InvalidCommand),
+    };
+    let transport = match data[13] & 0x0f {
+        0 => ProxyTransport::Unknown,
+        1 => ProxyTransport::Stream,
+        2 => ProxyTransport::Datagram,
+        _ => return Err(ProxyError::InvalidFamily),
+    };
+    let address_length = match data[13] >> 4 {
+        0 => 0,
+        1 => 12,
+        2 => 36,
+        3 => 216,
+        _ => return Err(ProxyError::InvalidFamily),
+    };
+
+    let length = u16::from_be_bytes([data[14], data[15]]) as usize;
+    if length < address_length {
+        return Err(ProxyError::InvalidLength);
+    }
+    let total = V2_HEADER_LENGTH + length;
+    if data.len() < total {
+        return Ok(None);
+    }
+
+    let body = &data[V2_HEADER_LENGTH .. total];
+    let mut header = ProxyHeader {
+        version: 2,
+        command,
+        transport,
+        source: None,
+        destination: None,
+        tlvs: Vec::new(),
+    };
+
+    // Addresses of a LOCAL connection must be ignored, and unix paths aren't socket addresses
+    if command == ProxyCommand::Proxy {
+        match address_length {
+            12 => {
+                let src = Ipv4Addr::new(body[0], body[1], body[2], body[3]);
+                let dst = Ipv4Addr::new(body[4], body[5], body[6], body[7]);
+                header.source = Some(SocketAddr::new(IpAddr::V4(src), u16::from_be_bytes([body[8], body[9]])));
+                header.destination = Some(SocketAddr::new(IpAddr::V4(dst), u16::from_be_bytes([body[10], body[11]])));
+            },
+            36 => {
+                let mut src = [0u8; 16];
+                let mut dst = [0u8; 16];
+                src.copy_from_slice(&body[0..16]);
+                dst.copy_from_slice(&body[16..32]);
+                header.source = Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(src)),
+                                                     u16::from_be_bytes([body[32], body[33]])));
+                header.destination = Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(dst)),
+                                                          u16::from_be_bytes([body[34], body[35]])));
+            },
+            _ => (),
+        }
+    }
+
+    let mut tlvs = &body[address_length..];
+    while !tlvs.is_empty() {
+        if tlvs.len() < 3 {
+            return Err(ProxyError::InvalidTlv);
+        }
+        let len = u16::from_be_bytes([tlvs[1], tlvs[2]]) as usize;
+        if tlvs.len() < 3 + len {
+            return Err(ProxyError::InvalidTlv);
+        }
+        header.tlvs.push((tlvs[0], tlvs[3 .. 3 + len].to_vec()));
+        tlvs = &tlvs[3 + len..];
+    }
+
+    Ok(Some((header, total)))
+}
@@ -0,0 +1,439 @@
+//! Range requests (RFC 9110 section 14): `Range`, `Content-Range` and
+//! `multipart/byteranges` response bodies.
+
+use std::cmp;
+use std::fmt;
+use std::str;
+
+use callback::{CallbackResult, ParseAction};
+use media_type::MediaType;
+use multipart::{MultipartCallback, MultipartError, MultipartParser};
+
+/// `RangeError` defines the errors encountered with range requests and responses.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum RangeError {
+    /// The `Range` value is malformed
+    InvalidRange,
+    /// The range unit is not `bytes`
+    UnsupportedUnit,
+    /// No range overlaps the resource
+    Unsatisfiable,
+    /// The `Content-Range` value is malformed or does not fit the complete length
+    InvalidContentRange,
+    /// The body is not `multipart/byteranges` with a boundary
+    NotByteRanges,
+    /// A part of a `multipart/byteranges` body has no `Content-Range`
+    MissingContentRange,
+    /// Parts of a `multipart/byteranges` body disagree on the complete length
+    LengthMismatch,
+    /// The data of a part is not as long as its `Content-Range`
+    PartLengthMismatch,
+    /// The multipart body is malformed
+    Multipart(MultipartError),
+    /// A callback returned an error
+    CallbackFailed,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RangeError::InvalidRange => write!(f, "invalid Range"),
+            RangeError::UnsupportedUnit => write!(f, "unsupported range unit"),
+            RangeError::Unsatisfiable => write!(f, "range not satisfiable"),
+            RangeError::InvalidContentRange => write!(f, "invalid Content-Range"),
+            RangeError::NotByteRanges => write!(f, "body is not multipart/byteranges"),
+            RangeError::MissingContentRange => write!(f, "byteranges part without Content-Range"),
+            RangeError::LengthMismatch => write!(f, "byteranges parts disagree on the complete length"),
+            RangeError::PartLengthMismatch => write!(f, "byteranges part length does not match its Content-Range"),
+            RangeError::Multipart(err) => write!(f, "{}", err),
+            RangeError::CallbackFailed => write!(f, "a callback failed"),
+        }
+    }
+}
+
+fn parse_number(s: &str) -> Option<u64> {
+    if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// A range of a `Range` header.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ByteRange {
+    /// `first-last`, both inclusive
+    FromTo(u64, u64),
+    /// `first-`, up to the end
+    From(u64),
+    /// `-length`, the last `length` bytes
+    Last(u64),
+}
+
+impl ByteRange {
+    /// Returns the first and last (inclusive) offsets of this range in a resource of
+    /// `length` bytes, or `None` if it does not overlap the resource.
+    pub fn resolve(self, length: u64) -> Option<(u64, u64)> {
+        match self {
+            ByteRange::FromTo(first, last) if first < length => Some((first, cmp::min(last, length - 1))),
+            ByteRange::From(first) if first < length => Some((first, length - 1)),
+            ByteRange::Last(n) if n > 0 && length > 0 => Some((length - cmp::min(n, length), length - 1)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ByteRange::FromTo(first, last) => write!(f, "{}-{}", first, last),
+            ByteRange::From(first) => write!(f, "{}-", first),
+            ByteRange::Last(n) => write!(f, "-{}", n),
+        }
+    }
+}
+
+/// The value of a `Range` request header.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let range = Range::parse(b"bytes=0-99, 200-, -50").unwrap();
+/// assert_eq!(range.ranges, vec![ByteRange::FromTo(0, 99), ByteRange::From(200), ByteRange::Last(50)]);
+/// assert_eq!(range.resolve(1000), Ok(vec![(0, 99), (200, 999), (950, 999)]));
+/// assert_eq!(range.resolve(100), Ok(vec![(0, 99), (50, 99)]));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Range {
+    /// The requested byte ranges, in order
+    pub ranges: Vec<ByteRange>,
+}
+
+impl Range {
+    /// Parses a `Range` value. Units other than `bytes` are `UnsupportedUnit`, which a
+    /// server should answer with the whole resource.
+    pub fn parse(value: &[u8]) -> Result<Range, RangeError> {
+        let value = str::from_utf8(value).map_err(|_| RangeError::InvalidRange)?.trim();
+        let eq = value.find('=').ok_or(RangeError::InvalidRange)?;
+        if !value[..eq].eq_ignore_ascii_case("bytes") {
+            return Err(RangeError::UnsupportedUnit);
+        }
+
+        let mut ranges = Vec::new();
+        for spec in value[eq + 1..].split(',').map(|s| s.trim()) {
+            if spec.is_empty() {
+                // empty list elements are allowed
+                continue;
+            }
+            let dash = spec.find('-').ok_or(RangeError::InvalidRange)?;
+            let (first, last) = (&spec[..dash], &spec[dash + 1..]);
+            let range = if first.is_empty() {
+                ByteRange::Last(parse_number(last).ok_or(RangeError::InvalidRange)?)
+            } else if last.is_empty() {
+                ByteRange::From(parse_number(first).ok_or(RangeError::InvalidRange)?)
+            } else {
+                let first = parse_number(first).ok_or(RangeError::InvalidRange)?;
+                let last = parse_number(last).ok_or(RangeError::InvalidRange)?;
+                if last < first {
+                    return Err(RangeError::InvalidRange);
+                }
+                ByteRange::FromTo(first, last)
+            };
+            ranges.push(range);
+        }
+        if ranges.is_empty() {
+            return Err(RangeError::InvalidRange);
+        }
+        Ok(Range { ranges })
+    }
+
+    /// Returns the first and last (inclusive) offsets of the ranges that overlap a
+    /// resource of `length` bytes, in order, or `Unsatisfiable` if none does.
+    pub fn resolve(&self, length: u64) -> Result<Vec<(u64, u64)>, RangeError> {
+        let resolved: Vec<_> = self.ranges.iter().filter_map(|r| r.resolve(length)).collect();
+        if resolved.is_empty() {
+            return Err(RangeError::Unsatisfiable);
+        }
+        Ok(resolved)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bytes=")?;
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", range)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of a `Content-Range` header.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let range = ContentRange::parse(b"bytes 200-999/1000").unwrap();
+/// assert_eq!(range, ContentRange::Bytes { first: 200, last: 999, complete_length: Some(1000) });
+/// assert_eq!(range.length(), Some(800));
+/// assert_eq!(ContentRange::Unsatisfied(1000).to_string(), "bytes */1000");
+/// ```
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ContentRange {
+    /// `bytes first-last/complete-length` of a 206 response, where the complete length may
+    /// be unknown (`*`)
+    Bytes {
+        /// First offset of the range
+        first: u64,
+        /// Last offset of the range, inclusive
+        last: u64,
+        /// Length of the whole resource
+        complete_length: Option<u64>,
+    },
+    /// `bytes */complete-length` of a 416 response
+    Unsatisfied(u64),
+}
+
+impl ContentRange {
+    /// Parses a `Content-Range` value, checking that the range fits the complete length.
+    pub fn parse(value: &[u8]) -> Result<ContentRange, RangeError> {
+        let value = str::from_utf8(value).map_err(|_| RangeError::InvalidContentRange)?.trim();
+        if !value.get(..6).is_some_and(|unit| unit.eq_ignore_ascii_case("bytes ")) {
+            return Err(RangeError::InvalidContentRange);
+        }
+        let value = &value[6..];
+        let slash = value.find('/').ok_or(RangeError::InvalidContentRange)?;
+        let (range, length) = (&value[..slash], &value[slash + 1..]);
+
+        if range == "*" {
+            return parse_number(length).map(ContentRange::Unsatisfied).ok_or(RangeError::InvalidContentRange);
+        }
+        let complete_length = if length == "*" {
+            None
+        } else {
+            Some(parse_number(length).ok_or(RangeError::InvalidContentRange)?)
+        };
+        let dash = range.find('-').ok_or(RangeError::InvalidContentRange)?;
+        let first = parse_number(&range[..dash]).ok_or(RangeError::InvalidContentRange)?;
+        let last = parse_number(&range[dash + 1..]).ok_or(RangeError::InvalidContentRange)?;
+        // `last == u64::MAX` would overflow the range length
+        if last < first || last == u64::MAX || complete_length.is_some_and(|length| last >= length) {
+            return Err(RangeError::InvalidContentRange);
+        }
+        Ok(ContentRange::Bytes { first, last, complete_length })
+    }
+
+    /// Number of bytes in the range, `None` for `Unsatisfied`.
+    pub fn length(&self) -> Option<u64> {
+        match *self {
+            ContentRange::Bytes { first, last, .. } => Some(last - first + 1),
+            ContentRange::Unsatisfied(_) => None,
+        }
+    }
+
+    /// Length of the whole resource, if known.
+    pub fn complete_length(&self) -> Option<u64> {
+        match *self {
+            ContentRange::Bytes { complete_length, .. } => complete_length,
+            ContentRange::Unsatisfied(length) => Some(length),
+        }
+    }
+}
+
+impl fmt::Display for ContentRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ContentRange::Bytes { first, last, complete_length: Some(length) } =>
+                write!(f, "bytes {}-{}/{}", first, last, length),
+            ContentRange::Bytes { first, last, complete_length: None } =>
+                write!(f, "bytes {}-{}/*", first, last),
+            ContentRange::Unsatisfied(length) => write!(f, "bytes */{}", length),
+        }
+    }
+}
+
+/// It defines the callback functions that would be called by `ByteRangesParser`. They
+/// receive the nested `MultipartParser`.
+pub trait ByteRangesCallback {
+    /// Function called when a part begins, with its `Content-Range` and `Content-Type`.
+    #[allow(unused_variables)]
+    fn on_range(&mut self, parser: &mut MultipartParser, range: &ContentRange,
+                content_type: Option<&MediaType>) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with data of the current range. It may be called several times per
+    /// range.
+    #[allow(unused_variables)]
+    fn on_range_data(&mut self, parser: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called when the current range is complete.
+    #[allow(unused_variables)]
+    fn on_range_end(&mut self, parser: &mut MultipartParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+}
+
+// State of the part being parsed
+#[derive(Default)]
+struct RangePart {
+    range: Option<ContentRange>,
+    content_type: Option<MediaType>,
+    received: u64,
+    expected: u64,
+    complete_length: Option<Option<u64>>,
+}
+
+// Checks the parts of the nested parser and forwards them as ranges
+struct Forward<'a, T: 'a> {
+    cb: &'a mut T,
+    part: &'a mut RangePart,
+    error: Option<RangeError>,
+}
+
+impl<'a, T: ByteRangesCallback> Forward<'a, T> {
+    fn fail(&mut self, err: RangeError) -> CallbackResult {
+        self.error = Some(err);
+        Err(err.to_string())
+    }
+
+    fn forward(&mut self, result: CallbackResult) -> CallbackResult {
+        match result {
+            Ok(action) => Ok(action),
+            Err(_) => self.fail(RangeError::CallbackFailed),
+        }
+    }
+}
+
+impl<'a, T: ByteRangesCallback> MultipartCallback for Forward<'a, T> {
+    fn on_part_begin(&mut self, _: &mut MultipartParser) -> CallbackResult {
+        self.part.range = None;
+        self.part.content_type = None;
+        self.part.received = 0;
+        Ok(ParseAction::None)
+    }
+
+    fn on_part_header(&mut self, _: &mut MultipartParser, name: &[u8], value: &[u8]) -> CallbackResult {
+        if name.eq_ignore_ascii_case(b"Content-Range") {
+            match ContentRange::parse(value) {
+                Ok(range @ ContentRange::Bytes { .. }) => self.part.range = Some(range),
+                _ => return self.fail(RangeError::InvalidContentRange),
+            }
+        } else if name.eq_ignore_ascii_case(b"Content-Type") {
+            self.part.content_type = MediaType::parse(value).ok();
+        }
+        Ok(ParseAction::None)
+    }
+
+    fn on_part_headers_complete(&mut self, parser: &mut MultipartParser) -> CallbackResult {
+        let range = match self.part.range {
+            Some(range) => range,
+            None => return self.fail(RangeError::MissingContentRange),
+        };
+        match self.part.complete_length {
+            Some(length) if length != range.complete_length() => return self.fail(RangeError::LengthMismatch),
+            _ => self.part.complete_length = Some(range.complete_length()),
+        }
+        self.part.expected = range.length().unwrap_or(0);
+        let result = self.cb.on_range(parser, &range, self.part.content_type.as_ref());
+        self.forward(result)
+    }
+
+    fn on_part_data(&mut self, parser: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+        self.part.received += data.len() as u64;
+        if self.part.received > self.part.expected {
+            return self.fail(RangeError::PartLengthMismatch);
+        }
+        let result = self.cb.on_range_data(parser, data);
+        self.forward(result)
+    }
+
+    fn on_part_end(&mut self, parser: &mut MultipartParser) -> CallbackResult {
+        if self.part.received != self.part.expected {
+            return self.fail(RangeError::PartLengthMismatch);
+        }
+        let result = self.cb.on_range_end(parser);
+        self.forward(result)
+    }
+}
+
+/// A streaming parser of `multipart/byteranges` bodies of 206 responses.
+///
+/// Feed it the body as it arrives in `on_body`, and an empty slice at the end of the body.
+/// Every part must have a `Content-Range` whose length matches its data, and all parts
+/// must agree on the complete length.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// struct Callback {
+///     ranges: Vec<(ContentRange, Vec<u8>)>,
+/// }
+///
+/// impl ByteRangesCallback for Callback {
+///     fn on_range(&mut self, _: &mut MultipartParser, range: &ContentRange,
+///                 _: Option<&MediaType>) -> CallbackResult {
+///         self.ranges.push((*range, Vec::new()));
+///         Ok(ParseAction::None)
+///     }
+///
+///     fn on_range_data(&mut self, _: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+///         self.ranges.last_mut().unwrap().1.extend_from_slice(data);
+///         Ok(ParseAction::None)
+///     }
+/// }
+///
+/// let body = b"--sep\r\nContent-Range: bytes 0-3/20\r\n\r\nabcd\r\n\
+///              --sep\r\nContent-Range: bytes 18-19/20\r\n\r\nyz\r\n--sep--";
+/// let mut parser = ByteRangesParser::new("multipart/byteranges; boundary=sep").unwrap();
+/// let mut cb = Callback { ranges: Vec::new() };
+/// parser.execute(&mut cb, body);
+/// parser.execute(&mut cb, &[]);
+/// assert_eq!(parser.errno, None);
+/// assert_eq!(cb.ranges[1].1, b"yz".to_vec());
+/// ```
+pub struct ByteRangesParser {
+    /// Error if there was one in parsing
+    pub errno: Option<RangeError>,
+    /// The nested parser of the multipart body, whose limits can be set
+    pub multipart: MultipartParser,
+
+    part: RangePart,
+}
+
+impl ByteRangesParser {
+    /// Creates a parser for a response with the given `Content-Type`, which must be
+    /// `multipart/byteranges` with a boundary.
+    pub fn new(content_type: &str) -> Result<ByteRangesParser, RangeError> {
+        let media_type = MediaType::parse(content_type.as_bytes()).map_err(|_| RangeError::NotByteRanges)?;
+        if !media_type.is("multipart/byteranges") {
+            return Err(RangeError::NotByteRanges);
+        }
+        let boundary = media_type.boundary().ok_or(RangeError::NotByteRanges)?;
+        Ok(ByteRangesParser {
+            errno: None,
+            multipart: MultipartParser::new(boundary).map_err(RangeError::Multipart)?,
+            part: RangePart::default(),
+        })
+    }
+
+    /// Parses `data` and returns the number of bytes consumed. On error the returned count
+    /// is smaller than `data.len()` and `errno` is set. An empty `data` ends the body.
+    pub fn execute<T: ByteRangesCallback>(&mut self, cb: &mut T, data: &[u8]) -> usize {
+        if self.errno.is_some() {
+            return 0;
+        }
+
+        let mut forward = Forward { cb, part: &mut self.part, error: None };
+        let parsed = self.multipart.execute(&mut forward, data);
+        self.errno = forward.error.or_else(|| self.multipart.errno.map(RangeError::Multipart));
+        parsed
+    }
+}
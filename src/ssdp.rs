@@ -0,0 +1,136 @@
+//! SSDP (UPnP discovery) messages, which are HTTP messages sent one per UDP datagram (HTTPU).
+
+use std::fmt;
+
+use parser::{HttpParser, HttpParserType};
+use error::HttpErrno;
+use http_method::HttpMethod;
+use message::{Message, MessageCollector};
+
+/// `SsdpError` defines the errors encountered when parsing an SSDP datagram.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SsdpError {
+    /// The datagram is not a valid HTTP message
+    Http(HttpErrno),
+    /// The datagram ends before the end of the headers
+    Incomplete,
+    /// The datagram holds more than one message
+    TrailingData,
+    /// The method is neither M-SEARCH nor NOTIFY
+    InvalidMethod,
+}
+
+impl fmt::Display for SsdpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SsdpError::Http(errno) => write!(f, "{}", errno),
+            SsdpError::Incomplete => write!(f, "SSDP datagram ends in the middle of the message"),
+            SsdpError::TrailingData => write!(f, "data after the SSDP message"),
+            SsdpError::InvalidMethod => write!(f, "SSDP method is neither M-SEARCH nor NOTIFY"),
+        }
+    }
+}
+
+/// The kind of SSDP message.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SsdpKind {
+    /// `M-SEARCH` request looking for devices or services
+    Search,
+    /// `NOTIFY` advertisement, see `nts`
+    Notify,
+    /// Response to an `M-SEARCH`
+    Response,
+}
+
+/// An SSDP message with its discovery headers.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let data = b"NOTIFY * HTTP/1.1\r\n\
+///              HOST: 239.255.255.250:1900\r\n\
+///              CACHE-CONTROL: max-age=1800\r\n\
+///              LOCATION: http://192.168.1.2:49152/description.xml\r\n\
+///              NT: upnp:rootdevice\r\n\
+///              NTS: ssdp:alive\r\n\
+///              USN: uuid:2f402f80-da50-11e1-9b23-001788255acc::upnp:rootdevice\r\n\
+///              \r\n";
+///
+/// let ssdp = SsdpMessage::parse_datagram(data).unwrap();
+/// assert_eq!(ssdp.kind, SsdpKind::Notify);
+/// assert_eq!(ssdp.nts.as_ref().map(|s| &s[..]), Some("ssdp:alive"));
+/// assert_eq!(ssdp.max_age, Some(1800));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SsdpMessage {
+    /// Kind of message
+    pub kind: SsdpKind,
+    /// `ST`, the search target of a search or its response
+    pub st: Option<String>,
+    /// `USN`, the unique service name
+    pub usn: Option<String>,
+    /// `NT`, the notification type
+    pub nt: Option<String>,
+    /// `NTS`, the notification sub type such as `ssdp:alive` or `ssdp:byebye`
+    pub nts: Option<String>,
+    /// `LOCATION`, the URL of the device description
+    pub location: Option<String>,
+    /// `max-age` of `CACHE-CONTROL`, in seconds
+    pub max_age: Option<u32>,
+    /// The whole message
+    pub message: Message,
+}
+
+impl SsdpMessage {
+    /// Parses a datagram holding exactly one message. The end of the datagram ends the
+    /// message, so a body needs no `Content-Length`.
+    pub fn parse_datagram(data: &[u8]) -> Result<SsdpMessage, SsdpError> {
+        let mut parser = HttpParser::new(HttpParserType::Both);
+        parser.datagram = true;
+        let mut cb = MessageCollector::new();
+
+        let parsed = parser.execute(&mut cb, data);
+        if parser.errno.is_none() {
+            parser.execute(&mut cb, &[]);
+        }
+        match parser.errno {
+            Some(HttpErrno::InvalidEofState) => return Err(SsdpError::Incomplete),
+            Some(errno) if cb.messages.is_empty() => return Err(SsdpError::Http(errno)),
+            Some(_) => return Err(SsdpError::TrailingData),
+            None if parsed < data.len() || cb.messages.len() != 1 => return Err(SsdpError::TrailingData),
+            None => (),
+        }
+
+        let message = cb.messages.remove(0);
+        let kind = match message.method {
+            Some(HttpMethod::MSearch) => SsdpKind::Search,
+            Some(HttpMethod::Notify) => SsdpKind::Notify,
+            Some(_) => return Err(SsdpError::InvalidMethod),
+            None => SsdpKind::Response,
+        };
+        let header = |name| message.headers.get(name).map(|v| v.trim().to_string());
+        let max_age = message.headers.get_all("Cache-Control").iter()
+            .flat_map(|v| v.split(','))
+            .filter_map(|d| {
+                let mut parts = d.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(name), Some(value)) if name.trim().eq_ignore_ascii_case("max-age") =>
+                        value.trim().parse().ok(),
+                    _ => None,
+                }
+            })
+            .next();
+
+        Ok(SsdpMessage {
+            kind,
+            st: header("ST"),
+            usn: header("USN"),
+            nt: header("NT"),
+            nts: header("NTS"),
+            location: header("LOCATION"),
+            max_age,
+            message,
+        })
+    }
+}
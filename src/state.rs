@@ -5,10 +5,7 @@ pub enum State {
     StartReqOrRes,
     ResOrRespH,
     StartRes,
-    ResH,
-    ResHT,
-    ResHTT,
-    ResHTTP,
+    ResProtocol,
     ResFirstHttpMajor,
     ResHttpMajor,
     ResFirstHttpMinor,
@@ -22,6 +19,7 @@ pub enum State {
     StartReq,
 
     ReqMethod,
+    ReqMethodToken,
     ReqSpacesBeforeUrl,
     ReqSchema,
     ReqSchemaSlash,
@@ -34,11 +32,14 @@ pub enum State {
     ReqQueryString,
     ReqFragmentStart,
     ReqFragment,
+    ReqConnectHostStart,
+    ReqConnectHost,
+    ReqConnectHostV6,
+    ReqConnectHostV6End,
+    ReqConnectPortStart,
+    ReqConnectPort,
     ReqHttpStart,
-    ReqHttpH,
-    ReqHttpHT,
-    ReqHttpHTT,
-    ReqHttpHTTP,
+    ReqProtocol,
     ReqFirstHttpMajor,
     ReqHttpMajor,
     ReqFirstHttpMinor,
@@ -64,6 +65,8 @@ pub enum State {
     HeadersAlmostDone,
     HeadersDone,
 
+    Http2Preface,
+
     ChunkData,
     ChunkDataAlmostDone,
     ChunkDataDone,
@@ -71,7 +74,9 @@ pub enum State {
     BodyIdentity,
     BodyIdentityEof,
 
-    MessageDone
+    MessageDone,
+
+    Tunnel
 }
 
 impl State {
@@ -91,17 +96,29 @@ pub enum HeaderState {
     MatchingContentLength,
     MatchingTransferEncoding,
     MatchingUpgrade,
+    MatchingExpect,
 
     Connection,
     ContentLength,
     TransferEncoding,
     Upgrade,
+    Expect,
 
     MatchingTransferEncodingChunked,
     MatchingConnectionKeepAlive,
     MatchingConnectionClose,
+    MatchingExpectContinue,
 
     TransferEncodingChunked,
+    // inside a Transfer-Encoding coding other than chunked
+    TransferEncodingToken,
+    // after a comma of the Transfer-Encoding list
+    TransferEncodingDelimiter,
     ConnectionKeepAlive,
     ConnectionClose,
+    ExpectContinue,
+    // inside an expectation other than 100-continue
+    ExpectToken,
+    // after a comma of the Expect list
+    ExpectDelimiter,
 }
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::str::FromStr;
+
+use error::HttpErrno;
+
+/// A three digit HTTP status code.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let status = StatusCode::new(404).unwrap();
+/// assert_eq!(status, StatusCode::NOT_FOUND);
+/// assert!(status.is_client_error());
+/// assert_eq!(status.reason_phrase(), Some("Not Found"));
+/// assert_eq!(status.to_string(), "404 Not Found");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct StatusCode(u16);
+
+macro_rules! status_codes(
+    ($(($code:expr, $name:ident, $phrase:expr);)+) => (
+        impl StatusCode {
+            $(
+                #[doc = concat!("`", stringify!($code), " ", $phrase, "`")]
+                pub const $name: StatusCode = StatusCode($code);
+            )+
+        }
+
+        fn canonical_reason(code: u16) -> Option<&'static str> {
+            match code {
+                $($code => Some($phrase),)+
+                _ => None,
+            }
+        }
+    );
+);
+
+// The IANA HTTP Status Code Registry
+status_codes! {
+    (100, CONTINUE, "Continue");
+    (101, SWITCHING_PROTOCOLS, "Switching Protocols");
+    (102, PROCESSING, "Processing");
+    (103, EARLY_HINTS, "Early Hints");
+    (200, OK, "OK");
+    (201, CREATED, "Created");
+    (202, ACCEPTED, "Accepted");
+    (203, NON_AUTHORITATIVE_INFORMATION, "Non-Authoritative Information");
+    (204, NO_CONTENT, "No Content");
+    (205, RESET_CONTENT, "Reset Content");
+    (206, PARTIAL_CONTENT, "Partial Content");
+    (207, MULTI_STATUS, "Multi-Status");
+    (208, ALREADY_REPORTED, "Already Reported");
+    (226, IM_USED, "IM Used");
+    (300, MULTIPLE_CHOICES, "Multiple Choices");
+    (301, MOVED_PERMANENTLY, "Moved Permanently");
+    (302, FOUND, "Found");
+    (303, SEE_OTHER, "See Other");
+    (304, NOT_MODIFIED, "Not Modified");
+    (305, USE_PROXY, "Use Proxy");
+    (307, TEMPORARY_REDIRECT, "Temporary Redirect");
+    (308, PERMANENT_REDIRECT, "Permanent Redirect");
+    (400, BAD_REQUEST, "Bad Request");
+    (401, UNAUTHORIZED, "Unauthorized");
+    (402, PAYMENT_REQUIRED, "Payment Required");
+    (403, FORBIDDEN, "Forbidden");
+    (404, NOT_FOUND, "Not Found");
+    (405, METHOD_NOT_ALLOWED, "Method Not Allowed");
+    (406, NOT_ACCEPTABLE, "Not Acceptable");
+    (407, PROXY_AUTHENTICATION_REQUIRED, "Proxy Authentication Required");
+    (408, REQUEST_TIMEOUT, "Request Timeout");
+    (409, CONFLICT, "Conflict");
+    (410, GONE, "Gone");
+    (411, LENGTH_REQUIRED, "Length Required");
+    (412, PRECONDITION_FAILED, "Precondition Failed");
+    (413, CONTENT_TOO_LARGE, "Content Too Large");
+    (414, URI_TOO_LONG, "URI Too Long");
+    (415, UNSUPPORTED_MEDIA_TYPE, "Unsupported Media Type");
+    (416, RANGE_NOT_SATISFIABLE, "Range Not Satisfiable");
+    (417, EXPECTATION_FAILED, "Expectation Failed");
+    (421, MISDIRECTED_REQUEST, "Misdirected Request");
+    (422, UNPROCESSABLE_CONTENT, "Unprocessable Content");
+    (423, LOCKED, "Locked");
+    (424, FAILED_DEPENDENCY, "Failed Dependency");
+    (425, TOO_EARLY, "Too Early");
+    (426, UPGRADE_REQUIRED, "Upgrade Required");
+    (428, PRECONDITION_REQUIRED, "Precondition Required");
+    (429, TOO_MANY_REQUESTS, "Too Many Requests");
+    (431, REQUEST_HEADER_FIELDS_TOO_LARGE, "Request Header Fields Too Large");
+    (451, UNAVAILABLE_FOR_LEGAL_REASONS, "Unavailable For Legal Reasons");
+    (500, INTERNAL_SERVER_ERROR, "Internal Server Error");
+    (501, NOT_IMPLEMENTED, "Not Implemented");
+    (502, BAD_GATEWAY, "Bad Gateway");
+    (503, SERVICE_UNAVAILABLE, "Service Unavailable");
+    (504, GATEWAY_TIMEOUT, "Gateway Timeout");
+    (505, HTTP_VERSION_NOT_SUPPORTED, "HTTP Version Not Supported");
+    (506, VARIANT_ALSO_NEGOTIATES, "Variant Also Negotiates");
+    (507, INSUFFICIENT_STORAGE, "Insufficient Storage");
+    (508, LOOP_DETECTED, "Loop Detected");
+    (510, NOT_EXTENDED, "Not Extended");
+    (511, NETWORK_AUTHENTICATION_REQUIRED, "Network Authentication Required");
+}
+
+impl StatusCode {
+    /// Creates a status code, which must have three digits.
+    pub fn new(code: u16) -> Option<StatusCode> {
+        if (100..1000).contains(&code) {
+            Some(StatusCode(code))
+        } else {
+            None
+        }
+    }
+
+    /// The numeric code.
+    pub fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    /// The reason phrase the registry gives for the code, such as `Not Found` for 404.
+    pub fn reason_phrase(self) -> Option<&'static str> {
+        canonical_reason(self.0)
+    }
+
+    /// 1xx, the request was received and is being processed
+    pub fn is_informational(self) -> bool {
+        self.0 / 100 == 1
+    }
+
+    /// 2xx, the request was accepted
+    pub fn is_success(self) -> bool {
+        self.0 / 100 == 2
+    }
+
+    /// 3xx, further action is needed to complete the request
+    pub fn is_redirection(self) -> bool {
+        self.0 / 100 == 3
+    }
+
+    /// 4xx, the request is wrong
+    pub fn is_client_error(self) -> bool {
+        self.0 / 100 == 4
+    }
+
+    /// 5xx, the server failed to fulfill a valid request
+    pub fn is_server_error(self) -> bool {
+        self.0 / 100 == 5
+    }
+
+    /// Returns false if a response with this status never has a body, whatever its
+    /// headers say: 1xx, `204 No Content` and `304 Not Modified` (RFC 9112 section 6.3).
+    pub fn allows_body(self) -> bool {
+        !self.is_informational() && self != StatusCode::NO_CONTENT && self != StatusCode::NOT_MODIFIED
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(status: StatusCode) -> u16 {
+        status.0
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.reason_phrase() {
+            Some(phrase) => write!(f, "{} {}", self.0, phrase),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+impl FromStr for StatusCode {
+    type Err = HttpErrno;
+
+    fn from_str(s: &str) -> Result<StatusCode, HttpErrno> {
+        if s.len() != 3 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(HttpErrno::InvalidStatus);
+        }
+        s.parse().ok().and_then(StatusCode::new).ok_or(HttpErrno::InvalidStatus)
+    }
+}
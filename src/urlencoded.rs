@@ -0,0 +1,246 @@
+//! Query strings and `application/x-www-form-urlencoded` bodies.
+
+use std::fmt;
+use std::mem;
+
+const DEFAULT_MAX_PAIRS: usize = 1000;
+const DEFAULT_MAX_KEY_LENGTH: usize = 1024;
+const DEFAULT_MAX_VALUE_LENGTH: usize = 64 * 1024;
+
+/// `FormError` defines the errors encountered when decoding a form body.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum FormError {
+    /// The body has more than `max_pairs` pairs
+    TooManyPairs,
+    /// A key is longer than `max_key_length`
+    KeyTooLong,
+    /// A value is longer than `max_value_length`
+    ValueTooLong,
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormError::TooManyPairs => write!(f, "too many form pairs"),
+            FormError::KeyTooLong => write!(f, "form key too long"),
+            FormError::ValueTooLong => write!(f, "form value too long"),
+        }
+    }
+}
+
+fn hex_value(ch: u8) -> Option<u8> {
+    match ch {
+        b'0' ..= b'9' => Some(ch - b'0'),
+        b'a' ..= b'f' => Some(ch - b'a' + 10),
+        b'A' ..= b'F' => Some(ch - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn decode(data: &[u8], plus_as_space: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let ch = data[i];
+        if ch == b'%' && i + 2 < data.len() {
+            if let (Some(hi), Some(lo)) = (hex_value(data[i + 1]), hex_value(data[i + 2])) {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(if ch == b'+' && plus_as_space { b' ' } else { ch });
+        i += 1;
+    }
+    out
+}
+
+/// Decodes `%XX` escapes. A `%` that does not start a valid escape is kept as it is.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// assert_eq!(percent_decode(b"caf%C3%A9+100%"), "café+100%".as_bytes().to_vec());
+/// ```
+pub fn percent_decode(data: &[u8]) -> Vec<u8> {
+    decode(data, false)
+}
+
+fn decode_pair(key: &[u8], value: &[u8]) -> (String, String) {
+    (String::from_utf8_lossy(&decode(key, true)).into_owned(),
+     String::from_utf8_lossy(&decode(value, true)).into_owned())
+}
+
+fn split_pair(pair: &[u8]) -> (&[u8], &[u8]) {
+    match pair.iter().position(|&c| c == b'=') {
+        Some(eq) => (&pair[..eq], &pair[eq + 1..]),
+        None => (pair, &[]),
+    }
+}
+
+/// An iterator over the decoded `(key, value)` pairs of a query string or form body.
+///
+/// Pairs are separated by `&`, `+` decodes to a space and invalid UTF-8 is replaced with
+/// U+FFFD. Empty pairs are skipped and a pair without `=` has an empty value.
+pub struct QueryPairs<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> QueryPairs<'a> {
+    /// Iterates over the pairs of `query`, the part of a URL after `?` or a form body.
+    pub fn new(query: &'a [u8]) -> QueryPairs<'a> {
+        QueryPairs { data: query }
+    }
+}
+
+impl<'a> Iterator for QueryPairs<'a> {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<(String, String)> {
+        while !self.data.is_empty() {
+            let end = self.data.iter().position(|&c| c == b'&').unwrap_or(self.data.len());
+            let pair = &self.data[..end];
+            self.data = if end < self.data.len() { &self.data[end + 1..] } else { &[] };
+            if !pair.is_empty() {
+                let (key, value) = split_pair(pair);
+                return Some(decode_pair(key, value));
+            }
+        }
+        None
+    }
+}
+
+/// Iterates over the query pairs of a request target such as `Message::url`: the part
+/// after the first `?`, up to any `#`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let pairs: Vec<_> = query_pairs(b"/search?q=rust+http&lang=en#top").collect();
+/// assert_eq!(pairs, vec![("q".to_string(), "rust http".to_string()),
+///                        ("lang".to_string(), "en".to_string())]);
+/// ```
+pub fn query_pairs(url: &[u8]) -> QueryPairs<'_> {
+    let url = match url.iter().position(|&c| c == b'#') {
+        Some(hash) => &url[..hash],
+        None => url,
+    };
+    match url.iter().position(|&c| c == b'?') {
+        Some(question) => QueryPairs::new(&url[question + 1..]),
+        None => QueryPairs::new(&[]),
+    }
+}
+
+/// A streaming decoder of `application/x-www-form-urlencoded` bodies.
+///
+/// Feed it the body as it arrives in `on_body`, split at any point, and an empty slice at
+/// the end of the body. The limits apply to the encoded length of keys and values.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let mut decoder = FormDecoder::new();
+/// let mut pairs = Vec::new();
+/// decoder.execute(b"name=J%C3%", &mut pairs);
+/// decoder.execute(b"B6rg&city=Ume%C3%A5", &mut pairs);
+/// decoder.execute(&[], &mut pairs);
+/// assert_eq!(pairs, vec![("name".to_string(), "Jörg".to_string()),
+///                        ("city".to_string(), "Umeå".to_string())]);
+/// ```
+pub struct FormDecoder {
+    /// Error if there was one in decoding
+    pub errno: Option<FormError>,
+    /// Maximum number of pairs, 1000 by default
+    pub max_pairs: usize,
+    /// Maximum encoded length of a key, 1024 by default
+    pub max_key_length: usize,
+    /// Maximum encoded length of a value, 64 KiB by default
+    pub max_value_length: usize,
+
+    key: Vec<u8>,
+    value: Vec<u8>,
+    in_value: bool,
+    pairs: usize,
+}
+
+impl Default for FormDecoder {
+    fn default() -> FormDecoder {
+        FormDecoder::new()
+    }
+}
+
+impl FormDecoder {
+    /// Creates a decoder with the default limits.
+    pub fn new() -> FormDecoder {
+        FormDecoder {
+            errno: None,
+            max_pairs: DEFAULT_MAX_PAIRS,
+            max_key_length: DEFAULT_MAX_KEY_LENGTH,
+            max_value_length: DEFAULT_MAX_VALUE_LENGTH,
+            key: Vec::new(),
+            value: Vec::new(),
+            in_value: false,
+            pairs: 0,
+        }
+    }
+
+    /// Decodes `data`, appending the completed pairs to `pairs`, and returns the number of
+    /// bytes consumed. On error the returned count is smaller than `data.len()` and
+    /// `errno` is set. An empty `data` ends the body and completes the last pair.
+    pub fn execute(&mut self, data: &[u8], pairs: &mut Vec<(String, String)>) -> usize {
+        if self.errno.is_some() {
+            return 0;
+        }
+
+        if data.is_empty() {
+            self.end_pair(pairs);
+            self.pairs = 0;
+            return 0;
+        }
+
+        for (index, &ch) in data.iter().enumerate() {
+            if ch != b'&' && self.key.is_empty() && !self.in_value {
+                // first byte of a pair
+                self.pairs += 1;
+                if self.pairs > self.max_pairs {
+                    self.errno = Some(FormError::TooManyPairs);
+                    return index;
+                }
+            }
+            let result = match ch {
+                b'&' => {
+                    self.end_pair(pairs);
+                    Ok(())
+                },
+                b'=' if !self.in_value => {
+                    self.in_value = true;
+                    Ok(())
+                },
+                _ if self.in_value => {
+                    self.value.push(ch);
+                    if self.value.len() > self.max_value_length { Err(FormError::ValueTooLong) } else { Ok(()) }
+                },
+                _ => {
+                    self.key.push(ch);
+                    if self.key.len() > self.max_key_length { Err(FormError::KeyTooLong) } else { Ok(()) }
+                },
+            };
+            if let Err(err) = result {
+                self.errno = Some(err);
+                return index;
+            }
+        }
+        data.len()
+    }
+
+    fn end_pair(&mut self, pairs: &mut Vec<(String, String)>) {
+        let key = mem::take(&mut self.key);
+        let value = mem::take(&mut self.value);
+        if mem::replace(&mut self.in_value, false) || !key.is_empty() {
+            pairs.push(decode_pair(&key, &value));
+        }
+    }
+}
@@ -0,0 +1,681 @@
+//! WebSocket (RFC 6455) handshake validation and frame parser.
+
+use std::cmp;
+use std::fmt;
+use std::mem;
+use std::str;
+
+use base64;
+use sha1::sha1;
+use http_method::HttpMethod;
+use message::Message;
+use callback::{CallbackResult, ParseAction};
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const WEBSOCKET_VERSION: &str = "13";
+const MAX_CONTROL_PAYLOAD: u64 = 125;
+
+/// `WebSocketError` defines the errors encountered in the handshake or in the frame stream.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum WebSocketError {
+    // Handshake errors
+    /// The opening handshake is not a GET request
+    InvalidMethod,
+    /// The opening handshake is not HTTP/1.1 or later
+    InvalidHttpVersion,
+    /// `Upgrade: websocket` is missing
+    MissingUpgrade,
+    /// `Connection: Upgrade` is missing
+    MissingConnectionUpgrade,
+    /// `Sec-WebSocket-Version` is missing or not 13
+    UnsupportedVersion,
+    /// `Sec-WebSocket-Key` is missing or not a base64-encoded 16-byte nonce
+    InvalidKey,
+    /// The response status is not `101 Switching Protocols`
+    InvalidStatus,
+    /// `Sec-WebSocket-Accept` is missing or does not match the key
+    InvalidAccept,
+    /// The server selected a subprotocol the client did not offer
+    InvalidProtocol,
+
+    // Frame errors
+    /// Reserved bits are set without a negotiated extension
+    ReservedBits,
+    /// The opcode is reserved
+    ReservedOpcode,
+    /// A control frame is fragmented
+    FragmentedControlFrame,
+    /// A control frame has more than 125 bytes of payload
+    ControlFrameTooLarge,
+    /// A continuation frame arrived without a message in progress
+    UnexpectedContinuation,
+    /// A new data frame arrived while a fragmented message is in progress
+    ExpectedContinuation,
+    /// A client frame is not masked
+    MaskRequired,
+    /// A server frame is masked
+    UnexpectedMask,
+    /// The payload length is not minimally encoded or too large
+    InvalidLength,
+    /// The close frame has an invalid status code or payload
+    InvalidCloseCode,
+    /// A text message or close reason is not valid UTF-8
+    InvalidUtf8,
+    /// A frame arrived after the close frame
+    DataAfterClose,
+    /// A callback returned an error
+    CallbackFailed,
+}
+
+impl fmt::Display for WebSocketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WebSocketError::InvalidMethod => write!(f, "websocket handshake must be a GET request"),
+            WebSocketError::InvalidHttpVersion => write!(f, "websocket handshake requires HTTP/1.1"),
+            WebSocketError::MissingUpgrade => write!(f, "missing 'Upgrade: websocket' header"),
+            WebSocketError::MissingConnectionUpgrade => write!(f, "missing 'Connection: Upgrade' header"),
+            WebSocketError::UnsupportedVersion => write!(f, "unsupported websocket version"),
+            WebSocketError::InvalidKey => write!(f, "invalid Sec-WebSocket-Key"),
+            WebSocketError::InvalidStatus => write!(f, "websocket handshake response is not 101"),
+            WebSocketError::InvalidAccept => write!(f, "invalid Sec-WebSocket-Accept"),
+            WebSocketError::InvalidProtocol => write!(f, "server selected a subprotocol that was not offered"),
+            WebSocketError::ReservedBits => write!(f, "reserved bits set in frame header"),
+            WebSocketError::ReservedOpcode => write!(f, "reserved opcode"),
+            WebSocketError::FragmentedControlFrame => write!(f, "fragmented control frame"),
+            WebSocketError::ControlFrameTooLarge => write!(f, "control frame payload too large"),
+            WebSocketError::UnexpectedContinuation => write!(f, "continuation frame without a message"),
+            WebSocketError::ExpectedContinuation => write!(f, "expected a continuation frame"),
+            WebSocketError::MaskRequired => write!(f, "client frames must be masked"),
+            WebSocketError::UnexpectedMask => write!(f, "server frames must not be masked"),
+            WebSocketError::InvalidLength => write!(f, "invalid payload length"),
+            WebSocketError::InvalidCloseCode => write!(f, "invalid close frame"),
+            WebSocketError::InvalidUtf8 => write!(f, "invalid UTF-8 in text payload"),
+            WebSocketError::DataAfterClose => write!(f, "data received after close frame"),
+            WebSocketError::CallbackFailed => write!(f, "a callback failed"),
+        }
+    }
+}
+
+/// Computes the `Sec-WebSocket-Accept` value for a `Sec-WebSocket-Key`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// assert_eq!(websocket_accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+/// ```
+pub fn websocket_accept_key(key: &str) -> String {
+    let mut input = key.trim().as_bytes().to_vec();
+    input.extend_from_slice(WEBSOCKET_GUID.as_bytes());
+    base64::encode(&sha1(&input))
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    match value {
+        Some(v) => v.split(',')
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_string())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// A validated WebSocket opening handshake request.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// let mut parser = HttpParser::new(HttpParserType::Request);
+/// let mut cb = MessageCollector::new();
+/// parser.execute(&mut cb, b"GET /chat HTTP/1.1\r\n\
+///                           Host: server.example.com\r\n\
+///                           Upgrade: websocket\r\n\
+///                           Connection: Upgrade\r\n\
+///                           Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+///                           Sec-WebSocket-Version: 13\r\n\r\n");
+///
+/// let handshake = WebSocketHandshake::from_request(&cb.messages[0]).unwrap();
+/// assert_eq!(handshake.accept_key(), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct WebSocketHandshake {
+    /// The `Sec-WebSocket-Key` nonce
+    pub key: String,
+    /// Subprotocols offered in `Sec-WebSocket-Protocol`
+    pub protocols: Vec<String>,
+    /// Extensions offered in `Sec-WebSocket-Extensions`
+    pub extensions: Vec<String>,
+}
+
+impl WebSocketHandshake {
+    /// Validates an opening handshake request.
+    pub fn from_request(request: &Message) -> Result<WebSocketHandshake, WebSocketError> {
+        if request.method != Some(HttpMethod::Get) {
+            return Err(WebSocketError::InvalidMethod);
+        }
+        let version = request.http_version;
+        if version.major < 1 || (version.major == 1 && version.minor < 1) {
+            return Err(WebSocketError::InvalidHttpVersion);
+        }
+        if !request.headers.has_token("Upgrade", "websocket") {
+            return Err(WebSocketError::MissingUpgrade);
+        }
+        if !request.headers.has_token("Connection", "upgrade") {
+            return Err(WebSocketError::MissingConnectionUpgrade);
+        }
+        if request.headers.get("Sec-WebSocket-Version").map(|v| v.trim()) != Some(WEBSOCKET_VERSION) {
+            return Err(WebSocketError::UnsupportedVersion);
+        }
+
+        let key = request.headers.get("Sec-WebSocket-Key").ok_or(WebSocketError::InvalidKey)?.trim();
+        match base64::decode(key.as_bytes()) {
+            Some(ref nonce) if nonce.len() == 16 => (),
+            _ => return Err(WebSocketError::InvalidKey),
+        }
+
+        Ok(WebSocketHandshake {
+            key: key.to_string(),
+            protocols: split_list(request.headers.get("Sec-WebSocket-Protocol")),
+            extensions: split_list(request.headers.get("Sec-WebSocket-Extensions")),
+        })
+    }
+
+    /// The `Sec-WebSocket-Accept` value the server must answer with.
+    pub fn accept_key(&self) -> String {
+        websocket_accept_key(&self.key)
+    }
+
+    /// Validates the server's response to this handshake.
+    pub fn validate_response(&self, response: &Message) -> Result<(), WebSocketError> {
+        if response.status_code != Some(101) {
+            return Err(WebSocketError::InvalidStatus);
+        }
+        if !response.headers.has_token("Upgrade", "websocket") {
+            return Err(WebSocketError::MissingUpgrade);
+        }
+        if !response.headers.has_token("Connection", "upgrade") {
+            return Err(WebSocketError::MissingConnectionUpgrade);
+        }
+        match response.headers.get("Sec-WebSocket-Accept") {
+            Some(accept) if accept.trim() == self.accept_key() => (),
+            _ => return Err(WebSocketError::InvalidAccept),
+        }
+        if let Some(protocol) = response.headers.get("Sec-WebSocket-Protocol") {
+            if !self.protocols.iter().any(|p| p == protocol.trim()) {
+                return Err(WebSocketError::InvalidProtocol);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// WebSocket frame opcodes.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum WebSocketOpcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl WebSocketOpcode {
+    fn from_u8(op: u8) -> Option<WebSocketOpcode> {
+        match op {
+            0x0 => Some(WebSocketOpcode::Continuation),
+            0x1 => Some(WebSocketOpcode::Text),
+            0x2 => Some(WebSocketOpcode::Binary),
+            0x8 => Some(WebSocketOpcode::Close),
+            0x9 => Some(WebSocketOpcode::Ping),
+            0xA => Some(WebSocketOpcode::Pong),
+            _ => None,
+        }
+    }
+
+    /// Returns true for close, ping and pong.
+    pub fn is_control(self) -> bool {
+        matches!(self, WebSocketOpcode::Close | WebSocketOpcode::Ping | WebSocketOpcode::Pong)
+    }
+}
+
+/// A parsed frame header.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct WebSocketFrameHeader {
+    /// Whether this is the final fragment of a message
+    pub fin: bool,
+    /// The RSV1-3 bits, in the low three bits
+    pub rsv: u8,
+    /// The frame opcode
+    pub opcode: WebSocketOpcode,
+    /// The masking key, if the frame is masked
+    pub mask: Option<[u8; 4]>,
+    /// Payload length in bytes
+    pub payload_len: u64,
+}
+
+/// Which side of the connection the frame parser is on.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum WebSocketRole {
+    /// Parse frames sent by a client, which must be masked
+    Server,
+    /// Parse frames sent by a server, which must not be masked
+    Client,
+}
+
+/// It defines the callback functions that would be called by `WebSocketParser`.
+pub trait WebSocketCallback {
+    /// Function called when a frame header is parsed.
+    #[allow(unused_variables)]
+    fn on_frame_header(&mut self, parser: &mut WebSocketParser,
+                       header: &WebSocketFrameHeader) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with unmasked payload of a text, binary or continuation frame.
+    /// It may be called several times per frame.
+    #[allow(unused_variables)]
+    fn on_frame_payload(&mut self, parser: &mut WebSocketParser, data: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called when a frame, data or control, is complete.
+    #[allow(unused_variables)]
+    fn on_frame_complete(&mut self, parser: &mut WebSocketParser) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called when the final fragment of a text or binary message is complete.
+    #[allow(unused_variables)]
+    fn on_message_complete(&mut self, parser: &mut WebSocketParser,
+                           opcode: WebSocketOpcode) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with the payload of a ping frame.
+    #[allow(unused_variables)]
+    fn on_ping(&mut self, parser: &mut WebSocketParser, data: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with the payload of a pong frame.
+    #[allow(unused_variables)]
+    fn on_pong(&mut self, parser: &mut WebSocketParser, data: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+
+    /// Function called with the status code and reason of a close frame.
+    #[allow(unused_variables)]
+    fn on_close(&mut self, parser: &mut WebSocketParser, code: Option<u16>,
+                reason: &[u8]) -> CallbackResult {
+        Ok(ParseAction::None)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum FrameState {
+    Header,
+    Length,
+    ExtendedLength,
+    MaskKey,
+    Payload,
+}
+
+/// An incremental WebSocket frame parser.
+///
+/// It continues where `HttpParser` stops after an upgrade: feed it the bytes after the
+/// offset returned by `HttpParser::execute`.
+///
+/// # Example
+///
+/// ```
+/// # use http_parser::*;
+/// struct Callback {
+///     text: Vec<u8>,
+/// }
+///
+/// impl WebSocketCallback for Callback {
+///     fn on_frame_payload(&mut self, _: &mut WebSocketParser, data: &[u8]) -> CallbackResult {
+///         self.text.extend_from_slice(data);
+///         Ok(ParseAction::None)
+///     }
+/// }
+///
+/// let mut parser = WebSocketParser::new(WebSocketRole::Server);
+/// let mut cb = Callback { text: Vec::new() };
+/// // a masked "Hello" text frame from RFC 6455
+/// let frame = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+/// assert_eq!(parser.execute(&mut cb, &frame), frame.len());
+/// assert_eq!(cb.text, b"Hello".to_vec());
+/// ```
+pub struct WebSocketParser {
+    /// Error if there was one in parsing
+    pub errno: Option<WebSocketError>,
+    /// RSV bits (in the low three bits) allowed by negotiated extensions
+    pub allowed_rsv: u8,
+
+    role: WebSocketRole,
+    state: FrameState,
+    fin: bool,
+    rsv: u8,
+    opcode: WebSocketOpcode,
+    masked: bool,
+    mask: [u8; 4],
+    index: usize,
+    ext_len_size: usize,
+    payload_len: u64,
+    payload_read: u64,
+    message: Option<WebSocketOpcode>,
+    utf8_pending: [u8; 4],
+    utf8_pending_len: usize,
+    closed: bool,
+    control: Vec<u8>,
+    scratch: Vec<u8>,
+}
+
+macro_rules! ws_callback(
+    ($parser:ident, $cb:expr, $idx:expr) => (
+        if $cb.is_err() {
+            $parser.errno = Some(WebSocketError::CallbackFailed);
+            return $idx;
+        }
+    );
+);
+
+macro_rules! ws_error(
+    ($parser:ident, $err:expr, $idx:expr) => ({
+        $parser.errno = Some($err);
+        return $idx;
+    });
+);
+
+fn is_valid_close_code(code: u16) -> bool {
+    matches!(code, 1000 ..= 1003 | 1007 ..= 1014 | 3000 ..= 4999)
+}
+
+impl WebSocketParser {
+    /// Creates a frame parser for the given side of the connection.
+    pub fn new(role: WebSocketRole) -> WebSocketParser {
+        WebSocketParser {
+            errno: None,
+            allowed_rsv: 0,
+            role,
+            state: FrameState::Header,
+            fin: false,
+            rsv: 0,
+            opcode: WebSocketOpcode::Continuation,
+            masked: false,
+            mask: [0; 4],
+            index: 0,
+            ext_len_size: 0,
+            payload_len: 0,
+            payload_read: 0,
+            message: None,
+            utf8_pending: [0; 4],
+            utf8_pending_len: 0,
+            closed: false,
+            control: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Returns true once a close frame has been parsed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Parses frames from `data` and returns the number of bytes consumed. On error the
+    /// returned count is smaller than `data.len()` and `errno` is set.
+    pub fn execute<T: WebSocketCallback>(&mut self, cb: &mut T, data: &[u8]) -> usize {
+        if self.errno.is_some() {
+            return 0;
+        }
+
+        let len = data.len();
+        let mut index = 0;
+        while index < len {
+            let ch = data[index];
+            match self.state {
+                FrameState::Header => {
+                    if self.closed {
+                        ws_error!(self, WebSocketError::DataAfterClose, index);
+                    }
+                    self.fin = ch & 0x80 != 0;
+                    self.rsv = (ch >> 4) & 0x07;
+                    if self.rsv & !self.allowed_rsv != 0 {
+                        ws_error!(self, WebSocketError::ReservedBits, index);
+                    }
+                    self.opcode = match WebSocketOpcode::from_u8(ch & 0x0f) {
+                        Some(op) => op,
+                        None => ws_error!(self, WebSocketError::ReservedOpcode, index),
+                    };
+                    if self.opcode.is_control() {
+                        if !self.fin {
+                            ws_error!(self, WebSocketError::FragmentedControlFrame, index);
+                        }
+                    } else if self.opcode == WebSocketOpcode::Continuation {
+                        if self.message.is_none() {
+                            ws_error!(self, WebSocketError::UnexpectedContinuation, index);
+                        }
+                    } else if self.message.is_some() {
+                        ws_error!(self, WebSocketError::ExpectedContinuation, index);
+                    }
+                    self.state = FrameState::Length;
+                    index += 1;
+                },
+                FrameState::Length => {
+                    self.masked = ch & 0x80 != 0;
+                    if self.masked && self.role == WebSocketRole::Client {
+                        ws_error!(self, WebSocketError::UnexpectedMask, index);
+                    }
+                    if !self.masked && self.role == WebSocketRole::Server {
+                        ws_error!(self, WebSocketError::MaskRequired, index);
+                    }
+                    self.payload_len = (ch & 0x7f) as u64;
+                    self.index = 0;
+                    match self.payload_len {
+                        126 => {
+                            self.ext_len_size = 2;
+                            self.payload_len = 0;
+                            self.state = FrameState::ExtendedLength;
+                        },
+                        127 => {
+                            self.ext_len_size = 8;
+                            self.payload_len = 0;
+                            self.state = FrameState::ExtendedLength;
+                        },
+                        _ => {
+                            self.state = if self.masked { FrameState::MaskKey } else { FrameState::Payload };
+                        },
+                    }
+                    if self.opcode.is_control() && self.payload_len > MAX_CONTROL_PAYLOAD {
+                        ws_error!(self, WebSocketError::ControlFrameTooLarge, index);
+                    }
+                    index += 1;
+                    if self.state == FrameState::Payload {
+                        let done = self.begin_frame(cb);
+                        if done.is_some() {
+                            return index;
+                        }
+                    }
+                },
+                FrameState::ExtendedLength => {
+                    self.payload_len = (self.payload_len << 8) | ch as u64;
+                    self.index += 1;
+                    index += 1;
+                    if self.index == self.ext_len_size {
+                        // the length must use the minimal encoding and the MSB must be 0
+                        let minimum = if self.ext_len_size == 2 { 126 } else { 0x10000 };
+                        if self.payload_len < minimum || self.payload_len >> 63 != 0 {
+                            ws_error!(self, WebSocketError::InvalidLength, index - 1);
+                        }
+                        if self.opcode.is_control() {
+                            ws_error!(self, WebSocketError::ControlFrameTooLarge, index - 1);
+                        }
+                        self.index = 0;
+                        self.state = if self.masked { FrameState::MaskKey } else { FrameState::Payload };
+                        if self.state == FrameState::Payload && self.begin_frame(cb).is_some() {
+                            return index;
+                        }
+                    }
+                },
+                FrameState::MaskKey => {
+                    self.mask[self.index] = ch;
+                    self.index += 1;
+                    index += 1;
+                    if self.index == 4 {
+                        self.state = FrameState::Payload;
+                        if self.begin_frame(cb).is_some() {
+                            return index;
+                        }
+                    }
+                },
+                FrameState::Payload => {
+                    let to_read = cmp::min(self.payload_len - self.payload_read,
+                                           (len - index) as u64) as usize;
+                    let chunk = &data[index .. index + to_read];
+                    if self.payload(cb, chunk).is_some() {
+                        return index;
+                    }
+                    index += to_read;
+                    if self.payload_read == self.payload_len && self.end_frame(cb).is_some() {
+                        return index;
+                    }
+                },
+            }
+        }
+        len
+    }
+
+    // Called once the whole header is parsed. Returns `Some(())` on error.
+    fn begin_frame<T: WebSocketCallback>(&mut self, cb: &mut T) -> Option<()> {
+        self.payload_read = 0;
+        self.control.clear();
+        if !self.opcode.is_control() && self.opcode != WebSocketOpcode::Continuation {
+            self.message = Some(self.opcode);
+        }
+
+        let header = WebSocketFrameHeader {
+            fin: self.fin,
+            rsv: self.rsv,
+            opcode: self.opcode,
+            mask: if self.masked { Some(self.mask) } else { None },
+            payload_len: self.payload_len,
+        };
+        ws_callback!(self, cb.on_frame_header(self, &header), Some(()));
+
+        if self.payload_len == 0 {
+            return self.end_frame(cb);
+        }
+        None
+    }
+
+    fn payload<T: WebSocketCallback>(&mut self, cb: &mut T, chunk: &[u8]) -> Option<()> {
+        let mut scratch = mem::take(&mut self.scratch);
+        scratch.clear();
+        scratch.extend_from_slice(chunk);
+        if self.masked {
+            for (i, b) in scratch.iter_mut().enumerate() {
+                *b ^= self.mask[((self.payload_read + i as u64) % 4) as usize];
+            }
+        }
+        self.payload_read += chunk.len() as u64;
+
+        let result = if self.opcode.is_control() {
+            self.control.extend_from_slice(&scratch);
+            None
+        } else if self.message == Some(WebSocketOpcode::Text) && !self.validate_utf8(&scratch) {
+            self.errno = Some(WebSocketError::InvalidUtf8);
+            Some(())
+        } else if cb.on_frame_payload(self, &scratch).is_err() {
+            self.errno = Some(WebSocketError::CallbackFailed);
+            Some(())
+        } else {
+            None
+        };
+        self.scratch = scratch;
+        result
+    }
+
+    fn end_frame<T: WebSocketCallback>(&mut self, cb: &mut T) -> Option<()> {
+        self.state = FrameState::Header;
+        let control = mem::take(&mut self.control);
+
+        match self.opcode {
+            WebSocketOpcode::Ping => {
+                ws_callback!(self, cb.on_ping(self, &control), Some(()));
+            },
+            WebSocketOpcode::Pong => {
+                ws_callback!(self, cb.on_pong(self, &control), Some(()));
+            },
+            WebSocketOpcode::Close => {
+                let code = match control.len() {
+                    0 => None,
+                    1 => ws_error!(self, WebSocketError::InvalidCloseCode, Some(())),
+                    _ => {
+                        let code = ((control[0] as u16) << 8) | control[1] as u16;
+                        if !is_valid_close_code(code) {
+                            ws_error!(self, WebSocketError::InvalidCloseCode, Some(()));
+                        }
+                        Some(code)
+                    },
+                };
+                let reason = if control.len() > 2 { &control[2..] } else { &[][..] };
+                if str::from_utf8(reason).is_err() {
+                    ws_error!(self, WebSocketError::InvalidUtf8, Some(()));
+                }
+                self.closed = true;
+                ws_callback!(self, cb.on_close(self, code, reason), Some(()));
+            },
+            _ => (),
+        }
+        self.control = control;
+
+        ws_callback!(self, cb.on_frame_complete(self), Some(()));
+
+        if !self.opcode.is_control() && self.fin {
+            let opcode = self.message.take().unwrap_or(self.opcode);
+            if opcode == WebSocketOpcode::Text && self.utf8_pending_len > 0 {
+                ws_error!(self, WebSocketError::InvalidUtf8, Some(()));
+            }
+            ws_callback!(self, cb.on_message_complete(self, opcode), Some(()));
+        }
+        None
+    }
+
+    // Validates UTF-8 incrementally, carrying an incomplete code point over to the next call.
+    fn validate_utf8(&mut self, data: &[u8]) -> bool {
+        let mut data = data;
+        while self.utf8_pending_len > 0 && !data.is_empty() {
+            self.utf8_pending[self.utf8_pending_len] = data[0];
+            self.utf8_pending_len += 1;
+            data = &data[1..];
+            match str::from_utf8(&self.utf8_pending[..self.utf8_pending_len]) {
+                Ok(..) => self.utf8_pending_len = 0,
+                Err(e) => {
+                    if e.error_len().is_some() || self.utf8_pending_len == 4 {
+                        return false;
+                    }
+                },
+            }
+        }
+        if self.utf8_pending_len > 0 {
+            return true;
+        }
+
+        match str::from_utf8(data) {
+            Ok(..) => true,
+            Err(e) => {
+                if e.error_len().is_some() {
+                    return false;
+                }
+                let tail = &data[e.valid_up_to()..];
+                self.utf8_pending[..tail.len()].copy_from_slice(tail);
+                self.utf8_pending_len = tail.len();
+                true
+            },
+        }
+    }
+}
@@ -939,3 +939,34 @@ pub fn test_scan(r1: &Message, r2: &Message, r3: &Message) {
         }
     }
 }
+
+pub fn parse_with(lenient: Lenient, tp: HttpParserType, data: &[u8]) -> (HttpParser, MessageCollector) {
+    let mut hp = HttpParser::new(tp);
+    hp.lenient = lenient;
+    let mut cb = MessageCollector::new();
+    hp.execute(&mut cb, data);
+    (hp, cb)
+}
+
+// Calls `execute` with `data` in chunks of `chunk` bytes and then with no data for the end
+// of the input, checking that each chunk is parsed completely.
+pub fn execute_chunks<F: FnMut(&[u8]) -> usize>(data: &[u8], chunk: usize, mut execute: F) {
+    for part in data.chunks(chunk) {
+        assert_eq!(execute(part), part.len(), "{}", String::from_utf8_lossy(data));
+    }
+    execute(&[]);
+}
+
+pub fn feed<CB: HttpParserCallback>(hp: &mut HttpParser, cb: &mut CB, data: &[u8], chunk: usize) {
+    execute_chunks(data, chunk, |part| hp.execute(cb, part));
+    assert!(hp.errno.is_none(), "{:?}: {}", hp.errno, String::from_utf8_lossy(data));
+}
+
+pub fn parse_configured<F: FnOnce(&mut HttpParser)>(tp: HttpParserType, data: &[u8], chunk: usize,
+                                                    configure: F) -> (HttpParser, MessageCollector) {
+    let mut hp = HttpParser::new(tp);
+    configure(&mut hp);
+    let mut cb = MessageCollector::new();
+    feed(&mut hp, &mut cb, data, chunk);
+    (hp, cb)
+}
@@ -0,0 +1,113 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+fn media(s: &str) -> MediaType {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_accept() {
+    // RFC 9110 section 12.5.1
+    let accept = Accept::parse(b"text/*;q=0.3, text/plain;q=0.7, text/plain;format=flowed, \
+                                 text/plain;format=fixed;q=0.4, */*;q=0.5").unwrap();
+    assert_eq!(accept.items.len(), 5);
+    assert_eq!(accept.items[2].item.param("format"), Some("flowed"));
+    let expected = [("text/plain;format=flowed", 1000), ("text/plain", 700), ("text/html", 300),
+                    ("image/jpeg", 500), ("text/plain;format=fixed", 400), ("text/html;level=3", 300)];
+    for &(media_type, q) in expected.iter() {
+        assert_eq!(accept.quality(&media(media_type)), q, "{}", media_type);
+    }
+    assert_eq!(accept.to_string(), "text/*;q=0.3, text/plain;q=0.7, text/plain; format=flowed, \
+                                    text/plain; format=fixed;q=0.4, */*;q=0.5");
+
+    let (html, json, png) = (media("text/html"), media("application/json"), media("image/png"));
+    let accept = Accept::parse(b"application/json;q=0.9 ,, text/html;Q=1.0;ext=\"a,b\", image/*;q=0").unwrap();
+    assert_eq!(accept.items[1].q, MAX_QUALITY);
+    assert_eq!(accept.items[1].item.params().len(), 0);
+    assert_eq!(accept.negotiate(&[&json, &html]), Some(&html));
+    assert_eq!(accept.negotiate(&[&png]), None);
+    assert_eq!(accept.preferred(), vec![&html, &json]);
+
+    let any = Accept::parse(b"").unwrap();
+    assert_eq!(any.negotiate(&[&png, &html]), Some(&png));
+
+    assert_eq!(Accept::parse(b"*/html"), Err(AcceptError::InvalidMediaRange));
+    assert_eq!(Accept::parse(b"text"), Err(AcceptError::InvalidMediaRange));
+    assert_eq!(Accept::parse(b"text/html text/plain"), Err(AcceptError::InvalidMediaRange));
+    for q in ["1.001", "2", "0.1234", "-0", ".5"].iter() {
+        assert_eq!(Accept::parse(format!("text/html;q={}", q).as_bytes()), Err(AcceptError::InvalidQValue), "{}", q);
+    }
+}
+
+#[test]
+fn test_accept_encoding() {
+    let accept = AcceptEncoding::parse(b"GZIP;q=0.5, br;q=1, deflate;q=0").unwrap();
+    assert_eq!(accept.quality("gzip"), 500);
+    assert_eq!(accept.quality("x-gzip"), 500);
+    assert_eq!(accept.quality("deflate"), 0);
+    assert_eq!(accept.quality("zstd"), 0);
+    assert_eq!(accept.quality("identity"), 1);
+    assert_eq!(accept.negotiate(&["deflate", "gzip", "br"]), Some("br"));
+    assert_eq!(accept.negotiate(&["identity", "gzip"]), Some("gzip"));
+    assert_eq!(accept.negotiate(&["deflate", "identity"]), Some("identity"));
+    assert_eq!(accept.preferred(), vec!["br", "gzip"]);
+    assert_eq!(accept.to_string(), "gzip;q=0.5, br, deflate;q=0");
+
+    // an empty value allows only identity
+    let empty = AcceptEncoding::parse(b"").unwrap();
+    assert_eq!(empty.negotiate(&["gzip", "identity"]), Some("identity"));
+
+    let no_identity = AcceptEncoding::parse(b"gzip, identity;q=0").unwrap();
+    assert_eq!(no_identity.negotiate(&["identity"]), None);
+    let nothing_else = AcceptEncoding::parse(b"*;q=0").unwrap();
+    assert_eq!(nothing_else.negotiate(&["identity", "gzip"]), None);
+    let anything = AcceptEncoding::parse(b"*;q=0.2, identity").unwrap();
+    assert_eq!(anything.negotiate(&["zstd", "identity"]), Some("identity"));
+    assert_eq!(anything.quality("zstd"), 200);
+
+    assert_eq!(AcceptEncoding::parse(b"gzip deflate"), Err(AcceptError::InvalidCoding));
+    assert_eq!(AcceptEncoding::parse(b"gzip;q=x"), Err(AcceptError::InvalidQValue));
+}
+
+#[test]
+fn test_accept_language() {
+    let accept = AcceptLanguage::parse(b"de-CH, de;q=0.9, en;q=0.8, *;q=0.1, fr-FR;q=0").unwrap();
+    assert_eq!(accept.quality("de-ch"), 1000);
+    assert_eq!(accept.quality("de-AT"), 900);
+    assert_eq!(accept.quality("en-US"), 800);
+    assert_eq!(accept.quality("enx"), 100);
+    assert_eq!(accept.quality("fr-FR-x-1"), 0);
+    assert_eq!(accept.quality("fr"), 100);
+    assert_eq!(accept.negotiate(&["fr-FR", "en-GB", "de"]), Some("de"));
+    assert_eq!(accept.negotiate(&["fr-FR"]), None);
+    assert_eq!(accept.preferred(), vec!["de-CH", "de", "en", "*"]);
+    assert_eq!(accept.to_string(), "de-CH, de;q=0.9, en;q=0.8, *;q=0.1, fr-FR;q=0");
+
+    assert!(language_matches("en", "EN-us"));
+    assert!(!language_matches("en-us", "en"));
+    assert!(!language_matches("en", "eng"));
+
+    assert_eq!(AcceptLanguage::parse(b"").unwrap().negotiate(&["fr", "en"]), Some("fr"));
+    assert_eq!(AcceptLanguage::parse(b"en_US"), Err(AcceptError::InvalidLanguageRange));
+    assert_eq!(AcceptLanguage::parse(b"toolongtag"), Err(AcceptError::InvalidLanguageRange));
+    assert_eq!(AcceptLanguage::parse(b"1en"), Err(AcceptError::InvalidLanguageRange));
+    assert_eq!(AcceptLanguage::parse(b"zh-Hant-2020"), Ok(AcceptLanguage {
+        items: vec![QualityItem { item: "zh-Hant-2020".to_string(), q: MAX_QUALITY }],
+    }));
+}
+
+#[test]
+fn test_accept_headers() {
+    let mut cb = MessageCollector::new();
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.execute(&mut cb, b"GET / HTTP/1.1\r\nAccept: text/html\r\nAccept-Encoding: br;q=0.5\r\n\
+                          accept: application/json;q=0.8\r\nAccept-Encoding: gzip\r\n\
+                          Accept-Language: en_US\r\n\r\n");
+    let headers = &cb.messages[0].headers;
+    let accept = headers.accept().unwrap();
+    assert_eq!(accept.items.len(), 2);
+    assert_eq!(accept.quality(&media("application/json")), 800);
+    assert_eq!(headers.accept_encoding().unwrap().negotiate(&["br", "gzip"]), Some("gzip"));
+    assert_eq!(headers.accept_language(), None);
+}
@@ -0,0 +1,137 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+#[test]
+fn test_basic_and_bearer() {
+    let credentials = Credentials::parse(b"basic  dXNlcjpwYTpzcw==").unwrap();
+    assert_eq!(credentials, Credentials::Basic { username: "user".to_string(), password: "pa:ss".to_string() });
+    assert_eq!(credentials.to_string(), "Basic dXNlcjpwYTpzcw==");
+
+    assert_eq!(Credentials::parse(b"Basic dXNlcg=="), Err(AuthError::InvalidBasicCredentials));
+    assert_eq!(Credentials::parse(b"Basic !!!"), Err(AuthError::InvalidParameter));
+    assert_eq!(Credentials::parse(b"Basic"), Err(AuthError::InvalidBasicCredentials));
+
+    let credentials = Credentials::parse(b"Bearer mF_9.B5f-4.1JqM").unwrap();
+    assert_eq!(credentials, Credentials::Bearer("mF_9.B5f-4.1JqM".to_string()));
+    assert_eq!(credentials.to_string(), "Bearer mF_9.B5f-4.1JqM");
+    assert_eq!(Credentials::parse(b"Bearer"), Err(AuthError::MissingToken));
+
+    match Credentials::parse(b"Negotiate YIIB==").unwrap() {
+        Credentials::Other(other) => {
+            assert!(other.is("negotiate"));
+            assert_eq!(other.token68, Some("YIIB==".to_string()));
+        },
+        other => panic!("{:?}", other),
+    }
+
+    assert_eq!(Credentials::parse(b""), Err(AuthError::MissingScheme));
+    assert_eq!(Credentials::parse(b"realm=x"), Err(AuthError::MissingScheme));
+    assert_eq!(Credentials::parse(b"Basic a, Bearer b"), Err(AuthError::InvalidParameter));
+}
+
+#[test]
+fn test_challenges() {
+    let challenges = Challenge::parse_list(b"Newauth realm=\"apps\", type=1, \
+                                             title=\"Login to \\\"apps\\\"\", Basic realm=\"simple\", \
+                                             Negotiate, Negotiate abc==,Digest realm = \"a, b\" , qop=\"auth,auth-int\"").unwrap();
+    assert_eq!(challenges.len(), 5);
+    assert_eq!(challenges[0].params, vec![("realm".to_string(), "apps".to_string()),
+                                          ("type".to_string(), "1".to_string()),
+                                          ("title".to_string(), "Login to \"apps\"".to_string())]);
+    assert_eq!(challenges[0].to_string(), "Newauth realm=\"apps\", type=\"1\", title=\"Login to \\\"apps\\\"\"");
+    assert!(challenges[1].is("basic"));
+    assert_eq!(challenges[1].realm(), Some("simple"));
+    assert_eq!(challenges[2].to_string(), "Negotiate");
+    assert_eq!(challenges[3].token68, Some("abc==".to_string()));
+    assert_eq!(challenges[4].realm(), Some("a, b"));
+    assert_eq!(challenges[4].param("QOP"), Some("auth,auth-int"));
+
+    assert_eq!(Challenge::parse_list(b"Basic realm=\"x"), Err(AuthError::UnterminatedQuote));
+    assert_eq!(Challenge::parse_list(b"Basic realm=\"a\" b"), Err(AuthError::InvalidParameter));
+    assert_eq!(Challenge::parse_list(b"Negotiate abc==, realm=x"), Err(AuthError::InvalidParameter));
+    assert_eq!(Challenge::parse_list(b", ,"), Ok(vec![]));
+}
+
+#[test]
+fn test_digest_rfc2617() {
+    let value = b"Digest username=\"Mufasa\", realm=\"testrealm@host.com\", \
+                  nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"/dir/index.html\", qop=auth, \
+                  nc=00000001, cnonce=\"0a4f113b\", response=\"6629fae49393a05397450978507c4ef1\", \
+                  opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"";
+    let digest = match Credentials::parse(value).unwrap() {
+        Credentials::Digest(digest) => digest,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(digest.username, "Mufasa");
+    assert_eq!(digest.nc, Some("00000001".to_string()));
+    assert_eq!(digest.compute_response("GET", "Circle Of Life", b"").unwrap(), digest.response);
+    assert!(digest.verify("GET", "Circle Of Life", b""));
+    assert!(!digest.verify("GET", "circle of life", b""));
+    assert!(!digest.verify("POST", "Circle Of Life", b""));
+
+    let reparsed = Credentials::parse(digest.to_string().as_bytes()).unwrap();
+    assert_eq!(reparsed, Credentials::Digest(digest));
+
+    assert_eq!(Credentials::parse(b"Digest username=\"a\", realm=\"b\", nonce=\"c\", uri=\"/\""),
+               Err(AuthError::MissingParameter("response")));
+}
+
+#[test]
+fn test_digest_rfc7616() {
+    let mut digest = DigestCredentials {
+        username: "Mufasa".to_string(),
+        realm: "http-auth@example.org".to_string(),
+        nonce: "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v".to_string(),
+        uri: "/dir/index.html".to_string(),
+        algorithm: Some("MD5".to_string()),
+        cnonce: Some("f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ".to_string()),
+        opaque: Some("FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS".to_string()),
+        qop: Some("auth".to_string()),
+        nc: Some("00000001".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(digest.compute_response("GET", "Circle of Life", b"").unwrap(),
+               "8ca523f5e9506fed4657c9700eebdbec");
+
+    // with userhash the digest still uses the plain user name
+    let mut hashed = digest.clone();
+    hashed.userhash = true;
+    hashed.username = "4238f3a16167373febb9bc4d43db9cc4".to_string();
+    hashed.response = "8ca523f5e9506fed4657c9700eebdbec".to_string();
+    assert_eq!(hashed.hash_username("Mufasa").unwrap(), hashed.username);
+    assert_eq!(hashed.compute_response("GET", "Circle of Life", b""), Err(AuthError::HashedUsername));
+    assert!(!hashed.verify("GET", "Circle of Life", b""));
+    assert!(hashed.verify_as("Mufasa", "GET", "Circle of Life", b""));
+    assert!(!hashed.verify_as("Simba", "GET", "Circle of Life", b""));
+    digest.algorithm = Some("SHA-256".to_string());
+    assert_eq!(digest.compute_response("GET", "Circle of Life", b"").unwrap(),
+               "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1");
+
+    // session variants and auth-int depend on cnonce and the body
+    digest.algorithm = Some("SHA-256-sess".to_string());
+    digest.qop = Some("auth-int".to_string());
+    let response = digest.compute_response("POST", "Circle of Life", b"body").unwrap();
+    assert_eq!(response.len(), 64);
+    assert!(response != digest.compute_response("POST", "Circle of Life", b"other").unwrap());
+    digest.cnonce = None;
+    assert_eq!(digest.compute_response("POST", "Circle of Life", b"body"),
+               Err(AuthError::MissingParameter("cnonce")));
+
+    digest.algorithm = Some("SHA-512-256".to_string());
+    assert_eq!(digest.compute_response("GET", "x", b""), Err(AuthError::UnsupportedAlgorithm));
+    digest.algorithm = None;
+    digest.qop = Some("auth-conf".to_string());
+    digest.cnonce = Some("c".to_string());
+    assert_eq!(digest.compute_response("GET", "x", b""), Err(AuthError::UnsupportedQop));
+
+    let value = b"Digest username*=UTF-8''J%C3%A4s%C3%B8n%20Doe, realm=\"api@example.org\", \
+                  uri=\"/doe.json\", nonce=\"n\", response=\"r\", userhash=false";
+    match Credentials::parse(value).unwrap() {
+        Credentials::Digest(digest) => {
+            assert_eq!(digest.username, "J\u{e4}s\u{f8}n Doe");
+            assert!(!digest.userhash);
+        },
+        other => panic!("{:?}", other),
+    }
+}
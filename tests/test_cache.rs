@@ -0,0 +1,93 @@
+extern crate http_parser;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use http_parser::*;
+
+#[test]
+fn test_cache_control() {
+    let cc = CacheControl::parse(b"Max-Age=60, max-age=10, s-maxage=\"x\", private=\"Set-Cookie\", \
+                                   no-store, must-revalidate,, stale-while-revalidate=30, \
+                                   stale-if-error=abc, immutable, ext=\"a, b\", ext=2, bad value, max-stale");
+    assert_eq!(cc.max_age, Some(60));
+    assert_eq!(cc.s_maxage, Some(0));
+    assert_eq!(cc.private, Some(vec!["set-cookie".to_string()]));
+    assert!(cc.no_store && cc.must_revalidate && cc.immutable);
+    assert!(!cc.public && !cc.no_transform);
+    assert_eq!(cc.no_cache, None);
+    assert_eq!(cc.stale_while_revalidate, Some(30));
+    assert_eq!(cc.stale_if_error, None);
+    assert_eq!(cc.max_stale, Some(None));
+    assert_eq!(cc.extensions, vec![("ext".to_string(), Some("a, b".to_string())), ("bad".to_string(), None)]);
+    assert_eq!(CacheControl::parse(cc.to_string().as_bytes()), cc);
+
+    assert_eq!(CacheControl::parse(b"max-age=99999999999999999999999").max_age, Some(1 << 31));
+    assert_eq!(CacheControl::parse(b"no-cache").no_cache, Some(Vec::new()));
+    assert_eq!(CacheControl::parse(b"min-fresh=5, only-if-cached").to_string(), "min-fresh=5, only-if-cached");
+
+    let mut headers = Headers::new();
+    headers.push("Pragma", "no-cache");
+    assert_eq!(CacheControl::from_headers(&headers).no_cache, Some(Vec::new()));
+    headers.push("Cache-Control", "max-age=5");
+    headers.push("cache-control", "public, max-age=7");
+    let cc = CacheControl::from_headers(&headers);
+    assert_eq!(cc.no_cache, None);
+    assert_eq!(cc.max_age, Some(5));
+    assert!(cc.public);
+}
+
+fn response(head: &str) -> Message {
+    let mut parser = HttpParser::new(HttpParserType::Response);
+    let mut collector = MessageCollector::new();
+    parser.execute(&mut collector, format!("{}Content-Length: 0\r\n\r\n", head).as_bytes());
+    collector.messages.remove(0)
+}
+
+#[test]
+fn test_freshness() {
+    // Sun, 06 Nov 1994 08:49:37 GMT
+    let date = UNIX_EPOCH + Duration::from_secs(784111777);
+    let secs = Duration::from_secs;
+
+    let msg = response("HTTP/1.1 200 OK\r\nDate: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+                        Cache-Control: max-age=100, s-maxage=1000\r\nExpires: Sun, 06 Nov 1994 09:49:37 GMT\r\n");
+    let f = Freshness::evaluate(&msg, date, date, date + secs(50), false);
+    assert_eq!((f.lifetime, f.heuristic, f.age), (secs(100), false, secs(50)));
+    assert!(f.is_fresh());
+    assert_eq!(f.time_to_live(), secs(50));
+    let f = Freshness::evaluate(&msg, date, date, date + secs(500), true);
+    assert_eq!(f.lifetime, secs(1000));
+
+    // Expires relative to Date; the response arrived 10s after its Date and took 2s
+    let msg = response("HTTP/1.1 200 OK\r\nDate: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+                        Expires: Sun, 06 Nov 1994 09:49:37 GMT\r\nAge: 5\r\n");
+    let f = Freshness::evaluate(&msg, date + secs(8), date + secs(10), date + secs(20), false);
+    assert_eq!((f.lifetime, f.age), (secs(3600), secs(20)));
+    let f = Freshness::evaluate(&msg, date + secs(8), date + secs(30), date + secs(30), false);
+    assert_eq!(f.age, secs(30));
+
+    // invalid or past Expires is stale
+    for expires in ["0", "Sun, 06 Nov 1994 07:49:37 GMT"].iter() {
+        let msg = response(&format!("HTTP/1.1 200 OK\r\nDate: Sun, 06 Nov 1994 08:49:37 GMT\r\nExpires: {}\r\n", expires));
+        let f = Freshness::evaluate(&msg, date, date, date, false);
+        assert_eq!(f.lifetime, secs(0));
+        assert!(!f.is_fresh());
+        assert_eq!(f.time_to_live(), secs(0));
+    }
+
+    // heuristic: 10% of the time since Last-Modified, for heuristically cacheable status
+    let head = "Date: Sun, 06 Nov 1994 08:49:37 GMT\r\nLast-Modified: Sun, 06 Nov 1994 06:49:37 GMT\r\n";
+    let f = Freshness::evaluate(&response(&format!("HTTP/1.1 200 OK\r\n{}", head)), date, date, date, false);
+    assert_eq!((f.lifetime, f.heuristic), (secs(720), true));
+    let f = Freshness::evaluate(&response(&format!("HTTP/1.1 302 Found\r\n{}", head)), date, date, date, false);
+    assert_eq!((f.lifetime, f.heuristic), (secs(0), false));
+    let msg = response(&format!("HTTP/1.1 302 Found\r\nCache-Control: public\r\n{}", head));
+    let f = Freshness::evaluate(&msg, date, date, date, false);
+    assert_eq!((f.lifetime, f.heuristic), (secs(720), true));
+
+    // without Date, the response time stands in for it
+    let msg = response("HTTP/1.1 200 OK\r\nCache-Control: max-age=60\r\n");
+    let f = Freshness::evaluate(&msg, date, date, date + secs(60), false);
+    assert_eq!(f.age, secs(60));
+    assert!(!f.is_fresh());
+}
@@ -0,0 +1,124 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+#[derive(Default)]
+struct Callback {
+    url: Vec<u8>,
+    completed: usize,
+    tunnel: Vec<u8>,
+}
+
+impl HttpParserCallback for Callback {
+    fn on_url(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.url.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.completed += 1;
+        Ok(ParseAction::None)
+    }
+
+    fn on_tunnel_data(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.tunnel.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+}
+
+fn parse_target(target: &str) -> HttpParser {
+    let request = format!("CONNECT {} HTTP/1.1\r\n\r\n", target);
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    let mut cb = Callback::default();
+    // byte by byte, to check the authority states resume across calls
+    for b in request.as_bytes() {
+        if hp.execute(&mut cb, &[*b]) != 1 {
+            break;
+        }
+    }
+    if hp.errno.is_none() {
+        assert_eq!(cb.url, target.as_bytes());
+    }
+    hp
+}
+
+#[test]
+fn test_connect_authority() {
+    let targets = [
+        ("example.com:443", "example.com", 443),
+        ("home_0.netscape.com:80", "home_0.netscape.com", 80),
+        ("10.0.0.1:8080", "10.0.0.1", 8080),
+        ("[::1]:443", "::1", 443),
+        ("[2001:db8::ffff:192.0.2.1]:65535", "2001:db8::ffff:192.0.2.1", 65535),
+    ];
+    for &(target, host, port) in targets.iter() {
+        let hp = parse_target(target);
+        assert!(hp.errno.is_none(), "{}", target);
+        assert!(hp.upgrade);
+        assert_eq!(hp.connect_host, Some(host.to_string()));
+        assert_eq!(hp.connect_port, Some(port));
+    }
+}
+
+#[test]
+fn test_connect_authority_errors() {
+    let targets = [
+        (":443", HttpErrno::InvalidHost),
+        ("user@example.com:443", HttpErrno::InvalidHost),
+        ("example.com/path:443", HttpErrno::InvalidHost),
+        ("[example.com]:443", HttpErrno::InvalidHost),
+        ("[]:443", HttpErrno::InvalidHost),
+        ("[::1]x:443", HttpErrno::InvalidHost),
+        ("example.com", HttpErrno::InvalidPort),
+        ("[::1]", HttpErrno::InvalidPort),
+        ("example.com:", HttpErrno::InvalidPort),
+        ("example.com:https", HttpErrno::InvalidPort),
+        ("example.com:65536", HttpErrno::InvalidPort),
+        ("example.com:443/", HttpErrno::InvalidPort),
+    ];
+    for &(target, err) in targets.iter() {
+        let hp = parse_target(target);
+        assert_eq!(hp.errno, Some(err), "{}", target);
+        assert_eq!(hp.connect_port, None);
+    }
+}
+
+#[test]
+fn test_connect_tunnel() {
+    let request = b"CONNECT example.com:443 HTTP/1.1\r\n\
+                    Host: example.com:443\r\n\
+                    \r\n\
+                    \x16\x03\x01 client hello";
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.tunnel = true;
+    let mut cb = Callback::default();
+
+    assert_eq!(hp.execute(&mut cb, request), request.len());
+    assert_eq!(cb.completed, 1);
+    assert_eq!(cb.tunnel, b"\x16\x03\x01 client hello".to_vec());
+
+    // everything after the request is tunnel data, even if it looks like HTTP
+    assert_eq!(hp.execute(&mut cb, b"GET / HTTP/1.1\r\n\r\n"), 18);
+    assert!(cb.tunnel.ends_with(b"GET / HTTP/1.1\r\n\r\n"));
+    assert_eq!(cb.completed, 1);
+
+    // EOF closes the tunnel without an error
+    assert_eq!(hp.execute(&mut cb, &[]), 0);
+    assert!(hp.errno.is_none());
+}
+
+#[test]
+fn test_upgrade_tunnel() {
+    let response = b"HTTP/1.1 101 Switching Protocols\r\n\
+                     Connection: Upgrade\r\n\
+                     Upgrade: websocket\r\n\
+                     \r\n";
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    hp.tunnel = true;
+    let mut cb = Callback::default();
+
+    assert_eq!(hp.execute(&mut cb, response), response.len());
+    assert!(cb.tunnel.is_empty());
+    assert_eq!(hp.execute(&mut cb, b"\x81\x02hi"), 4);
+    assert_eq!(cb.tunnel, b"\x81\x02hi".to_vec());
+}
@@ -0,0 +1,66 @@
+extern crate http_parser;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use http_parser::*;
+
+#[test]
+fn test_cookie_header() {
+    let pairs = parse_cookies(" a=1;b = 2 ; c=\"quoted\"; =nameless; novalue; d=; e=x=y;");
+    let pairs: Vec<(&str, &str)> = pairs.iter().map(|p| (&p.0[..], &p.1[..])).collect();
+    assert_eq!(pairs, vec![("a", "1"), ("b", "2"), ("c", "\"quoted\""), ("d", ""), ("e", "x=y")]);
+    assert_eq!(format_cookies(pairs), "a=1; b=2; c=\"quoted\"; d=; e=x=y");
+
+    let mut cb = MessageCollector::new();
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.execute(&mut cb, b"GET / HTTP/1.1\r\nCookie: a=1; b=2\r\nCookie: c=3\r\n\r\n");
+    let cookies = cb.messages[0].headers.cookies();
+    assert_eq!(cookies.len(), 3);
+    assert_eq!(cookies[2], ("c".to_string(), "3".to_string()));
+}
+
+#[test]
+fn test_set_cookie() {
+    // 1994-11-06 08:49:37 UTC
+    let time = UNIX_EPOCH + Duration::from_secs(784111777);
+    let dates = ["Sun, 06 Nov 1994 08:49:37 GMT", "Sunday, 06-Nov-94 08:49:37 GMT",
+                 "Sun Nov  6 08:49:37 1994", "6 nov 1994 8:49:37", "06-NOVEMBER-1994 08:49:37.123"];
+    for date in dates.iter() {
+        let cookie = SetCookie::parse(&format!("a=b; expires={}", date)).unwrap();
+        assert_eq!(cookie.expires, Some(time), "{}", date);
+    }
+    for date in ["Sun, 31 Feb 1994 08:49:37 GMT", "Sun, 06 Nov 1994 24:00:00 GMT", "06 Nov 1994", "1600-01-01"].iter() {
+        let cookie = SetCookie::parse(&format!("a=b; Expires={}", date)).unwrap();
+        assert_eq!(cookie.expires, None, "{}", date);
+    }
+
+    let cookie = SetCookie::parse("SID = 31d4 ; Domain=.Example.COM; Path=docs; path=/app; \
+                                   max-age=-5; Max-Age=x; secure; HTTPONLY; SameSite=Bogus; \
+                                   Partitioned; Unknown=1").unwrap();
+    assert_eq!(cookie.name, "SID");
+    assert_eq!(cookie.value, "31d4");
+    assert_eq!(cookie.domain, Some("example.com".to_string()));
+    assert_eq!(cookie.path, Some("/app".to_string()));
+    assert_eq!(cookie.max_age, Some(-5));
+    assert!(cookie.secure && cookie.http_only && cookie.partitioned);
+    assert_eq!(cookie.same_site, None);
+
+    assert_eq!(SetCookie::parse("noequals; Secure"), Err(CookieError::MissingEquals));
+    assert_eq!(SetCookie::parse("=value"), Err(CookieError::EmptyName));
+    assert_eq!(SetCookie::new("a b", "c").unwrap_err(), CookieError::InvalidName);
+    assert_eq!(SetCookie::new("a", "b;c").unwrap_err(), CookieError::InvalidValue);
+
+    let mut cookie = SetCookie::new("lang", "\"en-US\"").unwrap();
+    cookie.expires = Some(time);
+    cookie.path = Some("/".to_string());
+    cookie.same_site = Some(SameSite::None);
+    cookie.secure = true;
+    let value = cookie.to_string();
+    assert_eq!(value, "lang=\"en-US\"; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Path=/; Secure; SameSite=None");
+    assert_eq!(SetCookie::parse(&value), Ok(cookie.clone()));
+
+    let mut headers = Headers::new();
+    headers.push_set_cookie(&cookie);
+    headers.push("Set-Cookie", "invalid");
+    assert_eq!(headers.set_cookies(), vec![cookie]);
+}
@@ -0,0 +1,130 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::feed;
+
+pub mod helper;
+
+struct Callback {
+    reject: bool,
+    expects_continue: Vec<bool>,
+    body: Vec<u8>,
+    urls: Vec<u8>,
+    completed: usize,
+}
+
+impl Callback {
+    fn new(reject: bool) -> Callback {
+        Callback { reject, expects_continue: vec![], body: vec![], urls: vec![], completed: 0 }
+    }
+}
+
+impl HttpParserCallback for Callback {
+    fn on_url(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.urls.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_headers_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        let expects = parser.expects_continue();
+        self.expects_continue.push(expects);
+        if expects && self.reject {
+            // the server answers 417 Expectation Failed
+            Ok(ParseAction::DiscardBody)
+        } else {
+            Ok(ParseAction::None)
+        }
+    }
+
+    fn on_body(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.body.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.completed += 1;
+        Ok(ParseAction::None)
+    }
+}
+
+fn parse(cb: &mut Callback, data: &str) {
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    // byte by byte, to exercise the header matcher across calls
+    feed(&mut hp, cb, data.as_bytes(), 1);
+}
+
+const CONTENT_LENGTH_REQUEST: &str = "PUT /upload HTTP/1.1\r\n\
+                                      Expect: 100-continue\r\n\
+                                      Content-Length: 5\r\n\
+                                      \r\n\
+                                      hello";
+
+const CHUNKED_REQUEST: &str = "POST /upload HTTP/1.1\r\n\
+                               EXPECT: 100-Continue  \r\n\
+                               Transfer-Encoding: chunked\r\n\
+                               \r\n\
+                               5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+
+const NEXT_REQUEST: &str = "GET /next HTTP/1.1\r\n\r\n";
+
+#[test]
+fn test_expect_continue_proceed() {
+    let mut cb = Callback::new(false);
+    parse(&mut cb, CONTENT_LENGTH_REQUEST);
+    assert_eq!(cb.expects_continue, vec![true]);
+    assert_eq!(cb.body, b"hello".to_vec());
+    assert_eq!(cb.completed, 1);
+
+    let mut cb = Callback::new(false);
+    parse(&mut cb, CHUNKED_REQUEST);
+    assert_eq!(cb.expects_continue, vec![true]);
+    assert_eq!(cb.body, b"hello world".to_vec());
+}
+
+#[test]
+fn test_expect_continue_reject() {
+    for request in [CONTENT_LENGTH_REQUEST, CHUNKED_REQUEST].iter() {
+        let mut cb = Callback::new(true);
+        let mut data = request.to_string();
+        data.push_str(NEXT_REQUEST);
+        parse(&mut cb, &data);
+
+        assert_eq!(cb.expects_continue, vec![true, false]);
+        assert!(cb.body.is_empty());
+        assert_eq!(cb.completed, 2);
+        assert!(cb.urls.ends_with(b"/next"));
+    }
+}
+
+#[test]
+fn test_expect_continue_not_matched() {
+    let requests = [
+        // HTTP/1.0 clients can't expect 100 Continue
+        "POST / HTTP/1.0\r\nExpect: 100-continue\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpect: 100-continued\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpected: 100-continue\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpect: 200-ok\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpect: foo100-continue, bar\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpect: 100-continue foo\r\nContent-Length: 0\r\n\r\n",
+    ];
+    for request in requests.iter() {
+        let mut cb = Callback::new(true);
+        parse(&mut cb, request);
+        assert_eq!(cb.expects_continue, vec![false], "{}", request);
+    }
+}
+
+#[test]
+fn test_expect_continue_list() {
+    let requests = [
+        "POST / HTTP/1.1\r\nExpect: 100-continue, foo\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpect: foo=bar,100-Continue\r\nContent-Length: 0\r\n\r\n",
+        "POST / HTTP/1.1\r\nExpect: ,, 1, 100-continue\t,\r\nContent-Length: 0\r\n\r\n",
+    ];
+    for request in requests.iter() {
+        let mut cb = Callback::new(false);
+        parse(&mut cb, request);
+        assert_eq!(cb.expects_continue, vec![true], "{}", request);
+    }
+}
@@ -0,0 +1,166 @@
+extern crate http_parser;
+
+use std::net::IpAddr;
+
+use http_parser::*;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_forwarded() {
+    // RFC 7239 section 4 and 7.4
+    let forwarded = Forwarded::parse(b"for=\"_gazonk\", For=\"[2001:db8:cafe::17]:4711\", \
+                                       for=192.0.2.60;proto=HTTP;by=203.0.113.43, for=unknown;host=\"example.com:8080\", \
+                                       for=\"192.0.2.43:_hidden\";secret=\"a;b\",,").unwrap();
+    let elements = &forwarded.elements;
+    assert_eq!(elements.len(), 5);
+    assert_eq!(elements[0].for_, Some(Node { name: NodeName::Obfuscated("_gazonk".to_string()), port: None }));
+    assert_eq!(elements[1].for_, Some(Node { name: NodeName::Ip(ip("2001:db8:cafe::17")), port: Some(NodePort::Port(4711)) }));
+    assert_eq!(elements[2].proto, Some("http".to_string()));
+    assert_eq!(elements[2].by.as_ref().and_then(Node::ip), Some(ip("203.0.113.43")));
+    assert_eq!(elements[3].for_.as_ref().map(|n| &n.name), Some(&NodeName::Unknown));
+    assert_eq!(elements[3].host, Some("example.com:8080".to_string()));
+    assert_eq!(elements[4].for_.as_ref().unwrap().port, Some(NodePort::Obfuscated("_hidden".to_string())));
+    assert_eq!(elements[4].extensions, vec![("secret".to_string(), "a;b".to_string())]);
+    assert_eq!(forwarded.to_string(), "for=_gazonk, for=\"[2001:db8:cafe::17]:4711\", \
+                                       for=192.0.2.60;by=203.0.113.43;proto=http, for=unknown;host=\"example.com:8080\", \
+                                       for=\"192.0.2.43:_hidden\";secret=\"a;b\"");
+    assert_eq!(Forwarded::parse(forwarded.to_string().as_bytes()), Ok(forwarded));
+
+    let errors: [(&[u8], ForwardedError); 10] = [
+        (b"for=[2001:db8::1]", ForwardedError::InvalidParameter),
+        (b"for=\"2001:db8::1\"", ForwardedError::InvalidNode),
+        (b"for=\"[192.0.2.1]\"", ForwardedError::InvalidNode),
+        (b"for=\"[2001:db8::1\"", ForwardedError::InvalidNode),
+        (b"for=\"_\"", ForwardedError::InvalidNode),
+        (b"for=\"192.0.2.1:65536\"", ForwardedError::InvalidNode),
+        (b"for=\"_a!b\"", ForwardedError::InvalidNode),
+        (b"for=1.2.3.4;FOR=5.6.7.8", ForwardedError::DuplicateParameter),
+        (b"for=\"1.2.3.4", ForwardedError::UnterminatedQuote),
+        (b"proto=1http", ForwardedError::InvalidProto),
+    ];
+    for &(value, error) in errors.iter() {
+        assert_eq!(Forwarded::parse(value), Err(error), "{}", String::from_utf8_lossy(value));
+    }
+    assert_eq!(Forwarded::parse(b"host=\"a/b\""), Err(ForwardedError::InvalidHost));
+    assert_eq!(Forwarded::parse(b"for"), Err(ForwardedError::InvalidParameter));
+    assert_eq!(Forwarded::parse(b"for=192.0.2.43:_hidden"), Err(ForwardedError::InvalidParameter));
+}
+
+#[test]
+fn test_x_forwarded_headers() {
+    let nodes = parse_x_forwarded_for(b"203.0.113.195, 2001:db8:85a3::8a2e:370:7334,[2001:db8::1]:443, \
+                                        198.51.100.1:8080, unknown").unwrap();
+    let ips: Vec<Option<IpAddr>> = nodes.iter().map(Node::ip).collect();
+    assert_eq!(ips, vec![Some(ip("203.0.113.195")), Some(ip("2001:db8:85a3::8a2e:370:7334")),
+                         Some(ip("2001:db8::1")), Some(ip("198.51.100.1")), None]);
+    assert_eq!(nodes[2].port, Some(NodePort::Port(443)));
+    assert_eq!(nodes[2].to_string(), "[2001:db8::1]:443");
+    assert_eq!(parse_x_forwarded_for(b"_hidden"), Err(ForwardedError::InvalidNode));
+    assert_eq!(parse_x_forwarded_for(b"example.com"), Err(ForwardedError::InvalidNode));
+
+    assert_eq!(parse_x_forwarded_proto(b"HTTPS, http").unwrap(), vec!["https", "http"]);
+    assert_eq!(parse_x_forwarded_proto(b"ht tp"), Err(ForwardedError::InvalidProto));
+    assert_eq!(parse_x_forwarded_host(b"example.com, internal:8080").unwrap(), vec!["example.com", "internal:8080"]);
+
+    assert_eq!(parse_x_real_ip(b" 192.0.2.1 "), Ok(ip("192.0.2.1")));
+    assert_eq!(parse_x_real_ip(b"[::1]"), Ok(ip("::1")));
+    assert_eq!(parse_x_real_ip(b"192.0.2.1:80"), Err(ForwardedError::InvalidAddress));
+}
+
+#[test]
+fn test_ip_network() {
+    let network: IpNetwork = "10.0.0.0/8".parse().unwrap();
+    assert!(network.contains(ip("10.255.0.1")));
+    assert!(network.contains(ip("::ffff:10.0.0.1")));
+    assert!(!network.contains(ip("11.0.0.1")));
+    assert!(!network.contains(ip("::a00:1")));
+    assert_eq!(network.to_string(), "10.0.0.0/8");
+
+    let network: IpNetwork = "2001:db8::/32".parse().unwrap();
+    assert!(network.contains(ip("2001:db8:ffff::1")));
+    assert!(!network.contains(ip("2001:db9::1")));
+
+    let any: IpNetwork = "0.0.0.0/0".parse().unwrap();
+    assert!(any.contains(ip("192.0.2.1")));
+    assert!(!any.contains(ip("::1")));
+    let single: IpNetwork = "::1".parse().unwrap();
+    assert!(single.contains(ip("::1")));
+    assert!(!single.contains(ip("::2")));
+
+    assert_eq!("10.0.0.0/33".parse::<IpNetwork>(), Err(ForwardedError::InvalidAddress));
+    assert_eq!("10.0.0/8".parse::<IpNetwork>(), Err(ForwardedError::InvalidAddress));
+}
+
+fn headers(fields: &[(&str, &str)]) -> Headers {
+    let mut headers = Headers::new();
+    for &(name, value) in fields.iter() {
+        headers.push(name, value);
+    }
+    headers
+}
+
+#[test]
+fn test_trusted_proxies() {
+    let proxies = TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap(), "2001:db8::/32".parse().unwrap()]);
+    let peer = ip("10.0.0.1");
+
+    // an untrusted peer is the client, whatever it claims
+    let spoofed = headers(&[("Host", "example.com"), ("X-Forwarded-For", "1.2.3.4"), ("X-Forwarded-Proto", "https")]);
+    let client = proxies.resolve(ip("192.0.2.1"), &spoofed).unwrap();
+    assert_eq!(client, ClientInfo { node: Node::from(ip("192.0.2.1")), proto: None, host: Some("example.com".to_string()) });
+    assert_eq!(proxies.resolve(peer, &spoofed).unwrap().ip(), Some(ip("1.2.3.4")));
+
+    // the walk stops at the first untrusted hop from the right
+    let chain = headers(&[("Host", "internal"),
+                          ("Forwarded", "for=198.51.100.1;proto=http, for=192.0.2.60;proto=https;host=example.com"),
+                          ("Forwarded", "for=\"[2001:db8::7]\";proto=http"),
+                          ("X-Forwarded-For", "203.0.113.1")]);
+    let client = proxies.resolve(peer, &chain).unwrap();
+    assert_eq!(client.ip(), Some(ip("192.0.2.60")));
+    assert_eq!(client.proto, Some("https".to_string()));
+    assert_eq!(client.host, Some("example.com".to_string()));
+
+    // every hop trusted: the leftmost is the client
+    let internal = headers(&[("Forwarded", "for=10.1.1.1, for=10.2.2.2")]);
+    assert_eq!(proxies.resolve(peer, &internal).unwrap().ip(), Some(ip("10.1.1.1")));
+
+    // unknown and obfuscated hops can not be walked past
+    let hidden = headers(&[("Forwarded", "for=192.0.2.1, for=_proxy1, for=10.1.1.1")]);
+    let client = proxies.resolve(peer, &hidden).unwrap();
+    assert_eq!(client.node.name, NodeName::Obfuscated("_proxy1".to_string()));
+    assert_eq!(client.ip(), None);
+    let missing_for = headers(&[("Forwarded", "for=192.0.2.1, proto=https")]);
+    let client = proxies.resolve(peer, &missing_for).unwrap();
+    assert_eq!(client.node.name, NodeName::Unknown);
+    assert_eq!(client.proto, Some("https".to_string()));
+
+    // X-Forwarded-Proto and X-Forwarded-Host entries match hops from the right
+    let xff = headers(&[("Host", "internal"), ("X-Forwarded-For", "1.1.1.1, 203.0.113.9, 10.1.2.3"),
+                        ("X-Forwarded-Proto", "http, https, http"), ("X-Forwarded-Host", "a, b, c")]);
+    let client = proxies.resolve(peer, &xff).unwrap();
+    assert_eq!(client, ClientInfo { node: Node::from(ip("203.0.113.9")), proto: Some("https".to_string()),
+                                    host: Some("b".to_string()) });
+    let short = headers(&[("Host", "internal"), ("X-Forwarded-For", "1.1.1.1, 203.0.113.9, 10.1.2.3"),
+                          ("X-Forwarded-Proto", "https, http")]);
+    let client = proxies.resolve(peer, &short).unwrap();
+    assert_eq!(client.proto, Some("https".to_string()));
+    assert_eq!(client.host, Some("internal".to_string()));
+
+    let real_ip = headers(&[("X-Real-IP", "2001:db9::1"), ("X-Forwarded-Proto", "https")]);
+    let client = proxies.resolve(ip("2001:db8::1"), &real_ip).unwrap();
+    assert_eq!(client.ip(), Some(ip("2001:db9::1")));
+    assert_eq!(client.proto, Some("https".to_string()));
+
+    let none = headers(&[("X-Forwarded-Proto", "https")]);
+    assert_eq!(proxies.resolve(peer, &none).unwrap(),
+               ClientInfo { node: Node::from(peer), proto: Some("https".to_string()), host: None });
+
+    // malformed headers from a trusted proxy are errors
+    assert_eq!(proxies.resolve(peer, &headers(&[("Forwarded", "for=\"::1\"")])), Err(ForwardedError::InvalidNode));
+    assert_eq!(proxies.resolve(peer, &headers(&[("X-Forwarded-For", "1.2.3.4, garbage")])),
+               Err(ForwardedError::InvalidNode));
+    assert_eq!(proxies.resolve(peer, &headers(&[("X-Real-IP", "nope")])), Err(ForwardedError::InvalidAddress));
+}
@@ -0,0 +1,143 @@
+extern crate http_parser;
+
+use http_parser::{HttpParserType, HttpMethod, HttpVersion, Message, Har, HarEntry, HarError};
+
+use helper::parse_configured;
+
+pub mod helper;
+
+fn parse(tp: HttpParserType, data: &[u8]) -> Vec<Message> {
+    parse_configured(tp, data, data.len(), |_| ()).1.messages
+}
+
+#[test]
+fn test_har_round_trip() {
+    let request = "POST /form?x=1&y HTTP/1.1\r\n\
+                   Host: example.com\r\n\
+                   Cookie: sid=abc; theme=dark\r\n\
+                   Content-Type: application/x-www-form-urlencoded\r\n\
+                   Transfer-Encoding: chunked\r\n\
+                   \r\n\
+                   5\r\na=b&c\r\n0\r\n\r\n";
+    let response = "HTTP/1.1 201 Created\r\n\
+                    Location: /form/1\r\n\
+                    Set-Cookie: sid=def; Path=/; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Secure; SameSite=Lax\r\n\
+                    Content-Length: 4\r\n\
+                    \r\n\
+                    done";
+
+    let req = parse(HttpParserType::Request, request.as_bytes()).remove(0);
+    let res = parse(HttpParserType::Response, response.as_bytes()).remove(0);
+    assert!(req.started.is_some() && req.completed.is_some());
+
+    let mut har = Har::new();
+    har.entries.push(HarEntry::new(req, Some(res)));
+
+    let json = har.to_json();
+    assert!(json.starts_with("{\"log\":{\"version\":\"1.2\""));
+    assert!(json.contains("\"queryString\":[{\"name\":\"x\",\"value\":\"1\"},{\"name\":\"y\",\"value\":\"\"}]"));
+    assert!(json.contains("\"redirectURL\":\"/form/1\""));
+    assert!(json.contains("\"cookies\":[{\"name\":\"sid\",\"value\":\"abc\"},{\"name\":\"theme\",\"value\":\"dark\"}]"));
+    assert!(json.contains("\"cookies\":[{\"name\":\"sid\",\"value\":\"def\",\"path\":\"/\",\
+                           \"expires\":\"1994-11-06T08:49:37.000Z\",\"httpOnly\":false,\"secure\":true,\
+                           \"sameSite\":\"Lax\"}]"));
+
+    let imported = Har::from_json(&json).unwrap();
+    assert_eq!(imported.entries.len(), 1);
+    let entry = &imported.entries[0];
+
+    let req = parse(HttpParserType::Request, &entry.request_bytes()).remove(0);
+    assert_eq!(req.method, Some(HttpMethod::Post));
+    assert_eq!(req.url, "http://example.com/form?x=1&y");
+    assert_eq!(req.body, b"a=b&c".to_vec());
+    assert!(req.headers.get("Transfer-Encoding").is_none());
+    assert_eq!(req.headers.get("Content-Length"), Some("5"));
+
+    let res = parse(HttpParserType::Response, &entry.response_bytes().unwrap()).remove(0);
+    assert_eq!(res.status_code, Some(201));
+    assert_eq!(res.status, "Created");
+    assert_eq!(res.body, b"done".to_vec());
+}
+
+#[test]
+fn test_har_binary_content() {
+    let mut res = Message {
+        status_code: Some(200),
+        status: "OK".to_string(),
+        http_version: HttpVersion { major: 1, minor: 1 },
+        body: vec![0, 159, 146, 150, 255],
+        ..Default::default()
+    };
+    res.headers.push("Content-Type", "application/octet-stream");
+
+    let req = Message {
+        method: Some(HttpMethod::Put),
+        url: "http://example.com/blob".to_string(),
+        http_version: HttpVersion { major: 1, minor: 1 },
+        body: vec![255, 254, 0, 1],
+        ..Default::default()
+    };
+
+    let mut har = Har::new();
+    har.entries.push(HarEntry::new(req.clone(), Some(res.clone())));
+    let json = har.to_json();
+    assert_eq!(json.matches("\"encoding\":\"base64\"").count(), 2);
+
+    let imported = Har::from_json(&json).unwrap();
+    assert_eq!(imported.entries[0].request.body, req.body);
+    assert_eq!(imported.entries[0].response.as_ref().unwrap().body, res.body);
+}
+
+#[test]
+fn test_har_import_http2_entry() {
+    let json = r#"{"log": {"version": "1.2", "creator": {"name": "devtools", "version": "1"},
+        "entries": [{
+            "startedDateTime": "2020-02-29T12:34:56.789+01:00",
+            "time": 12.5,
+            "request": {"method": "GET", "url": "https://example.com/é", "httpVersion": "h2",
+                "headers": [{"name": ":authority", "value": "example.com"},
+                            {"name": ":path", "value": "/"},
+                            {"name": "accept", "value": "*/*"}],
+                "cookies": [], "queryString": [], "headersSize": -1, "bodySize": 0},
+            "response": {"status": 204, "statusText": "", "httpVersion": "h2",
+                "headers": [], "cookies": [], "content": {"size": 0, "mimeType": ""},
+                "redirectURL": "", "headersSize": -1, "bodySize": 0},
+            "cache": {},
+            "timings": {"send": 1, "wait": 10, "receive": 1.5}
+        }]}}"#;
+
+    let har = Har::from_json(json).unwrap();
+    let entry = &har.entries[0];
+    assert_eq!(entry.timings.total(), 12.5);
+
+    let started = entry.started_date_time.unwrap();
+    let secs = started.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    assert_eq!(secs, 1582976096);
+
+    assert_eq!(String::from_utf8(entry.request_bytes()).unwrap(),
+               "GET https://example.com/\u{e9} HTTP/1.1\r\naccept: */*\r\nHost: example.com\r\n\r\n");
+    assert_eq!(String::from_utf8(entry.response_bytes().unwrap()).unwrap(),
+               "HTTP/1.1 204\r\n\r\n");
+}
+
+#[test]
+fn test_har_import_errors() {
+    assert_eq!(Har::from_json("{\"log\": "), Err(HarError::InvalidJson));
+    assert_eq!(Har::from_json("{}"), Err(HarError::MissingField("log")));
+
+    // nesting is limited instead of overflowing the stack
+    assert_eq!(Har::from_json(&"[".repeat(200000)), Err(HarError::InvalidJson));
+    let nested = |depth: usize| format!("{{\"x\": {}{}}}", "[".repeat(depth), "]".repeat(depth));
+    assert_eq!(Har::from_json(&nested(127)), Err(HarError::MissingField("log")));
+    assert_eq!(Har::from_json(&nested(128)), Err(HarError::InvalidJson));
+
+    let bad_method = r#"{"log": {"entries": [{"request": {"method": "BREW", "url": "/",
+        "httpVersion": "HTTP/1.1", "headers": []}, "response": {"status": 0}}]}}"#;
+    assert_eq!(Har::from_json(bad_method), Err(HarError::InvalidMethod));
+
+    let no_response = r#"{"log": {"entries": [{"request": {"method": "GET", "url": "/",
+        "httpVersion": "HTTP/1.0", "headers": []}, "response": {"status": 0}}]}}"#;
+    let har = Har::from_json(no_response).unwrap();
+    assert!(har.entries[0].response.is_none());
+    assert_eq!(har.entries[0].request_bytes(), b"GET / HTTP/1.0\r\n\r\n".to_vec());
+}
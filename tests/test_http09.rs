@@ -0,0 +1,58 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::parse_configured;
+
+pub mod helper;
+
+const HTTP09: HttpVersion = HttpVersion { major: 0, minor: 9 };
+
+fn parse(tp: HttpParserType, data: &[u8], chunk: usize) -> (HttpParser, MessageCollector) {
+    parse_configured(tp, data, chunk, |hp| hp.http09 = true)
+}
+
+#[test]
+fn test_http09_request() {
+    for &data in [&b"GET /index.html\r\n"[..], b"GET /index.html\n", b"GET /index.html \r\n"].iter() {
+        for &chunk in [data.len(), 1].iter() {
+            let (hp, cb) = parse(HttpParserType::Request, data, chunk);
+            assert_eq!(cb.messages.len(), 1);
+            assert_eq!(cb.messages[0].url, "/index.html");
+            assert_eq!(cb.messages[0].http_version, HTTP09);
+            assert!(cb.messages[0].headers.is_empty());
+            assert!(!hp.http_should_keep_alive());
+        }
+    }
+
+    // requests with a version still have headers
+    let (_, cb) = parse(HttpParserType::Request, b"GET / HTTP/1.1\r\nHost: a\r\n\r\n", 64);
+    assert_eq!(cb.messages[0].headers.get("Host"), Some("a"));
+
+    // without http09 the line after the request line is read as a header
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    let mut cb = MessageCollector::new();
+    hp.execute(&mut cb, b"GET /\r\nHost: a\r\n\r\n");
+    assert_eq!(cb.messages[0].http_version, HTTP09);
+    assert_eq!(cb.messages[0].headers.get("Host"), Some("a"));
+
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.execute(&mut cb, b"GET /index.html \r\n");
+    assert_eq!(hp.errno, Some(HttpErrno::InvalidConstant));
+}
+
+#[test]
+fn test_http09_response() {
+    let data = b"<html>\r\nHTTP/1.1 200 OK\r\n</html>\r\n";
+    for &chunk in [data.len(), 1, 5].iter() {
+        let (_, cb) = parse(HttpParserType::Response, data, chunk);
+        assert_eq!(cb.messages.len(), 1);
+        assert_eq!(cb.messages[0].http_version, HTTP09);
+        assert_eq!(cb.messages[0].status_code, None);
+        assert_eq!(cb.messages[0].body, data.to_vec());
+    }
+
+    // an empty stream is no message at all
+    let (_, cb) = parse(HttpParserType::Response, b"", 1);
+    assert!(cb.messages.is_empty());
+}
@@ -0,0 +1,121 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::parse_configured;
+
+pub mod helper;
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+// an empty SETTINGS frame
+const SETTINGS_FRAME: &[u8] = b"\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+
+#[derive(Default)]
+struct Callback {
+    completed: usize,
+    tunnel: Vec<u8>,
+}
+
+impl HttpParserCallback for Callback {
+    fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.completed += 1;
+        Ok(ParseAction::None)
+    }
+
+    fn on_tunnel_data(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.tunnel.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+}
+
+#[test]
+fn test_http2_preface() {
+    let mut data = PREFACE.to_vec();
+    data.extend_from_slice(SETTINGS_FRAME);
+
+    for &tp in [HttpParserType::Request, HttpParserType::Both].iter() {
+        let mut hp = HttpParser::new(tp);
+        let mut cb = Callback::default();
+        let parsed = hp.execute(&mut cb, &data);
+        assert!(hp.errno.is_none());
+        assert_eq!(parsed, PREFACE.len());
+        assert!(hp.is_http2_preface());
+        assert!(hp.upgrade);
+        assert_eq!(hp.method, Some(HttpMethod::Pri));
+        assert_eq!(hp.http_version, HttpVersion { major: 2, minor: 0 });
+        assert_eq!(cb.completed, 1);
+    }
+
+    // byte by byte, with tunnel mode passing the frames on
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.tunnel = true;
+    let mut cb = Callback::default();
+    for b in data.iter() {
+        assert_eq!(hp.execute(&mut cb, &[*b]), 1);
+    }
+    assert!(hp.is_http2_preface());
+    assert_eq!(cb.tunnel, SETTINGS_FRAME.to_vec());
+
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.execute(&mut Callback::default(), b"GET / HTTP/1.1\r\n\r\n");
+    assert!(!hp.is_http2_preface());
+}
+
+#[test]
+fn test_http2_preface_errors() {
+    let cases: [(&[u8], HttpErrno); 5] = [
+        (b"PRI / HTTP/2.0\r\n\r\nSM\r\n\r\n", HttpErrno::InvalidUrl),
+        (b"PRI * HTTP/1.1\r\n\r\nSM\r\n\r\n", HttpErrno::InvalidVersion),
+        (b"PRI * HTTP/2.0\r\nHost: a\r\n\r\nSM\r\n\r\n", HttpErrno::InvalidConstant),
+        (b"PRI * HTTP/2.0\r\n\r\nSX\r\n\r\n", HttpErrno::InvalidConstant),
+        (b"PRY * HTTP/2.0\r\n\r\nSM\r\n\r\n", HttpErrno::InvalidMethod),
+    ];
+    for &(data, err) in cases.iter() {
+        let mut hp = HttpParser::new(HttpParserType::Request);
+        hp.execute(&mut Callback::default(), data);
+        assert_eq!(hp.errno, Some(err), "{}", String::from_utf8_lossy(data));
+    }
+}
+
+fn parse(data: &str) -> Message {
+    parse_configured(HttpParserType::Both, data.as_bytes(), data.len(), |_| ()).1.messages.remove(0)
+}
+
+#[test]
+fn test_h2c_upgrade() {
+    let request = "GET /index.html HTTP/1.1\r\n\
+                   Host: server.example.com\r\n\
+                   Connection: Upgrade, HTTP2-Settings\r\n\
+                   Upgrade: h2c\r\n\
+                   HTTP2-Settings: AAEAABAAAAIAAAAAAAUAAEAAAAf__wAB\r\n\
+                   \r\n";
+    let upgrade = H2cUpgrade::from_request(&parse(request)).unwrap();
+    assert_eq!(upgrade.settings, Http2Settings {
+        header_table_size: Some(4096),
+        enable_push: Some(false),
+        max_frame_size: Some(16384),
+        unknown: vec![(7, 0xffff0001)],
+        ..Default::default()
+    });
+
+    let response = parse("HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: h2c\r\n\r\n");
+    assert_eq!(upgrade.validate_response(&response), Ok(()));
+    let declined = parse("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+    assert_eq!(upgrade.validate_response(&declined), Err(Http2Error::InvalidStatus));
+
+    let cases = [
+        (request.replace("h2c", "websocket"), Http2Error::MissingUpgrade),
+        (request.replace("Upgrade, HTTP2-Settings", "Upgrade"), Http2Error::MissingConnectionOptions),
+        (request.replace("HTTP2-Settings: AAEAABAAAAIAAAAAAAUAAEAAAAf__wAB\r\n", ""), Http2Error::MissingSettings),
+        (request.replace("AAEAABAAAAIAAAAAAAUAAEAAAAf__wAB", "AAEAABAAAAIAAAAAAAUAAEAAAAf//wAB"),
+         Http2Error::InvalidBase64),
+        (request.replace("AAEAABAAAAIAAAAAAAUAAEAAAAf__wAB", "AAEAABAAAA"), Http2Error::InvalidSettingsLength),
+        // SETTINGS_ENABLE_PUSH = 2
+        (request.replace("AAEAABAAAAIAAAAAAAUAAEAAAAf__wAB", "AAIAAAAC"), Http2Error::InvalidSettingValue(2)),
+        // SETTINGS_MAX_FRAME_SIZE = 16383
+        (request.replace("AAEAABAAAAIAAAAAAAUAAEAAAAf__wAB", "AAUAAD__"), Http2Error::InvalidSettingValue(5)),
+    ];
+    for &(ref request, err) in cases.iter() {
+        assert_eq!(H2cUpgrade::from_request(&parse(request)), Err(err), "{}", request);
+    }
+}
@@ -0,0 +1,85 @@
+extern crate http_parser;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http_parser::*;
+
+#[test]
+fn test_http_date_formats() {
+    // 1994-11-06 08:49:37 UTC
+    let time = UNIX_EPOCH + Duration::from_secs(784111777);
+    let dates = ["Sun, 06 Nov 1994 08:49:37 GMT", "Sunday, 06-Nov-94 08:49:37 GMT",
+                 "Sun Nov  6 08:49:37 1994", "Sun Nov 06 08:49:37 1994", " sun, 06 NOV 1994 08:49:37 GMT\t"];
+    for date in dates.iter() {
+        let parsed = HttpDate::parse(date.as_bytes()).unwrap();
+        assert_eq!(SystemTime::from(parsed), time, "{}", date);
+        assert_eq!(parsed.to_string(), "Sun, 06 Nov 1994 08:49:37 GMT");
+    }
+    assert_eq!(HttpDate::from(time + Duration::from_millis(999)), "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap());
+
+    let leap_day: HttpDate = "Thu, 29 Feb 1996 23:59:60 GMT".parse().unwrap();
+    assert_eq!(leap_day.to_string(), "Fri, 01 Mar 1996 00:00:00 GMT");
+    let before_epoch = HttpDate::from(UNIX_EPOCH - Duration::from_millis(1500));
+    assert_eq!(before_epoch.to_string(), "Wed, 31 Dec 1969 23:59:58 GMT");
+    assert_eq!(SystemTime::from(before_epoch), UNIX_EPOCH - Duration::from_secs(2));
+    assert!(before_epoch < leap_day);
+}
+
+#[test]
+fn test_http_date_errors() {
+    let invalid = ["", "Sun, 06 Nov 1994 08:49:37 UTC", "Sun, 6 Nov 1994 08:49:37 GMT",
+                   "Xyz, 06 Nov 1994 08:49:37 GMT", "Sun, 06 Nox 1994 08:49:37 GMT",
+                   "Sun, 06 Nov 1994 08-49-37 GMT", "Sun, 06 Nov 1994 08:49:37 GMT x",
+                   "Sun, 06-Nov-94 08:49:37 GMT", "Sunday, 06 Nov 1994 08:49:37 GMT",
+                   "Sun Nov 6 08:49:37 1994", "1994-11-06T08:49:37Z", "784111777"];
+    for date in invalid.iter() {
+        assert_eq!(HttpDate::parse(date.as_bytes()), Err(HttpDateError::InvalidFormat), "{}", date);
+    }
+    let out_of_range = ["Wed, 29 Feb 1995 00:00:00 GMT", "Sun, 00 Nov 1994 08:49:37 GMT",
+                        "Sun, 06 Nov 1994 24:00:00 GMT", "Sun Nov 31 08:60:37 1994"];
+    for date in out_of_range.iter() {
+        assert_eq!(HttpDate::parse(date.as_bytes()), Err(HttpDateError::InvalidDate), "{}", date);
+    }
+}
+
+#[test]
+fn test_retry_after() {
+    let now = UNIX_EPOCH + Duration::from_secs(784111777);
+    let retry = RetryAfter::parse(b" 120 ").unwrap();
+    assert_eq!(retry, RetryAfter::Delay(120));
+    assert_eq!(retry.delay(now), Duration::from_secs(120));
+    assert_eq!(retry.to_string(), "120");
+
+    let retry = RetryAfter::parse(b"Sun, 06 Nov 1994 08:50:07 GMT").unwrap();
+    assert_eq!(retry.delay(now), Duration::from_secs(30));
+    assert_eq!(retry.delay(now + Duration::from_secs(60)), Duration::from_secs(0));
+    assert_eq!(retry.to_string(), "Sun, 06 Nov 1994 08:50:07 GMT");
+
+    assert_eq!(RetryAfter::parse(b"-1"), Err(HttpDateError::InvalidFormat));
+}
+
+#[test]
+fn test_header_dates() {
+    let mut cb = MessageCollector::new();
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    hp.execute(&mut cb, b"HTTP/1.1 200 OK\r\nDate: Sun Nov  6 08:49:37 1994\r\n\
+                          Last-Modified: Saturday, 05-Nov-94 08:49:37 GMT\r\nExpires: 0\r\n\
+                          Content-Length: 0\r\n\r\n");
+    let headers = &cb.messages[0].headers;
+    let date = headers.http_date("Date").unwrap();
+    let last_modified = headers.http_date("last-modified").unwrap();
+    assert_eq!(SystemTime::from(date).duration_since(SystemTime::from(last_modified)).unwrap(),
+               Duration::from_secs(86400));
+    assert_eq!(headers.http_date("Expires"), None);
+    assert_eq!(headers.http_date("If-Modified-Since"), None);
+
+    // an invalid Expires makes the response stale; without it the obsolete formats
+    // give the heuristic lifetime
+    let mut response = cb.messages[0].clone();
+    let time = SystemTime::from(date);
+    assert_eq!(Freshness::evaluate(&response, time, time, time, false).lifetime, Duration::from_secs(0));
+    response.headers.remove("Expires");
+    let freshness = Freshness::evaluate(&response, time, time, time, false);
+    assert!(freshness.heuristic);
+    assert_eq!(freshness.lifetime, Duration::from_secs(8640));
+}
@@ -0,0 +1,169 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::execute_chunks;
+
+pub mod helper;
+
+const REQ_HDR: &str = "GET /origin-resource HTTP/1.1\r\n\
+                       Host: www.origin-server.com\r\n\
+                       Accept: text/html, text/plain, image/gif\r\n\
+                       \r\n";
+const RES_HDR: &str = "HTTP/1.1 200 OK\r\n\
+                       Server: Apache/1.3.6 (Unix)\r\n\
+                       Content-Type: text/plain\r\n\
+                       Content-Length: 51\r\n\
+                       \r\n";
+const BODY: &str = "33\r\nThis is data that was returned by an origin server.\r\n0\r\n\r\n";
+
+fn icap_request(method: &str, sections: &[(&str, &str)]) -> Vec<u8> {
+    let mut offset = 0;
+    let mut encapsulated = Vec::new();
+    let mut body = String::new();
+    for &(name, data) in sections.iter() {
+        encapsulated.push(format!("{}={}", name, offset));
+        offset += data.len();
+        body.push_str(data);
+    }
+    format!("{} icap://icap.example.org/satisf ICAP/1.0\r\n\
+             Host: icap.example.org\r\n\
+             Encapsulated: {}\r\n\
+             \r\n{}", method, encapsulated.join(", "), body).into_bytes()
+}
+
+fn parse(tp: HttpParserType, data: &[u8], chunk: usize) -> Vec<IcapMessage> {
+    let mut parser = IcapParser::new(tp);
+    let mut cb = IcapCollector::new();
+    execute_chunks(data, chunk, |part| parser.execute(&mut cb, part));
+    assert_eq!(parser.errno, None);
+    cb.messages
+}
+
+#[test]
+fn test_icap_reqmod() {
+    let post = "POST /upload HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n";
+    let mut data = icap_request("REQMOD", &[("req-hdr", post), ("req-body", "5\r\nhello\r\n0\r\n\r\n")]);
+    data.extend_from_slice(&icap_request("REQMOD", &[("req-hdr", REQ_HDR), ("null-body", "")]));
+
+    for &chunk in [data.len(), 1, 7].iter() {
+        let messages = parse(HttpParserType::Request, &data, chunk);
+        assert_eq!(messages.len(), 2);
+
+        assert_eq!(messages[0].head.method, Some(HttpMethod::ReqMod));
+        assert_eq!(messages[0].head.protocol, Protocol::Icap);
+        assert_eq!(messages[0].head.url, "icap://icap.example.org/satisf");
+        let request = messages[0].request.as_ref().unwrap();
+        assert_eq!(request.method, Some(HttpMethod::Post));
+        assert_eq!(request.url, "/upload");
+        assert_eq!(request.body, b"hello".to_vec());
+        assert_eq!(messages[0].response, None);
+
+        let request = messages[1].request.as_ref().unwrap();
+        assert_eq!(request.url, "/origin-resource");
+        assert_eq!(request.headers.get("accept"), Some("text/html, text/plain, image/gif"));
+        assert!(request.body.is_empty());
+    }
+}
+
+#[test]
+fn test_icap_respmod() {
+    let data = icap_request("RESPMOD", &[("req-hdr", REQ_HDR), ("res-hdr", RES_HDR), ("res-body", BODY)]);
+
+    for &chunk in [data.len(), 1, 13].iter() {
+        let messages = parse(HttpParserType::Request, &data, chunk);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].head.method, Some(HttpMethod::RespMod));
+        assert_eq!(messages[0].request.as_ref().unwrap().url, "/origin-resource");
+        let response = messages[0].response.as_ref().unwrap();
+        assert_eq!(response.status_code, Some(200));
+        assert_eq!(response.headers.get("Content-Length"), Some("51"));
+        assert_eq!(response.body, b"This is data that was returned by an origin server.".to_vec());
+    }
+
+    // a response body without its head
+    let data = icap_request("RESPMOD", &[("req-hdr", REQ_HDR), ("res-body", BODY)]);
+    let messages = parse(HttpParserType::Request, &data, data.len());
+    assert_eq!(messages[0].response.as_ref().unwrap().status_code, None);
+    assert_eq!(messages[0].response.as_ref().unwrap().body.len(), 51);
+
+    // the ICAP response carries the adapted message the same way
+    let data = format!("ICAP/1.0 200 OK\r\n\
+                        ISTag: \"W3E4R7U9-L2E4-2\"\r\n\
+                        Encapsulated: res-hdr=0, res-body={}\r\n\
+                        \r\n{}{}\
+                        ICAP/1.0 204 No Content\r\n\
+                        Encapsulated: null-body=0\r\n\r\n", RES_HDR.len(), RES_HDR, BODY);
+    let messages = parse(HttpParserType::Response, data.as_bytes(), 1);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].head.status_code, Some(200));
+    assert_eq!(messages[0].head.headers.get("ISTag"), Some("\"W3E4R7U9-L2E4-2\""));
+    assert_eq!(messages[0].response.as_ref().unwrap().body.len(), 51);
+    assert_eq!(messages[1].head.status_code, Some(204));
+    assert_eq!(messages[1].request, None);
+    assert_eq!(messages[1].response, None);
+}
+
+#[test]
+fn test_icap_options() {
+    let data = b"OPTIONS icap://icap.server.net/sample-service ICAP/1.0\r\n\
+                 Host: icap.server.net\r\n\r\n\
+                 OPTIONS icap://icap.server.net/sample-service ICAP/1.0\r\n\
+                 Host: icap.server.net\r\n\r\n";
+    let messages = parse(HttpParserType::Request, data, data.len());
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].head.method, Some(HttpMethod::Options));
+    assert_eq!(messages[1].request, None);
+
+    let data = b"ICAP/1.0 200 OK\r\n\
+                 Methods: RESPMOD\r\n\
+                 Encapsulated: opt-body=0\r\n\r\n\
+                 6\r\nhello\n\r\n0\r\n\r\n";
+    let messages = parse(HttpParserType::Response, data, 3);
+    assert_eq!(messages[0].head.headers.get("Methods"), Some("RESPMOD"));
+    assert_eq!(messages[0].options_body, b"hello\n".to_vec());
+    assert_eq!(messages[0].response, None);
+}
+
+#[test]
+fn test_icap_errors() {
+    let cases = [
+        ("res-body=0, res-hdr=20", IcapError::InvalidEncapsulated),
+        ("req-hdr=0", IcapError::InvalidEncapsulated),
+        ("req-hdr=1, null-body=40", IcapError::InvalidEncapsulated),
+        ("res-hdr=0, req-hdr=40, null-body=80", IcapError::InvalidEncapsulated),
+        ("req-hdr=0, null-body=0", IcapError::InvalidEncapsulated),
+        ("req-hdr=0, null-body=+4", IcapError::InvalidEncapsulated),
+        ("req-hdr=0, nul-body=40", IcapError::InvalidEncapsulated),
+    ];
+    for &(value, err) in cases.iter() {
+        assert_eq!(parse_encapsulated(value), Err(err), "{}", value);
+    }
+    assert_eq!(parse_encapsulated("null-body=0"), Ok(vec![(IcapSection::NullBody, 0)]));
+
+    // offsets that cut the head short or run past its end
+    let request = String::from_utf8(icap_request("REQMOD", &[("req-hdr", REQ_HDR), ("null-body", "")])).unwrap();
+    let offset = format!("null-body={}", REQ_HDR.len());
+    let cases: [(Vec<u8>, IcapError); 4] = [
+        (request.replace(&offset, &format!("null-body={}", REQ_HDR.len() - 2)).into_bytes(),
+         IcapError::InvalidSectionLength),
+        (request.replace(&offset, &format!("null-body={}", REQ_HDR.len() + 2)).into_bytes(),
+         IcapError::InvalidSectionLength),
+        (icap_request("REQMOD", &[("req-hdr", "GET / HTTP/1.1\r\nHost a\r\n\r\n"), ("null-body", "")]),
+         IcapError::Http(HttpErrno::InvalidHeaderToken)),
+        (icap_request("REQMOD", &[("req-hdr", REQ_HDR), ("req-body", "x\r\n")]),
+         IcapError::Http(HttpErrno::InvalidChunkSize)),
+    ];
+    for &(ref data, err) in cases.iter() {
+        let mut parser = IcapParser::new(HttpParserType::Request);
+        parser.execute(&mut IcapCollector::new(), data);
+        assert_eq!(parser.errno, Some(err), "{}", String::from_utf8_lossy(data));
+    }
+
+    // EOF in the middle of a body
+    let data = icap_request("REQMOD", &[("req-hdr", REQ_HDR), ("req-body", "5\r\nhel")]);
+    let mut parser = IcapParser::new(HttpParserType::Request);
+    assert_eq!(parser.execute(&mut IcapCollector::new(), &data), data.len());
+    parser.execute(&mut IcapCollector::new(), &[]);
+    assert_eq!(parser.errno, Some(IcapError::UnexpectedEof));
+}
@@ -0,0 +1,107 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::feed;
+
+pub mod helper;
+
+struct Callback {
+    interim: Vec<u16>,
+    finals: Vec<u16>,
+    informational_at_headers: Vec<bool>,
+}
+
+impl HttpParserCallback for Callback {
+    fn on_headers_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.informational_at_headers.push(parser.is_informational());
+        if parser.is_informational() {
+            assert!(parser.http_should_keep_alive());
+        }
+        Ok(ParseAction::None)
+    }
+
+    fn on_informational(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.interim.push(parser.status_code.unwrap());
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        self.finals.push(parser.status_code.unwrap());
+        Ok(ParseAction::None)
+    }
+}
+
+fn parse(data: &str) -> (HttpParser, Callback) {
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    let mut cb = Callback { interim: vec![], finals: vec![], informational_at_headers: vec![] };
+    feed(&mut hp, &mut cb, data.as_bytes(), data.len());
+    (hp, cb)
+}
+
+#[test]
+fn test_100_continue() {
+    let (hp, cb) = parse("HTTP/1.1 100 Continue\r\n\
+                          \r\n\
+                          HTTP/1.1 200 OK\r\n\
+                          Content-Length: 2\r\n\
+                          \r\n\
+                          ok");
+    assert_eq!(cb.interim, vec![100]);
+    assert_eq!(cb.finals, vec![200]);
+    assert_eq!(cb.informational_at_headers, vec![true, false]);
+    assert!(hp.http_should_keep_alive());
+}
+
+#[test]
+fn test_interim_on_http10_connection() {
+    // An HTTP/1.0 interim response must not close the connection before the final one
+    let (_, cb) = parse("HTTP/1.0 100 Continue\r\n\
+                         \r\n\
+                         HTTP/1.0 102 Processing\r\n\
+                         \r\n\
+                         HTTP/1.0 204 No Content\r\n\
+                         \r\n");
+    assert_eq!(cb.interim, vec![100, 102]);
+    assert_eq!(cb.finals, vec![204]);
+}
+
+#[test]
+fn test_103_early_hints() {
+    let raw = "HTTP/1.1 103 Early Hints\r\n\
+               Link: </style.css>; rel=preload; as=style\r\n\
+               Link: </script.js>; rel=preload; as=script\r\n\
+               \r\n\
+               HTTP/1.1 200 OK\r\n\
+               Content-Length: 0\r\n\
+               \r\n";
+    let mut hp = HttpParser::new(HttpParserType::Both);
+    let mut cb = MessageCollector::new();
+    assert_eq!(hp.execute(&mut cb, raw.as_bytes()), raw.len());
+
+    assert_eq!(cb.informational.len(), 1);
+    assert_eq!(cb.informational[0].status_code, Some(103));
+    assert_eq!(cb.informational[0].headers.get_all("link"),
+               vec!["</style.css>; rel=preload; as=style", "</script.js>; rel=preload; as=script"]);
+
+    assert_eq!(cb.messages.len(), 1);
+    assert_eq!(cb.messages[0].status_code, Some(200));
+    assert!(cb.messages[0].headers.get("Link").is_none());
+}
+
+#[test]
+fn test_101_is_final() {
+    let raw = "HTTP/1.1 101 Switching Protocols\r\n\
+               Connection: Upgrade\r\n\
+               Upgrade: websocket\r\n\
+               \r\n\
+               frames";
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    let mut cb = Callback { interim: vec![], finals: vec![], informational_at_headers: vec![] };
+    let parsed = hp.execute(&mut cb, raw.as_bytes());
+
+    assert!(hp.upgrade);
+    assert_eq!(&raw[parsed..], "frames");
+    assert!(cb.interim.is_empty());
+    assert_eq!(cb.finals, vec![101]);
+}
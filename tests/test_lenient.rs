@@ -0,0 +1,93 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::parse_with;
+
+pub mod helper;
+
+
+#[test]
+fn test_lenient_checks() {
+    let cases: [(&[u8], HttpParserType, Lenient, HttpErrno); 10] = [
+        (b"GET / HTTP/1.1\r\nX Header: 1\r\n\r\n", HttpParserType::Request,
+         Lenient::HEADERS, HttpErrno::InvalidHeaderToken),
+        (b"POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+         HttpParserType::Request, Lenient::CHUNKED_LENGTH, HttpErrno::UnexpectedContentLength),
+        (b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\r\n", HttpParserType::Request,
+         Lenient::TRANSFER_ENCODING, HttpErrno::InvalidTransferEncoding),
+        (b"GET / HTTX/1.1\r\n\r\n", HttpParserType::Request, Lenient::VERSION, HttpErrno::Strict),
+        (b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\rX", HttpParserType::Response,
+         Lenient::OPTIONAL_LF_AFTER_CR, HttpErrno::Strict),
+        (b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\naXX0\r\n\r\n", HttpParserType::Response,
+         Lenient::OPTIONAL_CRLF_AFTER_CHUNK, HttpErrno::Strict),
+        (b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1 \r\na\r\n0\r\n\r\n", HttpParserType::Response,
+         Lenient::SPACES_AFTER_CHUNK_SIZE, HttpErrno::InvalidChunkSize),
+        (b"GET / HTTP/1.1\r\nConnection: close\r\n\r\nGET / HTTP/1.1\r\n\r\n", HttpParserType::Request,
+         Lenient::KEEP_ALIVE, HttpErrno::ClosedConnection),
+        (b"GET / HTTP/1.1\r\nConnection: close\r\n\r\nGET / HTTP/1.1\r\n\r\n", HttpParserType::Request,
+         Lenient::DATA_AFTER_CLOSE, HttpErrno::ClosedConnection),
+        (b"GET /a\tb HTTP/1.1\r\n\r\n", HttpParserType::Request, Lenient::URL, HttpErrno::InvalidUrl),
+    ];
+    for &(data, tp, flag, err) in cases.iter() {
+        let name = String::from_utf8_lossy(data);
+        let (hp, _) = parse_with(Lenient::empty(), tp, data);
+        assert_eq!(hp.errno, Some(err), "{}", name);
+
+        // the flag relaxes the check
+        let (hp, cb) = parse_with(flag, tp, data);
+        assert_eq!(hp.errno, None, "{}", name);
+        assert!(!cb.messages.is_empty(), "{}", name);
+
+        // and non-strict parsing relaxes all of them
+        let mut hp = HttpParser::new(tp);
+        hp.strict = false;
+        hp.execute(&mut MessageCollector::new(), data);
+        assert_eq!(hp.errno, None, "{}", name);
+    }
+}
+
+#[test]
+fn test_lenient_semantics() {
+    // chunked wins over Content-Length
+    let data = b"POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nab\r\n0\r\n\r\n";
+    let (_, cb) = parse_with(Lenient::CHUNKED_LENGTH, HttpParserType::Request, data);
+    assert_eq!(cb.messages[0].body, b"ab".to_vec());
+
+    // the connection stays usable after Connection: close, or the rest is dropped
+    let data = b"GET /a HTTP/1.1\r\nConnection: close\r\n\r\nGET /b HTTP/1.1\r\n\r\n";
+    let (_, cb) = parse_with(Lenient::KEEP_ALIVE, HttpParserType::Request, data);
+    assert_eq!(cb.messages.len(), 2);
+    let (hp, cb) = parse_with(Lenient::DATA_AFTER_CLOSE, HttpParserType::Request, data);
+    assert_eq!(cb.messages.len(), 1);
+    assert!(!hp.http_should_keep_alive());
+
+    // chunked as the final coding of a list frames the body
+    let data = b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\n\r\n2\r\nab\r\n0\r\n\r\n";
+    let (hp, cb) = parse_with(Lenient::empty(), HttpParserType::Request, data);
+    assert_eq!(hp.errno, None);
+    assert_eq!(cb.messages[0].body, b"ab".to_vec());
+    let data = b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: ,chunked \t\r\n\r\n0\r\n\r\n";
+    let (hp, cb) = parse_with(Lenient::empty(), HttpParserType::Request, data);
+    assert_eq!(hp.errno, None);
+    assert_eq!(cb.messages.len(), 1);
+    let lists: [&[u8]; 4] = [b"chunked, gzip", b"chunked\r\nTransfer-Encoding: gzip", b"chunkedx", b"chunk"];
+    for list in lists.iter() {
+        let mut data = b"POST / HTTP/1.1\r\nTransfer-Encoding: ".to_vec();
+        data.extend_from_slice(list);
+        data.extend_from_slice(b"\r\n\r\n");
+        let (hp, _) = parse_with(Lenient::empty(), HttpParserType::Request, &data);
+        assert_eq!(hp.errno, Some(HttpErrno::InvalidTransferEncoding), "{}", String::from_utf8_lossy(list));
+    }
+
+    // spaces after the chunk size are allowed by default
+    let hp = HttpParser::new(HttpParserType::Request);
+    assert_eq!(hp.lenient, Lenient::SPACES_AFTER_CHUNK_SIZE);
+
+    let mut lenient = Lenient::empty();
+    assert!(lenient.is_empty());
+    lenient |= Lenient::HEADERS | Lenient::VERSION;
+    lenient.remove(Lenient::HEADERS);
+    assert_eq!(lenient, Lenient::VERSION);
+    assert!(Lenient::all().contains(lenient));
+}
@@ -0,0 +1,60 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+#[test]
+fn test_media_type_parse() {
+    let media_type = MediaType::parse(b"multipart/Form-Data ;BOUNDARY=\"a \\\"quoted\\\\ b\";; charset=utf-8 ;").unwrap();
+    assert_eq!(media_type.essence(), "multipart/form-data");
+    assert!(media_type.is("Multipart/FORM-data"));
+    assert!(!media_type.is("multipart"));
+    assert_eq!(media_type.boundary(), Some("a \"quoted\\ b"));
+    assert_eq!(media_type.param("Charset"), Some("utf-8"));
+    assert_eq!(media_type.suffix(), None);
+    assert_eq!(media_type.params().len(), 2);
+    assert_eq!(media_type.to_string(), "multipart/form-data; boundary=\"a \\\"quoted\\\\ b\"; charset=utf-8");
+    assert_eq!(media_type.to_string().parse(), Ok(media_type));
+
+    let mut media_type = MediaType::new("application", "vnd.api+json").unwrap();
+    assert_eq!(media_type.suffix(), Some("json"));
+    media_type.set_param("charset", "utf-8");
+    media_type.set_param("Charset", "latin1");
+    assert_eq!(media_type.to_string(), "application/vnd.api+json; charset=latin1");
+    assert_eq!(MediaType::new("text", "html text").err(), Some(MediaTypeError::InvalidType));
+
+    let errors: [(&[u8], MediaTypeError); 8] = [
+        (b"", MediaTypeError::InvalidType),
+        (b"text", MediaTypeError::InvalidType),
+        (b"text/", MediaTypeError::InvalidType),
+        (b"/html", MediaTypeError::InvalidType),
+        (b"text/html; charset", MediaTypeError::InvalidParameter),
+        (b"text/html; charset=", MediaTypeError::InvalidParameter),
+        (b"text/html; charset=\"utf-8", MediaTypeError::UnterminatedQuote),
+        (b"text/html, text/plain", MediaTypeError::InvalidParameter),
+    ];
+    for &(value, err) in errors.iter() {
+        assert_eq!(MediaType::parse(value), Err(err), "{}", String::from_utf8_lossy(value));
+    }
+}
+
+#[test]
+fn test_media_type_matches() {
+    let media_type: MediaType = "text/html; charset=UTF-8; level=1".parse().unwrap();
+    for range in ["*/*", "text/*", "text/html", "text/html; charset=utf-8", "TEXT/HTML;level=1"].iter() {
+        assert!(media_type.matches(&range.parse().unwrap()), "{}", range);
+    }
+    for range in ["image/*", "text/plain", "text/html; level=2", "text/html; LEVEL=1; q=1"].iter() {
+        assert!(!media_type.matches(&range.parse().unwrap()), "{}", range);
+    }
+
+    let mut message = Message::default();
+    message.headers.push("content-type", "Text/HTML;Charset=\"utf-8\"");
+    assert_eq!(message.headers.content_type().unwrap().charset(), Some("utf-8"));
+    message.headers.remove("Content-Type");
+    message.headers.push("Content-Type", "invalid");
+    assert_eq!(message.headers.content_type(), None);
+
+    assert_eq!(multipart_boundary("multipart/mixed; boundary=\"simple boundary\""), Ok("simple boundary".to_string()));
+    assert_eq!(multipart_boundary("text/plain; boundary=x"), Err(MultipartError::MissingBoundary));
+    assert_eq!(multipart_boundary("multipart/mixed; boundary=\"x"), Err(MultipartError::MissingBoundary));
+}
@@ -0,0 +1,187 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+#[derive(Default, Debug, PartialEq)]
+struct Part {
+    headers: Vec<(String, String)>,
+    data: Vec<u8>,
+    complete: bool,
+}
+
+#[derive(Default)]
+struct Collector {
+    parts: Vec<Part>,
+}
+
+impl MultipartCallback for Collector {
+    fn on_part_begin(&mut self, _: &mut MultipartParser) -> CallbackResult {
+        self.parts.push(Part::default());
+        Ok(ParseAction::None)
+    }
+
+    fn on_part_header(&mut self, _: &mut MultipartParser, name: &[u8], value: &[u8]) -> CallbackResult {
+        let part = self.parts.last_mut().unwrap();
+        part.headers.push((String::from_utf8_lossy(name).into_owned(), String::from_utf8_lossy(value).into_owned()));
+        Ok(ParseAction::None)
+    }
+
+    fn on_part_data(&mut self, _: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+        self.parts.last_mut().unwrap().data.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_part_end(&mut self, _: &mut MultipartParser) -> CallbackResult {
+        self.parts.last_mut().unwrap().complete = true;
+        Ok(ParseAction::None)
+    }
+}
+
+const BODY: &[u8] = b"preamble --AaB03x is ignored\r\n\
+                      --AaB03x  \r\n\
+                      Content-Disposition: form-data; name=\"submit-name\"\r\n\
+                      \r\n\
+                      Larry\r\n\
+                      --AaB03x\r\n\
+                      Content-Disposition: form-data; name=\"files\"; filename=\"file1.txt\"\r\n\
+                      Content-Type:text/plain \r\n\
+                      \r\n\
+                      \r\n--AaB03 \r\r\n-- almost a boundary\r\n--AaB03x\r\n\
+                      \r\n\
+                      \r\n\
+                      --AaB03x--\r\n\
+                      epilogue\r\n--AaB03x\r\n";
+
+fn check_parts(parts: &[Part]) {
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0].headers, vec![("Content-Disposition".to_string(), "form-data; name=\"submit-name\"".to_string())]);
+    assert_eq!(parts[0].data, b"Larry".to_vec());
+    assert_eq!(parts[1].headers[1], ("Content-Type".to_string(), "text/plain".to_string()));
+    assert_eq!(parts[1].data, b"\r\n--AaB03 \r\r\n-- almost a boundary".to_vec());
+    assert!(parts[2].headers.is_empty());
+    assert!(parts[2].data.is_empty());
+    assert!(parts.iter().all(|p| p.complete));
+}
+
+#[test]
+fn test_multipart() {
+    for split in 1..BODY.len() {
+        let mut parser = MultipartParser::new("AaB03x").unwrap();
+        let mut cb = Collector::default();
+        assert_eq!(parser.execute(&mut cb, &BODY[..split]), split);
+        assert_eq!(parser.execute(&mut cb, &BODY[split..]), BODY.len() - split);
+        parser.execute(&mut cb, &[]);
+        assert_eq!(parser.errno, None, "split at {}", split);
+        assert!(parser.is_done());
+        check_parts(&cb.parts);
+    }
+
+    let mut parser = MultipartParser::new("AaB03x").unwrap();
+    let mut cb = Collector::default();
+    for b in BODY {
+        assert_eq!(parser.execute(&mut cb, &[*b]), 1);
+    }
+    check_parts(&cb.parts);
+}
+
+#[test]
+fn test_multipart_on_body() {
+    struct Upload {
+        parser: Option<MultipartParser>,
+        cb: Collector,
+    }
+
+    impl HttpParserCallback for Upload {
+        fn on_header_value(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+            let value = String::from_utf8_lossy(data);
+            if value.starts_with("multipart/") {
+                self.parser = Some(MultipartParser::from_content_type(&value).map_err(|e| e.to_string())?);
+            }
+            Ok(ParseAction::None)
+        }
+
+        fn on_body(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+            let parser = self.parser.as_mut().unwrap();
+            parser.execute(&mut self.cb, data);
+            Ok(ParseAction::None)
+        }
+
+        fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+            let parser = self.parser.as_mut().unwrap();
+            parser.execute(&mut self.cb, &[]);
+            parser.errno.map_or(Ok(ParseAction::None), |e| Err(e.to_string()))
+        }
+    }
+
+    let mut request = format!("POST /upload HTTP/1.1\r\n\
+                               Content-Type: multipart/form-data; boundary=\"AaB03x\"\r\n\
+                               Content-Length: {}\r\n\r\n", BODY.len()).into_bytes();
+    request.extend_from_slice(BODY);
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    let mut cb = Upload { parser: None, cb: Collector::default() };
+    assert_eq!(hp.execute(&mut cb, &request), request.len());
+    assert_eq!(hp.errno, None);
+    check_parts(&cb.cb.parts);
+}
+
+#[test]
+fn test_multipart_errors() {
+    assert_eq!(multipart_boundary("multipart/mixed"), Err(MultipartError::MissingBoundary));
+    assert_eq!(multipart_boundary("Multipart/Mixed;charset=x; BOUNDARY=b1"), Ok("b1".to_string()));
+    assert_eq!(MultipartParser::new("").err(), Some(MultipartError::InvalidBoundary));
+    assert_eq!(MultipartParser::new("trailing ").err(), Some(MultipartError::InvalidBoundary));
+    assert_eq!(MultipartParser::new("semi;colon").err(), Some(MultipartError::InvalidBoundary));
+    assert_eq!(MultipartParser::new(&"x".repeat(71)).err(), Some(MultipartError::InvalidBoundary));
+
+    let cases: [(&[u8], MultipartError, usize); 5] = [
+        (b"--b\r\nContent Type: x\r\n\r\n", MultipartError::InvalidHeader, 12),
+        (b"--b\r\nA: x\nB: y\r\n\r\n", MultipartError::InvalidHeader, 9),
+        (b"--bx\r\n", MultipartError::InvalidDelimiter, 3),
+        (b"--b-\r\n", MultipartError::InvalidDelimiter, 4),
+        (b"--b\r\n\r\ndata\r\n--b\r\r\n", MultipartError::InvalidDelimiter, 17),
+    ];
+    for &(data, err, offset) in cases.iter() {
+        let mut parser = MultipartParser::new("b").unwrap();
+        assert_eq!(parser.execute(&mut Collector::default(), data), offset, "{:?}", err);
+        assert_eq!(parser.errno, Some(err));
+    }
+
+    // the body ends before the close delimiter
+    let mut parser = MultipartParser::new("b").unwrap();
+    let mut cb = Collector::default();
+    parser.execute(&mut cb, b"--b\r\n\r\ndata\r\n--b");
+    parser.execute(&mut cb, &[]);
+    assert_eq!(parser.errno, Some(MultipartError::UnexpectedEof));
+    assert!(cb.parts[0].complete);
+}
+
+#[test]
+fn test_multipart_limits() {
+    let body = b"--b\r\nName: value\r\n\r\n0123456789\r\n--b--";
+
+    let mut parser = MultipartParser::new("b").unwrap();
+    parser.max_header_size = 10;
+    assert_eq!(parser.execute(&mut Collector::default(), body), 15);
+    assert_eq!(parser.errno, Some(MultipartError::HeadersTooLarge));
+
+    let mut parser = MultipartParser::new("b").unwrap();
+    parser.max_part_size = 10;
+    let mut cb = Collector::default();
+    assert_eq!(parser.execute(&mut cb, body), body.len());
+    assert_eq!(parser.errno, None);
+
+    let mut parser = MultipartParser::new("b").unwrap();
+    parser.max_part_size = 9;
+    let mut cb = Collector::default();
+    parser.execute(&mut cb, &body[..25]);
+    parser.execute(&mut cb, &body[25..]);
+    assert_eq!(parser.errno, Some(MultipartError::PartTooLarge));
+    assert_eq!(cb.parts[0].data, b"01234".to_vec());
+
+    let mut parser = MultipartParser::new("b").unwrap();
+    parser.max_body_size = 28;
+    let mut cb = Collector::default();
+    assert_eq!(parser.execute(&mut cb, body), 28);
+    assert_eq!(parser.errno, Some(MultipartError::BodyTooLarge));
+    assert_eq!(cb.parts[0].data, b"01234567".to_vec());
+}
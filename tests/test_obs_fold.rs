@@ -0,0 +1,90 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+const REQUEST: &[u8] = b"GET / HTTP/1.1\r\n\
+                         X-Folded: first\r\n  \t second\r\n\tthird\r\n\
+                         X-Empty:\r\n later\r\n\
+                         Host: a\r\n\
+                         \r\n";
+
+#[derive(Default)]
+struct Callback {
+    values: Vec<Vec<u8>>,
+    in_value: bool,
+    folds: usize,
+    completed: usize,
+}
+
+impl HttpParserCallback for Callback {
+    fn on_header_field(&mut self, _: &mut HttpParser, _: &[u8]) -> CallbackResult {
+        self.in_value = false;
+        Ok(ParseAction::None)
+    }
+
+    fn on_header_value(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        if !self.in_value {
+            self.values.push(Vec::new());
+            self.in_value = true;
+        }
+        self.values.last_mut().unwrap().extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_obs_fold(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.folds += 1;
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.completed += 1;
+        Ok(ParseAction::None)
+    }
+}
+
+fn parse(mode: ObsFold, chunk: usize) -> (HttpParser, Callback) {
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.obs_fold = mode;
+    let mut cb = Callback::default();
+    for part in REQUEST.chunks(chunk) {
+        if hp.execute(&mut cb, part) != part.len() {
+            break;
+        }
+    }
+    (hp, cb)
+}
+
+#[test]
+fn test_obs_fold_modes() {
+    for &chunk in [REQUEST.len(), 1, 3].iter() {
+        let (hp, cb) = parse(ObsFold::Preserve, chunk);
+        assert!(hp.errno.is_none());
+        assert_eq!(cb.values[0], b"first  \t second\tthird".to_vec());
+        assert_eq!(cb.values[1], b"later".to_vec());
+        assert_eq!(cb.folds, 3);
+
+        let (hp, cb) = parse(ObsFold::Replace, chunk);
+        assert!(hp.errno.is_none());
+        assert_eq!(cb.values[0], b"first second third".to_vec());
+        assert_eq!(cb.values[1], b"later".to_vec());
+        assert_eq!(cb.values[2], b"a".to_vec());
+        assert_eq!(cb.folds, 3);
+        assert_eq!(cb.completed, 1);
+
+        let (hp, cb) = parse(ObsFold::Reject, chunk);
+        assert_eq!(hp.errno, Some(HttpErrno::InvalidObsFold));
+        assert_eq!(cb.folds, 0);
+        assert_eq!(cb.completed, 0);
+    }
+}
+
+#[test]
+fn test_obs_fold_collector() {
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    hp.obs_fold = ObsFold::Replace;
+    let mut cb = MessageCollector::new();
+    hp.execute(&mut cb, b"HTTP/1.1 200 OK\r\nWarning: 199 -\r\n  \"folded\"\r\nContent-Length: 0\r\n\r\n");
+    assert!(hp.errno.is_none());
+    assert_eq!(cb.messages[0].headers.get("Warning"), Some("199 - \"folded\""));
+    assert_eq!(cb.messages[0].headers.len(), 2);
+}
@@ -0,0 +1,133 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::parse_configured;
+
+pub mod helper;
+
+fn parse(protocol: Protocol, tp: HttpParserType, data: &str) -> Vec<Message> {
+    // byte by byte, to check the start line matchers resume across calls
+    parse_configured(tp, data.as_bytes(), 1, |hp| hp.protocol = protocol).1.messages
+}
+
+fn parse_error(protocol: Protocol, tp: HttpParserType, data: &str) -> Option<HttpErrno> {
+    let mut hp = HttpParser::new(tp);
+    hp.protocol = protocol;
+    hp.execute(&mut MessageCollector::new(), data.as_bytes());
+    hp.errno
+}
+
+#[test]
+fn test_rtsp() {
+    let requests = "SETUP rtsp://example.com/media.mp4/streamid=0 RTSP/1.0\r\n\
+                    CSeq: 3\r\n\
+                    Transport: RTP/AVP;unicast;client_port=8000-8001\r\n\
+                    \r\n\
+                    PLAY rtsp://example.com/media.mp4 RTSP/1.0\r\n\
+                    CSeq: 4\r\n\
+                    Session: 12345678\r\n\
+                    \r\n\
+                    GET_PARAMETER rtsp://example.com/media.mp4 RTSP/1.0\r\n\
+                    CSeq: 5\r\n\
+                    Content-Length: 7\r\n\
+                    \r\n\
+                    packets";
+    let messages = parse(Protocol::Rtsp, HttpParserType::Request, requests);
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].method, Some(HttpMethod::Setup));
+    assert_eq!(messages[0].url, "rtsp://example.com/media.mp4/streamid=0");
+    assert_eq!(messages[0].protocol, Protocol::Rtsp);
+    assert_eq!(messages[0].http_version, HttpVersion { major: 1, minor: 0 });
+    assert_eq!(messages[1].method, Some(HttpMethod::Play));
+    assert_eq!(messages[2].method, Some(HttpMethod::GetParameter));
+    assert_eq!(messages[2].body, b"packets".to_vec());
+
+    // RTSP/1.0 responses without Content-Length have no body and keep the connection open
+    let responses = "RTSP/1.0 200 OK\r\n\
+                     CSeq: 3\r\n\
+                     Session: 12345678\r\n\
+                     \r\n\
+                     RTSP/1.0 454 Session Not Found\r\n\
+                     CSeq: 4\r\n\
+                     \r\n";
+    let messages = parse(Protocol::Rtsp, HttpParserType::Response, responses);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].status_code, Some(454));
+    assert_eq!(messages[1].status, "Session Not Found");
+    assert_eq!(messages[1].to_bytes(), b"RTSP/1.0 454 Session Not Found\r\nCSeq: 4\r\n\r\n".to_vec());
+}
+
+#[test]
+fn test_sip() {
+    let data = "INVITE sip:bob@biloxi.example.com SIP/2.0\r\n\
+                Via: SIP/2.0/TCP client.atlanta.example.com:5060;branch=z9hG4bK74bf9\r\n\
+                To: Bob <sip:bob@biloxi.example.com>\r\n\
+                Call-ID: 3848276298220188511@atlanta.example.com\r\n\
+                CSeq: 1 INVITE\r\n\
+                Content-Length: 4\r\n\
+                \r\n\
+                v=0\n\
+                SUBSCRIBE sips:alice@atlanta.example.com;transport=tcp SIP/2.0\r\n\
+                Content-Length: 0\r\n\
+                \r\n";
+    let messages = parse(Protocol::Sip, HttpParserType::Both, data);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].method, Some(HttpMethod::Invite));
+    assert_eq!(messages[0].url, "sip:bob@biloxi.example.com");
+    assert_eq!(messages[0].http_version, HttpVersion { major: 2, minor: 0 });
+    assert_eq!(messages[0].body, b"v=0\n".to_vec());
+    assert_eq!(messages[1].method, Some(HttpMethod::Subscribe));
+    assert_eq!(messages[1].url, "sips:alice@atlanta.example.com;transport=tcp");
+
+    let messages = parse(Protocol::Sip, HttpParserType::Both,
+                         "SIP/2.0 200 OK\r\n\
+                          CSeq: 1 INVITE\r\n\
+                          Content-Length: 0\r\n\
+                          \r\n");
+    assert_eq!(messages[0].status_code, Some(200));
+    assert_eq!(messages[0].method, None);
+}
+
+#[test]
+fn test_icap() {
+    let messages = parse(Protocol::Icap, HttpParserType::Both,
+                         "OPTIONS icap://icap.example.net/sample-service ICAP/1.0\r\n\
+                          Host: icap.example.net\r\n\
+                          \r\n");
+    assert_eq!(messages[0].method, Some(HttpMethod::Options));
+    assert_eq!(messages[0].protocol, Protocol::Icap);
+
+    let messages = parse(Protocol::Icap, HttpParserType::Response,
+                         "ICAP/1.0 200 OK\r\n\
+                          Methods: RESPMOD\r\n\
+                          Encapsulated: null-body=0\r\n\
+                          \r\n");
+    assert_eq!(messages[0].status_code, Some(200));
+    assert_eq!(messages[0].headers.get("Methods"), Some("RESPMOD"));
+}
+
+#[test]
+fn test_protocol_mismatch() {
+    assert_eq!(parse_error(Protocol::Rtsp, HttpParserType::Request, "GET / RTSP/1.0\r\n\r\n"),
+               Some(HttpErrno::InvalidMethod));
+    assert_eq!(parse_error(Protocol::Sip, HttpParserType::Request, "PLAY sip:a@b SIP/2.0\r\n\r\n"),
+               Some(HttpErrno::InvalidMethod));
+    assert_eq!(parse_error(Protocol::Icap, HttpParserType::Request, "REQMODX icap://a ICAP/1.0\r\n\r\n"),
+               Some(HttpErrno::InvalidMethod));
+    assert_eq!(parse_error(Protocol::Rtsp, HttpParserType::Request, "PLAY rtsp://a/b HTTP/1.1\r\n\r\n"),
+               Some(HttpErrno::InvalidConstant));
+    assert_eq!(parse_error(Protocol::Rtsp, HttpParserType::Response, "HTTP/1.1 200 OK\r\n\r\n"),
+               Some(HttpErrno::InvalidConstant));
+    assert_eq!(parse_error(Protocol::Http, HttpParserType::Response, "RTSP/1.0 200 OK\r\n\r\n"),
+               Some(HttpErrno::InvalidConstant));
+    assert_eq!(parse_error(Protocol::Http, HttpParserType::Request, "SETUP / HTTP/1.1\r\n\r\n"),
+               Some(HttpErrno::InvalidMethod));
+    // a response-like token must be followed by '/'
+    assert_eq!(parse_error(Protocol::Rtsp, HttpParserType::Both, "RTSP 200 OK\r\n\r\n"),
+               Some(HttpErrno::InvalidMethod));
+
+    assert_eq!("SIP".parse(), Ok(Protocol::Sip));
+    assert_eq!("SET_PARAMETER".parse(), Ok(HttpMethod::SetParameter));
+    assert_eq!(HttpMethod::PlayNotify.to_string(), "PLAY_NOTIFY");
+}
@@ -0,0 +1,146 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::feed;
+
+pub mod helper;
+
+const REQUEST: &[u8] = b"GET /health HTTP/1.1\r\nHost: example.com\r\n\r\n";
+
+#[derive(Default)]
+struct Callback {
+    proxy_headers: usize,
+    url: Vec<u8>,
+    completed: usize,
+}
+
+impl HttpParserCallback for Callback {
+    fn on_proxy_header(&mut self, parser: &mut HttpParser) -> CallbackResult {
+        assert!(parser.proxy_header.is_some());
+        self.proxy_headers += 1;
+        Ok(ParseAction::None)
+    }
+
+    fn on_url(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+        self.url.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, _: &mut HttpParser) -> CallbackResult {
+        self.completed += 1;
+        Ok(ParseAction::None)
+    }
+}
+
+fn parse(data: &[u8], chunk: usize) -> (HttpParser, Callback) {
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.proxy_protocol = true;
+    let mut cb = Callback::default();
+    feed(&mut hp, &mut cb, data, chunk);
+    (hp, cb)
+}
+
+fn v2_header(ver_cmd: u8, fam: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+    out.push(ver_cmd);
+    out.push(fam);
+    out.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+#[test]
+fn test_proxy_v1() {
+    let mut data = b"PROXY TCP6 2001:db8::1 ::1 56324 8080\r\n".to_vec();
+    data.extend_from_slice(REQUEST);
+    data.extend_from_slice(REQUEST);
+
+    for &chunk in [data.len(), 1, 7].iter() {
+        let (hp, cb) = parse(&data, chunk);
+        let header = hp.proxy_header.unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.transport, ProxyTransport::Stream);
+        assert_eq!(header.source.unwrap().to_string(), "[2001:db8::1]:56324");
+        assert_eq!(header.destination.unwrap().to_string(), "[::1]:8080");
+        assert_eq!(cb.proxy_headers, 1);
+        assert_eq!(cb.url, b"/health/health".to_vec());
+        assert_eq!(cb.completed, 2);
+    }
+
+    let (hp, _) = parse(b"PROXY UNKNOWN ffff::1 ffff::2 1 2\r\n", 64);
+    let header = hp.proxy_header.unwrap();
+    assert_eq!(header.command, ProxyCommand::Proxy);
+    assert_eq!(header.source, None);
+}
+
+#[test]
+fn test_proxy_v2() {
+    let mut body = vec![10, 0, 0, 1, 10, 0, 0, 2, 0xdc, 0x04, 0x01, 0xbb];
+    body.extend_from_slice(&[PP2_TYPE_ALPN, 0, 2]);
+    body.extend_from_slice(b"h2");
+    body.extend_from_slice(&[PP2_TYPE_AUTHORITY, 0, 11]);
+    body.extend_from_slice(b"example.com");
+    body.extend_from_slice(&[PP2_TYPE_NOOP, 0, 0]);
+    let mut data = v2_header(0x21, 0x11, &body);
+    data.extend_from_slice(REQUEST);
+
+    for &chunk in [data.len(), 1, 5].iter() {
+        let (hp, cb) = parse(&data, chunk);
+        let header = hp.proxy_header.unwrap();
+        assert_eq!(header.version, 2);
+        assert_eq!(header.command, ProxyCommand::Proxy);
+        assert_eq!(header.transport, ProxyTransport::Stream);
+        assert_eq!(header.source.unwrap().to_string(), "10.0.0.1:56324");
+        assert_eq!(header.destination.unwrap().to_string(), "10.0.0.2:443");
+        assert_eq!(header.alpn(), Some("h2"));
+        assert_eq!(header.authority(), Some("example.com"));
+        assert_eq!(header.tlv(PP2_TYPE_NOOP), Some(&[][..]));
+        assert_eq!(header.tlvs.len(), 3);
+        assert_eq!(cb.completed, 1);
+    }
+
+    // a LOCAL health check carries no addresses
+    let mut data = v2_header(0x20, 0x00, &[]);
+    data.extend_from_slice(REQUEST);
+    let (hp, cb) = parse(&data, data.len());
+    let header = hp.proxy_header.unwrap();
+    assert_eq!(header.command, ProxyCommand::Local);
+    assert_eq!(header.source, None);
+    assert_eq!(cb.completed, 1);
+}
+
+#[test]
+fn test_proxy_errors() {
+    let cases: Vec<(Vec<u8>, ProxyError)> = vec![
+        (REQUEST.to_vec(), ProxyError::InvalidSignature),
+        (b"PROXY TCP5 1.2.3.4 5.6.7.8 1 2\r\n".to_vec(), ProxyError::InvalidFamily),
+        (b"PROXY TCP4 1.2.3 5.6.7.8 1 2\r\n".to_vec(), ProxyError::InvalidAddress),
+        (b"PROXY TCP4 1.2.3.4 5.6.7.8 01 2\r\n".to_vec(), ProxyError::InvalidPort),
+        (b"PROXY TCP4 1.2.3.4 5.6.7.8 1 65536\r\n".to_vec(), ProxyError::InvalidPort),
+        (b"PROXY TCP4 1.2.3.4  5.6.7.8 1 2\r\n".to_vec(), ProxyError::InvalidLine),
+        ([&b"PROXY UNKNOWN "[..], &[b'x'; 100][..]].concat(), ProxyError::InvalidLine),
+        (v2_header(0x11, 0x11, &[0; 12]), ProxyError::InvalidVersion),
+        (v2_header(0x22, 0x11, &[0; 12]), ProxyError::InvalidCommand),
+        (v2_header(0x21, 0x41, &[0; 12]), ProxyError::InvalidFamily),
+        (v2_header(0x21, 0x21, &[0; 12]), ProxyError::InvalidLength),
+        (v2_header(0x21, 0x11, &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 5, b'h']), ProxyError::InvalidTlv),
+    ];
+    for &(ref data, err) in cases.iter() {
+        assert_eq!(ProxyHeader::parse(data), Err(err), "{:?}", String::from_utf8_lossy(data));
+
+        let mut hp = HttpParser::new(HttpParserType::Request);
+        hp.proxy_protocol = true;
+        hp.execute(&mut Callback::default(), data);
+        assert_eq!(hp.errno, Some(HttpErrno::InvalidProxyHeader));
+    }
+
+    // incomplete headers need more data, and EOF in the middle is an error
+    assert_eq!(ProxyHeader::parse(b"PRO"), Ok(None));
+    assert_eq!(ProxyHeader::parse(b"\r\n\r\n\0\r\nQUIT\n\x21\x11\x00\x0c"), Ok(None));
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.proxy_protocol = true;
+    assert_eq!(hp.execute(&mut Callback::default(), b"PROXY TCP4"), 10);
+    hp.execute(&mut Callback::default(), &[]);
+    assert_eq!(hp.errno, Some(HttpErrno::InvalidEofState));
+}
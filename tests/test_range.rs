@@ -0,0 +1,152 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+#[test]
+fn test_range() {
+    let range = Range::parse(b" BYTES=500-999 ,, 1000- , -1 ").unwrap();
+    assert_eq!(range.ranges.len(), 3);
+
+    let range = Range::parse(b"bytes=500-999, ,1000-,-1").unwrap();
+    assert_eq!(range.ranges, vec![ByteRange::FromTo(500, 999), ByteRange::From(1000), ByteRange::Last(1)]);
+    assert_eq!(range.to_string(), "bytes=500-999,1000-,-1");
+    assert_eq!(range.resolve(10000), Ok(vec![(500, 999), (1000, 9999), (9999, 9999)]));
+    assert_eq!(range.resolve(800), Ok(vec![(500, 799), (799, 799)]));
+    assert_eq!(Range::parse(b"bytes=0-").unwrap().resolve(0), Err(RangeError::Unsatisfiable));
+    assert_eq!(Range::parse(b"bytes=-0").unwrap().resolve(10), Err(RangeError::Unsatisfiable));
+    assert_eq!(Range::parse(b"bytes=10-20").unwrap().resolve(10), Err(RangeError::Unsatisfiable));
+    assert_eq!(Range::parse(b"bytes=-100").unwrap().resolve(10), Ok(vec![(0, 9)]));
+
+    for value in ["bytes=", "bytes=5", "bytes=9-5", "bytes=a-b", "bytes=1-2-3", "bytes=+1-2", "bytes"].iter() {
+        assert_eq!(Range::parse(value.as_bytes()), Err(RangeError::InvalidRange), "{}", value);
+    }
+    assert_eq!(Range::parse(b"items=0-5"), Err(RangeError::UnsupportedUnit));
+    assert_eq!(Range::parse(b"bytes=99999999999999999999-"), Err(RangeError::InvalidRange));
+}
+
+#[test]
+fn test_content_range() {
+    assert_eq!(ContentRange::parse(b"bytes 0-0/*"),
+               Ok(ContentRange::Bytes { first: 0, last: 0, complete_length: None }));
+    assert_eq!(ContentRange::parse(b"bytes */47022"), Ok(ContentRange::Unsatisfied(47022)));
+    let range = ContentRange::parse(b"BYTES 21010-47021/47022").unwrap();
+    assert_eq!(range.length(), Some(26012));
+    assert_eq!(range.complete_length(), Some(47022));
+    assert_eq!(range.to_string(), "bytes 21010-47021/47022");
+
+    for value in ["bytes 5-4/10", "bytes 0-10/10", "bytes */*", "bytes 0-/10", "bytes=0-1/10", "0-1/10", "bytes 0-1",
+                  "bytesé0-1/10", "bytes 0-18446744073709551615/*"].iter() {
+        assert_eq!(ContentRange::parse(value.as_bytes()), Err(RangeError::InvalidContentRange), "{}", value);
+    }
+}
+
+#[derive(Default)]
+struct Collector {
+    ranges: Vec<(ContentRange, Option<String>, Vec<u8>, bool)>,
+}
+
+impl ByteRangesCallback for Collector {
+    fn on_range(&mut self, _: &mut MultipartParser, range: &ContentRange,
+                content_type: Option<&MediaType>) -> CallbackResult {
+        self.ranges.push((*range, content_type.map(|m| m.essence()), Vec::new(), false));
+        Ok(ParseAction::None)
+    }
+
+    fn on_range_data(&mut self, _: &mut MultipartParser, data: &[u8]) -> CallbackResult {
+        self.ranges.last_mut().unwrap().2.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_range_end(&mut self, _: &mut MultipartParser) -> CallbackResult {
+        self.ranges.last_mut().unwrap().3 = true;
+        Ok(ParseAction::None)
+    }
+}
+
+fn parse_body(body: &[u8]) -> (ByteRangesParser, Collector) {
+    let mut parser = ByteRangesParser::new("multipart/byteranges; boundary=THIS_STRING_SEPARATES").unwrap();
+    let mut cb = Collector::default();
+    parser.execute(&mut cb, body);
+    parser.execute(&mut cb, &[]);
+    (parser, cb)
+}
+
+#[test]
+fn test_byteranges() {
+    let body = b"--THIS_STRING_SEPARATES\r\n\
+                 Content-Type: application/pdf\r\n\
+                 Content-Range: bytes 500-509/8000\r\n\
+                 \r\n\
+                 0123456789\r\n\
+                 --THIS_STRING_SEPARATES\r\n\
+                 Content-Range: bytes 7000-7001/8000\r\n\
+                 \r\n\
+                 \r\n\r\n\
+                 --THIS_STRING_SEPARATES--\r\n";
+    let response = format!("HTTP/1.1 206 Partial Content\r\n\
+                            Content-Type: multipart/byteranges; boundary=THIS_STRING_SEPARATES\r\n\
+                            Content-Length: {}\r\n\r\n{}", body.len(), String::from_utf8_lossy(body));
+
+    struct Response {
+        parser: Option<ByteRangesParser>,
+        cb: Collector,
+    }
+
+    impl HttpParserCallback for Response {
+        fn on_header_value(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+            if let Ok(parser) = ByteRangesParser::new(&String::from_utf8_lossy(data)) {
+                self.parser = Some(parser);
+            }
+            Ok(ParseAction::None)
+        }
+
+        fn on_body(&mut self, _: &mut HttpParser, data: &[u8]) -> CallbackResult {
+            self.parser.as_mut().unwrap().execute(&mut self.cb, data);
+            Ok(ParseAction::None)
+        }
+    }
+
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    let mut cb = Response { parser: None, cb: Collector::default() };
+    // the whole head at once, so that on_header_value sees complete values, then the
+    // body byte by byte
+    let head = response.len() - body.len();
+    assert_eq!(hp.execute(&mut cb, &response.as_bytes()[..head]), head);
+    for b in body.iter() {
+        hp.execute(&mut cb, &[*b]);
+    }
+    let mut parser = cb.parser.unwrap();
+    parser.execute(&mut cb.cb, &[]);
+    assert_eq!(parser.errno, None);
+
+    let ranges = cb.cb.ranges;
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0].0, ContentRange::Bytes { first: 500, last: 509, complete_length: Some(8000) });
+    assert_eq!(ranges[0].1, Some("application/pdf".to_string()));
+    assert_eq!(ranges[0].2, b"0123456789".to_vec());
+    assert_eq!(ranges[1].1, None);
+    assert_eq!(ranges[1].2, b"\r\n".to_vec());
+    assert!(ranges[0].3 && ranges[1].3);
+}
+
+#[test]
+fn test_byteranges_errors() {
+    assert_eq!(ByteRangesParser::new("multipart/mixed; boundary=x").err(), Some(RangeError::NotByteRanges));
+    assert_eq!(ByteRangesParser::new("multipart/byteranges").err(), Some(RangeError::NotByteRanges));
+    assert_eq!(ByteRangesParser::new("multipart/byteranges; boundary=\"a;b\"").err(),
+               Some(RangeError::Multipart(MultipartError::InvalidBoundary)));
+
+    let cases: [(&[u8], RangeError); 6] = [
+        (b"--THIS_STRING_SEPARATES\r\n\r\nabc\r\n--THIS_STRING_SEPARATES--", RangeError::MissingContentRange),
+        (b"--THIS_STRING_SEPARATES\r\nContent-Range: bytes */10\r\n\r\n", RangeError::InvalidContentRange),
+        (b"--THIS_STRING_SEPARATES\r\nContent-Range: bytes 0-2/10\r\n\r\nabcd\r\n", RangeError::PartLengthMismatch),
+        (b"--THIS_STRING_SEPARATES\r\nContent-Range: bytes 0-2/10\r\n\r\nab\r\n--THIS_STRING_SEPARATES--", RangeError::PartLengthMismatch),
+        (b"--THIS_STRING_SEPARATES\r\nContent-Range: bytes 0-1/10\r\n\r\nab\r\n\
+           --THIS_STRING_SEPARATES\r\nContent-Range: bytes 0-1/11\r\n\r\nab\r\n--THIS_STRING_SEPARATES--", RangeError::LengthMismatch),
+        (b"--THIS_STRING_SEPARATES\r\nContent-Range: bytes 0-1/10\r\n\r\nab\r\n", RangeError::Multipart(MultipartError::UnexpectedEof)),
+    ];
+    for &(body, err) in cases.iter() {
+        let (parser, _) = parse_body(body);
+        assert_eq!(parser.errno, Some(err));
+    }
+}
@@ -0,0 +1,71 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+const SEARCH: &str = "M-SEARCH * HTTP/1.1\r\n\
+                      HOST: 239.255.255.250:1900\r\n\
+                      MAN: \"ssdp:discover\"\r\n\
+                      MX: 2\r\n\
+                      ST: urn:schemas-upnp-org:device:MediaServer:1\r\n\
+                      \r\n";
+
+#[test]
+fn test_ssdp_search() {
+    let ssdp = SsdpMessage::parse_datagram(SEARCH.as_bytes()).unwrap();
+    assert_eq!(ssdp.kind, SsdpKind::Search);
+    assert_eq!(ssdp.message.method, Some(HttpMethod::MSearch));
+    assert_eq!(ssdp.message.url, "*");
+    assert_eq!(ssdp.st, Some("urn:schemas-upnp-org:device:MediaServer:1".to_string()));
+    assert_eq!(ssdp.message.headers.get("MX"), Some("2"));
+    assert_eq!(ssdp.max_age, None);
+
+    // no Content-Length, and no Connection header to keep the parser alive
+    let response = "HTTP/1.1 200 OK\r\n\
+                    CACHE-CONTROL: no-cache=\"Ext\", max-age = 120\r\n\
+                    EXT:\r\n\
+                    LOCATION: http://10.0.0.7:8200/rootDesc.xml\r\n\
+                    SERVER: Linux/5.10 UPnP/1.0 MiniDLNA/1.3.0\r\n\
+                    ST: urn:schemas-upnp-org:device:MediaServer:1\r\n\
+                    USN: uuid:4d696e69-444c-164e-9d41-b827eb2b2c1a::urn:schemas-upnp-org:device:MediaServer:1\r\n\
+                    \r\n";
+    let ssdp = SsdpMessage::parse_datagram(response.as_bytes()).unwrap();
+    assert_eq!(ssdp.kind, SsdpKind::Response);
+    assert_eq!(ssdp.message.status_code, Some(200));
+    assert_eq!(ssdp.max_age, Some(120));
+    assert_eq!(ssdp.location, Some("http://10.0.0.7:8200/rootDesc.xml".to_string()));
+    assert_eq!(ssdp.usn.unwrap(), "uuid:4d696e69-444c-164e-9d41-b827eb2b2c1a::urn:schemas-upnp-org:device:MediaServer:1");
+    assert_eq!(ssdp.message.headers.get("EXT"), Some(""));
+}
+
+#[test]
+fn test_ssdp_notify() {
+    let byebye = "NOTIFY * HTTP/1.1\r\n\
+                  HOST: 239.255.255.250:1900\r\n\
+                  NT: urn:schemas-upnp-org:service:ContentDirectory:1\r\n\
+                  NTS: ssdp:byebye\r\n\
+                  USN: uuid:4d696e69::urn:schemas-upnp-org:service:ContentDirectory:1\r\n\
+                  \r\n";
+    let ssdp = SsdpMessage::parse_datagram(byebye.as_bytes()).unwrap();
+    assert_eq!(ssdp.kind, SsdpKind::Notify);
+    assert_eq!(ssdp.nt, Some("urn:schemas-upnp-org:service:ContentDirectory:1".to_string()));
+    assert_eq!(ssdp.nts, Some("ssdp:byebye".to_string()));
+    assert_eq!(ssdp.location, None);
+
+    // the end of the packet ends the body
+    let data = "NOTIFY * HTTP/1.1\r\nNT: upnp:event\r\nNTS: upnp:propchange\r\n\r\n<e:propertyset/>";
+    let ssdp = SsdpMessage::parse_datagram(data.as_bytes()).unwrap();
+    assert_eq!(ssdp.message.body, b"<e:propertyset/>".to_vec());
+}
+
+#[test]
+fn test_ssdp_errors() {
+    let cases = [
+        (&SEARCH[.. SEARCH.len() - 2], SsdpError::Incomplete),
+        ("M-SEARCH * HTTP/1.1\r\nST ssdp:all\r\n\r\n", SsdpError::Http(HttpErrno::InvalidHeaderToken)),
+        ("GET / HTTP/1.1\r\nHost: a\r\n\r\n", SsdpError::InvalidMethod),
+        ("NOTIFY * HTTP/1.1\r\nContent-Length: 0\r\n\r\nNOTIFY * HTTP/1.1\r\n\r\n", SsdpError::TrailingData),
+    ];
+    for &(data, err) in cases.iter() {
+        assert_eq!(SsdpMessage::parse_datagram(data.as_bytes()), Err(err), "{}", data);
+    }
+}
@@ -0,0 +1,56 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+#[test]
+fn test_status_code() {
+    assert_eq!(StatusCode::new(99), None);
+    assert_eq!(StatusCode::new(1000), None);
+    assert_eq!(StatusCode::new(200), Some(StatusCode::OK));
+    assert_eq!(StatusCode::OK.as_u16(), 200);
+    assert_eq!(u16::from(StatusCode::IM_USED), 226);
+    assert!(StatusCode::CONTINUE < StatusCode::OK);
+
+    let classes = [
+        (StatusCode::EARLY_HINTS, 1),
+        (StatusCode::PARTIAL_CONTENT, 2),
+        (StatusCode::PERMANENT_REDIRECT, 3),
+        (StatusCode::TOO_MANY_REQUESTS, 4),
+        (StatusCode::BAD_GATEWAY, 5),
+        (StatusCode::new(799).unwrap(), 7),
+    ];
+    for &(status, class) in classes.iter() {
+        assert_eq!(status.is_informational(), class == 1, "{}", status);
+        assert_eq!(status.is_success(), class == 2, "{}", status);
+        assert_eq!(status.is_redirection(), class == 3, "{}", status);
+        assert_eq!(status.is_client_error(), class == 4, "{}", status);
+        assert_eq!(status.is_server_error(), class == 5, "{}", status);
+    }
+
+    assert_eq!(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS.reason_phrase(), Some("Unavailable For Legal Reasons"));
+    assert_eq!(StatusCode::new(599).unwrap().reason_phrase(), None);
+    assert_eq!(StatusCode::new(599).unwrap().to_string(), "599");
+    assert_eq!(StatusCode::NO_CONTENT.to_string(), "204 No Content");
+
+    assert_eq!("503".parse(), Ok(StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!("50".parse::<StatusCode>(), Err(HttpErrno::InvalidStatus));
+    assert_eq!("+50".parse::<StatusCode>(), Err(HttpErrno::InvalidStatus));
+    assert_eq!("050".parse::<StatusCode>(), Err(HttpErrno::InvalidStatus));
+
+    assert!(StatusCode::OK.allows_body());
+    assert!(!StatusCode::SWITCHING_PROTOCOLS.allows_body());
+    assert!(!StatusCode::NO_CONTENT.allows_body());
+    assert!(!StatusCode::NOT_MODIFIED.allows_body());
+}
+
+#[test]
+fn test_parser_status() {
+    let mut hp = HttpParser::new(HttpParserType::Response);
+    let mut cb = MessageCollector::new();
+    hp.execute(&mut cb, b"HTTP/1.1 304 Not Modified\r\nETag: \"x\"\r\n\r\nHTTP/1.1 418 I'm a teapot\r\nContent-Length: 0\r\n\r\n");
+    assert!(hp.errno.is_none());
+    assert_eq!(cb.messages.len(), 2);
+    assert_eq!(cb.messages[0].status(), Some(StatusCode::NOT_MODIFIED));
+    assert_eq!(hp.status(), StatusCode::new(418));
+    assert_eq!(hp.status().unwrap().reason_phrase(), None);
+}
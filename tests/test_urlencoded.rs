@@ -0,0 +1,87 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+    list.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_query_pairs() {
+    let url = b"http://example.com/a?x=1&&y=%41%2b+b&flag&=empty&bad=%zz%4&dup=1&dup=2#frag?z=3";
+    let decoded: Vec<_> = query_pairs(url).collect();
+    assert_eq!(decoded, pairs(&[("x", "1"), ("y", "A+ b"), ("flag", ""), ("", "empty"),
+                                ("bad", "%zz%4"), ("dup", "1"), ("dup", "2")]));
+    assert_eq!(query_pairs(b"/no/query").count(), 0);
+    assert_eq!(query_pairs(b"/?").count(), 0);
+
+    let decoded: Vec<_> = QueryPairs::new(b"k=%FF%FE").collect();
+    assert_eq!(decoded, pairs(&[("k", "\u{FFFD}\u{FFFD}")]));
+    assert_eq!(percent_decode(b"a%20b+c%2"), b"a b+c%2".to_vec());
+}
+
+#[test]
+fn test_form_decoder() {
+    let body = b"first=J%C3%B6rg+M&last=%26%3D&empty=&novalue&&x=%4";
+    let expected = pairs(&[("first", "Jörg M"), ("last", "&="), ("empty", ""), ("novalue", ""), ("x", "%4")]);
+    assert_eq!(QueryPairs::new(body).collect::<Vec<_>>(), expected);
+
+    // every split point, including in the middle of %XX escapes
+    for split in 0..body.len() {
+        let mut decoder = FormDecoder::new();
+        let mut decoded = Vec::new();
+        assert_eq!(decoder.execute(&body[..split], &mut decoded), split);
+        assert_eq!(decoder.execute(&body[split..], &mut decoded), body.len() - split);
+        decoder.execute(&[], &mut decoded);
+        assert_eq!(decoder.errno, None);
+        assert_eq!(decoded, expected, "split at {}", split);
+    }
+
+    // byte by byte through the parser's on_body
+    let request = format!("POST /form HTTP/1.1\r\nContent-Type: application/x-www-form-urlencoded\r\n\
+                           Content-Length: {}\r\n\r\n{}", body.len(), String::from_utf8_lossy(body));
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    let mut cb = MessageCollector::new();
+    for b in request.as_bytes() {
+        hp.execute(&mut cb, &[*b]);
+    }
+    let mut decoder = FormDecoder::new();
+    let mut decoded = Vec::new();
+    for b in cb.messages[0].body.iter() {
+        decoder.execute(&[*b], &mut decoded);
+    }
+    decoder.execute(&[], &mut decoded);
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn test_form_decoder_limits() {
+    let mut decoder = FormDecoder::new();
+    decoder.max_pairs = 2;
+    let mut decoded = Vec::new();
+    assert_eq!(decoder.execute(b"a=1&b=2&c=3", &mut decoded), 8);
+    assert_eq!(decoder.errno, Some(FormError::TooManyPairs));
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoder.execute(b"more", &mut decoded), 0);
+
+    let mut decoder = FormDecoder::new();
+    decoder.max_key_length = 3;
+    assert_eq!(decoder.execute(b"abc=1&abcd=2", &mut Vec::new()), 9);
+    assert_eq!(decoder.errno, Some(FormError::KeyTooLong));
+
+    let mut decoder = FormDecoder::new();
+    decoder.max_value_length = 3;
+    decoder.execute(b"a=%41%", &mut Vec::new());
+    assert_eq!(decoder.errno, Some(FormError::ValueTooLong));
+
+    // empty pairs do not count, and the count starts over with the next body
+    let mut decoder = FormDecoder::new();
+    decoder.max_pairs = 1;
+    let mut decoded = Vec::new();
+    assert_eq!(decoder.execute(b"&&a=1&&", &mut decoded), 7);
+    decoder.execute(&[], &mut decoded);
+    assert_eq!(decoder.execute(b"b=2", &mut decoded), 3);
+    decoder.execute(&[], &mut decoded);
+    assert_eq!(decoder.errno, None);
+    assert_eq!(decoded, pairs(&[("a", "1"), ("b", "2")]));
+}
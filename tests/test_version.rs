@@ -0,0 +1,70 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::parse_with;
+
+pub mod helper;
+
+
+#[test]
+fn test_version_validation() {
+    let cases: [(&[u8], HttpParserType, HttpErrno); 6] = [
+        (b"GET / HTTP/12.3\r\n\r\n", HttpParserType::Request, HttpErrno::InvalidVersion),
+        (b"GET / HTTP/1.10\r\n\r\n", HttpParserType::Request, HttpErrno::InvalidVersion),
+        (b"GET / HTTP/1.2\r\n\r\n", HttpParserType::Request, HttpErrno::UnsupportedVersion),
+        (b"GET / HTTP/2.0\r\n\r\n", HttpParserType::Request, HttpErrno::UnsupportedVersion),
+        (b"HTTP/3.0 200 OK\r\nContent-Length: 0\r\n\r\n", HttpParserType::Response, HttpErrno::UnsupportedVersion),
+        (b"HTTP/1.12 200 OK\r\nContent-Length: 0\r\n\r\n", HttpParserType::Response, HttpErrno::InvalidVersion),
+    ];
+    for &(data, tp, err) in cases.iter() {
+        let (hp, _) = parse_with(Lenient::empty(), tp, data);
+        assert_eq!(hp.errno, Some(err), "{}", String::from_utf8_lossy(data));
+
+        let (hp, cb) = parse_with(Lenient::VERSION, tp, data);
+        assert_eq!(hp.errno, None, "{}", String::from_utf8_lossy(data));
+        assert_eq!(cb.messages.len(), 1);
+    }
+
+    let (_, cb) = parse_with(Lenient::VERSION, HttpParserType::Request, b"GET / HTTP/12.34\r\n\r\n");
+    assert_eq!(cb.messages[0].http_version, HttpVersion { major: 12, minor: 34 });
+    // even lenient versions have at most two digits
+    let (hp, _) = parse_with(Lenient::VERSION, HttpParserType::Request, b"GET / HTTP/1.345\r\n\r\n");
+    assert_eq!(hp.errno, Some(HttpErrno::InvalidVersion));
+
+    for &version in ["1.0", "1.1"].iter() {
+        let data = format!("GET / HTTP/{}\r\n\r\n", version);
+        let (hp, cb) = parse_with(Lenient::empty(), HttpParserType::Request, data.as_bytes());
+        assert_eq!(hp.errno, None);
+        assert_eq!(cb.messages[0].http_version, version.parse().unwrap());
+    }
+
+    // other protocols keep their own versions
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    hp.protocol = Protocol::Sip;
+    hp.execute(&mut MessageCollector::new(), b"OPTIONS sip:a@b SIP/2.0\r\nContent-Length: 0\r\n\r\n");
+    assert_eq!(hp.errno, None);
+}
+
+#[test]
+fn test_version_keep_alive() {
+    // a message labelled with a later version behaves like HTTP/1.1
+    let data = b"HTTP/2.0 200 OK\r\nContent-Length: 0\r\n\r\n";
+    let (hp, _) = parse_with(Lenient::VERSION, HttpParserType::Response, data);
+    assert!(hp.http_should_keep_alive());
+    let data = b"HTTP/1.0 200 OK\r\nContent-Length: 0\r\n\r\n";
+    let (hp, _) = parse_with(Lenient::empty(), HttpParserType::Response, data);
+    assert!(!hp.http_should_keep_alive());
+}
+
+#[test]
+fn test_http_version() {
+    assert!(HttpVersion::HTTP_09 < HttpVersion::HTTP_10);
+    assert!(HttpVersion::HTTP_11 < HttpVersion::HTTP_20);
+    assert!(HttpVersion { major: 1, minor: 10 } > HttpVersion::HTTP_11);
+    assert_eq!("2.0".parse(), Ok(HttpVersion::HTTP_20));
+    assert_eq!(HttpVersion::HTTP_11.to_string().parse(), Ok(HttpVersion::HTTP_11));
+    for &s in ["1", "1.", ".1", "1.1.1", "a.1", "256.0", "+1.1", ""].iter() {
+        assert_eq!(s.parse::<HttpVersion>(), Err(HttpErrno::InvalidVersion), "{}", s);
+    }
+}
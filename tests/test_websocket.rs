@@ -0,0 +1,204 @@
+extern crate http_parser;
+
+use http_parser::*;
+
+use helper::parse_configured;
+
+pub mod helper;
+
+#[derive(Default)]
+struct Callback {
+    headers: Vec<WebSocketFrameHeader>,
+    payload: Vec<u8>,
+    messages: Vec<(WebSocketOpcode, Vec<u8>)>,
+    pings: Vec<Vec<u8>>,
+    closes: Vec<(Option<u16>, Vec<u8>)>,
+}
+
+impl WebSocketCallback for Callback {
+    fn on_frame_header(&mut self, _: &mut WebSocketParser,
+                       header: &WebSocketFrameHeader) -> CallbackResult {
+        self.headers.push(*header);
+        Ok(ParseAction::None)
+    }
+
+    fn on_frame_payload(&mut self, _: &mut WebSocketParser, data: &[u8]) -> CallbackResult {
+        self.payload.extend_from_slice(data);
+        Ok(ParseAction::None)
+    }
+
+    fn on_message_complete(&mut self, _: &mut WebSocketParser,
+                           opcode: WebSocketOpcode) -> CallbackResult {
+        let payload = std::mem::take(&mut self.payload);
+        self.messages.push((opcode, payload));
+        Ok(ParseAction::None)
+    }
+
+    fn on_ping(&mut self, _: &mut WebSocketParser, data: &[u8]) -> CallbackResult {
+        self.pings.push(data.to_vec());
+        Ok(ParseAction::None)
+    }
+
+    fn on_close(&mut self, _: &mut WebSocketParser, code: Option<u16>,
+                reason: &[u8]) -> CallbackResult {
+        self.closes.push((code, reason.to_vec()));
+        Ok(ParseAction::None)
+    }
+}
+
+fn frame(first: u8, mask: Option<[u8; 4]>, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![first];
+    let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+    if payload.len() < 126 {
+        out.push(mask_bit | payload.len() as u8);
+    } else if payload.len() < 0x10000 {
+        out.push(mask_bit | 126);
+        out.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    } else {
+        out.push(mask_bit | 127);
+        out.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    }
+    match mask {
+        Some(key) => {
+            out.extend_from_slice(&key);
+            out.extend(payload.iter().enumerate().map(|(i, b)| b ^ key[i % 4]));
+        },
+        None => out.extend_from_slice(payload),
+    }
+    out
+}
+
+fn parse_message(tp: HttpParserType, data: &str) -> Message {
+    parse_configured(tp, data.as_bytes(), data.len(), |_| ()).1.messages.remove(0)
+}
+
+const REQUEST: &str = "GET /chat HTTP/1.1\r\n\
+                       Host: server.example.com\r\n\
+                       Upgrade: websocket\r\n\
+                       Connection: keep-alive, Upgrade\r\n\
+                       Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                       Sec-WebSocket-Protocol: chat, superchat\r\n\
+                       Sec-WebSocket-Version: 13\r\n\
+                       \r\n";
+
+#[test]
+fn test_websocket_handshake() {
+    let handshake = WebSocketHandshake::from_request(&parse_message(HttpParserType::Request, REQUEST)).unwrap();
+    assert_eq!(handshake.protocols, vec!["chat", "superchat"]);
+    assert_eq!(handshake.accept_key(), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+    let response = "HTTP/1.1 101 Switching Protocols\r\n\
+                    Upgrade: websocket\r\n\
+                    Connection: Upgrade\r\n\
+                    Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+                    Sec-WebSocket-Protocol: chat\r\n\
+                    \r\n";
+    assert_eq!(handshake.validate_response(&parse_message(HttpParserType::Response, response)), Ok(()));
+
+    let wrong_accept = response.replace("xOo=", "xOp=");
+    assert_eq!(handshake.validate_response(&parse_message(HttpParserType::Response, &wrong_accept)),
+               Err(WebSocketError::InvalidAccept));
+    let wrong_protocol = response.replace("Protocol: chat", "Protocol: mqtt");
+    assert_eq!(handshake.validate_response(&parse_message(HttpParserType::Response, &wrong_protocol)),
+               Err(WebSocketError::InvalidProtocol));
+
+    let cases = [
+        (REQUEST.replace("GET", "POST"), WebSocketError::InvalidMethod),
+        (REQUEST.replace("HTTP/1.1", "HTTP/1.0"), WebSocketError::InvalidHttpVersion),
+        (REQUEST.replace("Upgrade: websocket", "Upgrade: h2c"), WebSocketError::MissingUpgrade),
+        (REQUEST.replace("keep-alive, Upgrade", "keep-alive"), WebSocketError::MissingConnectionUpgrade),
+        (REQUEST.replace("Version: 13", "Version: 8"), WebSocketError::UnsupportedVersion),
+        (REQUEST.replace("dGhlIHNhbXBsZSBub25jZQ==", "c2hvcnQ="), WebSocketError::InvalidKey),
+    ];
+    for &(ref request, err) in cases.iter() {
+        let message = parse_message(HttpParserType::Request, request);
+        assert_eq!(WebSocketHandshake::from_request(&message), Err(err));
+    }
+}
+
+#[test]
+fn test_websocket_frames_after_upgrade() {
+    let mut data = REQUEST.as_bytes().to_vec();
+    let mask = Some([0x37, 0xfa, 0x21, 0x3d]);
+    // fragmented text message with a ping in between
+    data.extend(frame(0x01, mask, b"Hel"));
+    data.extend(frame(0x89, mask, b"are you there"));
+    data.extend(frame(0x80, mask, "lo \u{e9}".as_bytes()));
+    // binary message with a 16-bit extended length
+    data.extend(frame(0x82, mask, &[7u8; 300]));
+    data.extend(frame(0x88, mask, b"\x03\xe8bye"));
+
+    let mut hp = HttpParser::new(HttpParserType::Request);
+    let mut collector = MessageCollector::new();
+    let offset = hp.execute(&mut collector, &data);
+    assert!(hp.upgrade);
+
+    let mut parser = WebSocketParser::new(WebSocketRole::Server);
+    let mut cb = Callback::default();
+    // feed the rest byte by byte
+    for b in &data[offset..] {
+        assert_eq!(parser.execute(&mut cb, &[*b]), 1);
+    }
+    assert!(parser.errno.is_none());
+    assert!(parser.is_closed());
+
+    assert_eq!(cb.headers.len(), 5);
+    assert!(!cb.headers[0].fin);
+    assert_eq!(cb.headers[1].opcode, WebSocketOpcode::Ping);
+    assert_eq!(cb.headers[3].payload_len, 300);
+    assert_eq!(cb.pings, vec![b"are you there".to_vec()]);
+    assert_eq!(cb.messages, vec![(WebSocketOpcode::Text, "Hello \u{e9}".as_bytes().to_vec()),
+                                 (WebSocketOpcode::Binary, vec![7u8; 300])]);
+    assert_eq!(cb.closes, vec![(Some(1000), b"bye".to_vec())]);
+}
+
+#[test]
+fn test_websocket_client_frames() {
+    let mut data = frame(0x82, None, &[1u8; 70000]);
+    data.extend(frame(0x88, None, b""));
+
+    let mut parser = WebSocketParser::new(WebSocketRole::Client);
+    let mut cb = Callback::default();
+    assert_eq!(parser.execute(&mut cb, &data), data.len());
+    assert_eq!(cb.headers[0].payload_len, 70000);
+    assert_eq!(cb.messages[0].1.len(), 70000);
+    assert_eq!(cb.closes, vec![(None, vec![])]);
+
+    // masked frames from a server are rejected
+    let mut parser = WebSocketParser::new(WebSocketRole::Client);
+    parser.execute(&mut cb, &frame(0x81, Some([1, 2, 3, 4]), b"hi"));
+    assert_eq!(parser.errno, Some(WebSocketError::UnexpectedMask));
+}
+
+#[test]
+fn test_websocket_frame_errors() {
+    let mask = Some([1, 2, 3, 4]);
+    let cases: Vec<(Vec<u8>, WebSocketError)> = vec![
+        (frame(0x81, None, b"hi"), WebSocketError::MaskRequired),
+        (frame(0xc1, mask, b"hi"), WebSocketError::ReservedBits),
+        (frame(0x83, mask, b"hi"), WebSocketError::ReservedOpcode),
+        (frame(0x09, mask, b"hi"), WebSocketError::FragmentedControlFrame),
+        (frame(0x89, mask, &[0u8; 126]), WebSocketError::ControlFrameTooLarge),
+        (frame(0x80, mask, b"hi"), WebSocketError::UnexpectedContinuation),
+        ([frame(0x01, mask, b"a"), frame(0x81, mask, b"b")].concat(), WebSocketError::ExpectedContinuation),
+        (vec![0x82, 0xfe, 0x00, 0x05], WebSocketError::InvalidLength),
+        (frame(0x81, mask, b"\xff"), WebSocketError::InvalidUtf8),
+        (frame(0x81, mask, b"\xce"), WebSocketError::InvalidUtf8),
+        (frame(0x88, mask, b"\x03"), WebSocketError::InvalidCloseCode),
+        (frame(0x88, mask, b"\x03\xed"), WebSocketError::InvalidCloseCode),
+        (frame(0x88, mask, b"\x03\xe8\xff"), WebSocketError::InvalidUtf8),
+        ([frame(0x88, mask, b""), frame(0x81, mask, b"hi")].concat(), WebSocketError::DataAfterClose),
+    ];
+    for &(ref data, err) in cases.iter() {
+        let mut parser = WebSocketParser::new(WebSocketRole::Server);
+        let mut cb = Callback::default();
+        assert!(parser.execute(&mut cb, data) < data.len() || parser.errno.is_some());
+        assert_eq!(parser.errno, Some(err), "{:?}", data);
+    }
+
+    // negotiated extensions may use RSV1
+    let mut parser = WebSocketParser::new(WebSocketRole::Server);
+    parser.allowed_rsv = 0b100;
+    let data = frame(0xc1, mask, b"hi");
+    assert_eq!(parser.execute(&mut Callback::default(), &data), data.len());
+}